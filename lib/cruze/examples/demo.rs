@@ -0,0 +1,225 @@
+extern crate cruze;
+
+use cruze::{window, app};
+use cruze::canvas::Color;
+use cruze::menu::{ContextMenu, Menu, MenuBar, MenuItem};
+use cruze::shortcut::{Chord, ShortcutScope};
+use cruze::split::{Pane, SplitPane};
+use cruze::tabs::{Tab, Tabs};
+use cruze::widgets::{
+    Alignment,
+    Element,
+    Label,
+    Orientation,
+    Rect,
+    WidgetOptions,
+};
+
+// A document open in the middle column
+fn document(title: &str) -> Element {
+    Rect::new(
+        WidgetOptions {
+            id: title.to_lowercase(),
+            vertical_align: Alignment::Center,
+            horizontal_align: Alignment::Center,
+            color: Color::from_rgb(0.9, 0.9, 0.9),
+            ..Default::default()
+        },
+        vec![
+            Label::new(
+                WidgetOptions {
+                    font_size: 21.0,
+                    color: Color::from_rgb(0.0, 0.0, 0.0),
+                    ..Default::default()
+                },
+                title.to_string(),
+            )
+        ]
+    )
+}
+
+// Commands of the menus, with their shortcuts. Nothing
+// handles them yet, the demo has no application.
+const COMMANDS: &[(&str, &str)] = &[
+    ("new", "Ctrl+N"),
+    ("open", "Ctrl+O"),
+    ("save", "Ctrl+S"),
+    ("quit", "Ctrl+Q"),
+];
+
+fn menus() -> Vec<Menu> {
+    vec![
+        Menu::new("&File", vec![
+            MenuItem::new("&New", ()).with_command("new"),
+            MenuItem::new("&Open...", ()).with_command("open"),
+            MenuItem::submenu("Open &Recent", vec![
+                MenuItem::new("canvas.txt", ()),
+                MenuItem::new("notes.txt", ()),
+            ]),
+            MenuItem::new("&Save", ()).with_command("save").disabled(),
+            MenuItem::separator(),
+            MenuItem::new("&Quit", ()).with_command("quit"),
+        ]),
+        Menu::new("&View", vec![
+            MenuItem::check("&Left Side", true, ()),
+            MenuItem::check("&Right Side", true, ()),
+        ]),
+    ]
+}
+
+// Top bar with the menus, bottom bar and three resizable
+// columns in between, the outer ones change color when
+// hovered and collapse when their divider is double clicked,
+// the left one has a context menu, the middle one shows the
+// documents open in tabs
+fn content() -> Element {
+    Rect::new(
+        WidgetOptions {
+            id: "main_cont".to_string(),
+            orientation: Orientation::Column,
+            ..Default::default()
+        },
+        vec![
+            Rect::new(
+                WidgetOptions {
+                    id: "top_bar".to_string(),
+                    height: stretch::style::Dimension::Points(60.0),
+                    color: Color::from_rgb(0.1, 0.1, 0.1),
+                    ..Default::default()
+                },
+                vec![
+                    MenuBar::new(
+                        WidgetOptions {
+                            id: "menu_bar".to_string(),
+                            color: Color::from_rgb(0.1, 0.1, 0.1),
+                            text_color: Color::from_rgb(1.0, 1.0, 1.0),
+                            font_size: 16.0,
+                            ..Default::default()
+                        },
+                        menus()
+                    )
+                ]
+            ),
+            SplitPane::new(
+                WidgetOptions {
+                    id: "main_content".to_string(),
+                    flex: 1.0,
+                    color: Color::from_rgb(0.9, 0.9, 0.9),
+                    text_color: Color::from_rgb(0.0, 0.0, 0.0),
+                    ..Default::default()
+                },
+                vec![
+                    Pane::new(ContextMenu::new(
+                        WidgetOptions {
+                            id: "left_side_menu".to_string(),
+                            ..Default::default()
+                        },
+                        Rect::new(
+                            WidgetOptions {
+                                padding: WidgetOptions::uniform_padding(5.0),
+                                id: "left_side".to_string(),
+                                color: Color::from_rgb(0.7, 0.7, 0.7),
+                                vertical_align: Alignment::Center,
+                                horizontal_align: Alignment::Center,
+                                ..Default::default()
+                            },
+                            vec![
+                                Label::new(
+                                    WidgetOptions {
+                                        font_size: 21.0,
+                                        ..Default::default()
+                                    },
+                                    "Left Side".to_string(),
+                                ),
+                            ]
+                        ),
+                        vec![
+                            MenuItem::new("&Refresh", ()),
+                            MenuItem::new("&Properties...", ()).disabled(),
+                        ]
+                    ))
+                    .with_min(150.0)
+                    .with_max(400.0)
+                    .collapsible(),
+                    Pane::new(Tabs::new(
+                        WidgetOptions {
+                            id: "documents".to_string(),
+                            color: Color::from_rgb(0.9, 0.9, 0.9),
+                            text_color: Color::from_rgb(0.0, 0.0, 0.0),
+                            ..Default::default()
+                        },
+                        vec![
+                            Tab::new("canvas", "Canvas", document("Canvas")),
+                            Tab::new("notes", "Notes", document("Notes")),
+                        ]
+                    ))
+                    .with_min(200.0),
+                    Pane::new(Rect::new(
+                        WidgetOptions {
+                            padding: WidgetOptions::uniform_padding(5.0),
+                            id: "right_side".to_string(),
+                            color: Color::from_rgb(0.7, 0.7, 0.7),
+                            vertical_align: Alignment::Center,
+                            horizontal_align: Alignment::Center,
+                            ..Default::default()
+                        },
+                        vec![
+                            Label::new(
+                                WidgetOptions {
+                                    font_size: 21.0,
+                                    vertical_align: Alignment::Center,
+                                    horizontal_align: Alignment::Center,
+                                    ..Default::default()
+                                },
+                                "Right Side".to_string(),
+                            )
+                        ]
+                    ))
+                    .with_min(150.0)
+                    .collapsible(),
+                ]
+            )
+            .with_ratios(vec![0.3, 0.4, 0.3]),
+            Rect::new(
+                WidgetOptions {
+                    id: "bottom_bar".to_string(),
+                    height: stretch::style::Dimension::Points(60.0),
+                    horizontal_align: Alignment::Center,
+                    vertical_align: Alignment::Center,
+                    color: Color::from_rgb(0.1, 0.1, 0.1),
+                    ..Default::default()
+                },
+                vec![
+                    Label::new(
+                        WidgetOptions {
+                            font_size: 16.0,
+                            ..Default::default()
+                        },
+                        "Bottom Bar".to_string()
+                    )
+                ]
+            )
+        ]
+    )
+}
+
+fn main() {
+    let mut app = app::App::new();
+
+    let options = window::WindowOptions::new("Layout demo")
+        .with_size(800, 600);
+
+    let handle = app.handle();
+
+    for (command, chord) in COMMANDS.iter() {
+        handle.bind_shortcut(command, Chord::parse(chord).unwrap(), ShortcutScope::Global, ()).unwrap();
+    }
+
+    let window = window::Window::new(&app.el, options, content());
+
+    app.add_window(window);
+
+    if let Err(e) = app.run() {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,443 @@
+use lyon::math::{
+    Point,
+    Vector,
+    point,
+    vector,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+};
+
+use super::widgets::WidgetOptions;
+
+use std::time::{
+    Duration,
+    Instant,
+};
+
+// Maps the elapsed fraction of an animation (0.0 - 1.0)
+// to how far the value has gone towards its target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    // Control points of a CSS-like cubic bezier,
+    // the curve goes from (0, 0) to (1, 1)
+    CubicBezier(f32, f32, f32, f32),
+    // Damped spring of unit mass, one second of spring time is
+    // squeezed into the duration of the transition. It overshoots
+    // when underdamped and is pinned to the target at the end.
+    Spring {
+        stiffness: f32,
+        damping: f32,
+    },
+}
+
+impl Easing {
+    pub fn ease() -> Easing {
+        Easing::CubicBezier(0.25, 0.1, 0.25, 1.0)
+    }
+
+    pub fn ease_in() -> Easing {
+        Easing::CubicBezier(0.42, 0.0, 1.0, 1.0)
+    }
+
+    pub fn ease_out() -> Easing {
+        Easing::CubicBezier(0.0, 0.0, 0.58, 1.0)
+    }
+
+    pub fn ease_in_out() -> Easing {
+        Easing::CubicBezier(0.42, 0.0, 0.58, 1.0)
+    }
+
+    pub fn apply(&self, t: f32) -> f32 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+
+        if t >= 1.0 {
+            return 1.0;
+        }
+
+        match *self {
+            Easing::Linear => t,
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Spring { stiffness, damping } => spring(stiffness, damping, t),
+        }
+    }
+}
+
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+    // x(s) is monotonic for x1, x2 in 0.0 - 1.0,
+    // so s can be found by bisection
+    let (mut low, mut high) = (0.0, 1.0);
+    let mut s = t;
+
+    for _ in 0..32 {
+        let x = bezier(x1, x2, s);
+
+        if (x - t).abs() < 1e-5 {
+            break;
+        }
+
+        if x < t {
+            low = s;
+        } else {
+            high = s;
+        }
+
+        s = (low + high) / 2.0;
+    }
+
+    bezier(y1, y2, s)
+}
+
+fn spring(stiffness: f32, damping: f32, t: f32) -> f32 {
+    let omega = stiffness.max(0.0).sqrt();
+    let zeta = damping / (2.0 * omega);
+
+    if omega == 0.0 {
+        return t;
+    }
+
+    if zeta < 1.0 {
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let decay = (-zeta * omega * t).exp();
+
+        1.0 - decay * ((omega_d * t).cos() + zeta * omega / omega_d * (omega_d * t).sin())
+    } else {
+        // Critically damped or slower, no oscillation
+        1.0 - (1.0 + omega * t) * (-omega * t).exp()
+    }
+}
+
+// Values that can be tweened, `t` is 0.0 for `self`
+// and 1.0 for `other` and may go past both with a spring
+pub trait Lerp {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, other: &Color, t: f32) -> Color {
+        let from = self.to_vec();
+        let to = other.to_vec();
+
+        Color::from_rgba(
+            from.x.lerp(&to.x, t),
+            from.y.lerp(&to.y, t),
+            from.z.lerp(&to.z, t),
+            from.w.lerp(&to.w, t)
+        )
+    }
+}
+
+impl Lerp for Point {
+    fn lerp(&self, other: &Point, t: f32) -> Point {
+        point(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+    }
+}
+
+impl Lerp for Vector {
+    fn lerp(&self, other: &Vector, t: f32) -> Vector {
+        vector(self.x.lerp(&other.x, t), self.y.lerp(&other.y, t))
+    }
+}
+
+impl Lerp for Size<f32> {
+    fn lerp(&self, other: &Size<f32>, t: f32) -> Size<f32> {
+        Size::new(self.width.lerp(&other.width, t), self.height.lerp(&other.height, t))
+    }
+}
+
+// Applied to a widget around its center, after layout,
+// so that it does not move anything else
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector,
+    pub scale: f32,
+    // In degrees, counterclockwise
+    pub rotation: f32,
+}
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform {
+            translation: vector(0.0, 0.0),
+            scale: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    pub fn translate(mut self, x: f32, y: f32) -> Transform {
+        self.translation = vector(x, y);
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Transform {
+        self.scale = scale;
+        self
+    }
+
+    pub fn rotate(mut self, degrees: f32) -> Transform {
+        self.rotation = degrees;
+        self
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::identity()
+    }
+
+    pub fn matrix(&self, center: Point) -> cgmath::Matrix4<f32> {
+        let to_center = cgmath::Matrix4::from_translation(cgmath::Vector3::new(
+            center.x + self.translation.x,
+            center.y + self.translation.y,
+            0.0
+        ));
+        let from_center = cgmath::Matrix4::from_translation(cgmath::Vector3::new(-center.x, -center.y, 0.0));
+        let rotation = cgmath::Matrix4::from_angle_z(cgmath::Deg(self.rotation));
+        let scale = cgmath::Matrix4::from_scale(self.scale);
+
+        to_center * rotation * scale * from_center
+    }
+
+    // Bounding box of the rectangle at `position` once transformed
+    pub fn bounds(&self, position: Point, size: Size<f32>) -> lyon::math::Rect {
+        let center = point(position.x + size.width / 2.0, position.y + size.height / 2.0);
+        let matrix = self.matrix(center);
+
+        let corners: [Point; 4] = [
+            point(position.x, position.y),
+            point(position.x + size.width, position.y),
+            point(position.x, position.y + size.height),
+            point(position.x + size.width, position.y + size.height),
+        ];
+
+        let corners: Vec<Point> = corners
+            .iter()
+            .map(|corner| {
+                let transformed = matrix * cgmath::Vector4::new(corner.x, corner.y, 0.0, 1.0);
+
+                point(transformed.x, transformed.y)
+            })
+            .collect();
+
+        lyon::math::Rect::from_points(corners.iter())
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::identity()
+    }
+}
+
+impl Lerp for Transform {
+    fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            translation: Lerp::lerp(&self.translation, &other.translation, t),
+            scale: self.scale.lerp(&other.scale, t),
+            rotation: self.rotation.lerp(&other.rotation, t),
+        }
+    }
+}
+
+// How a property reaches a new value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Transition {
+    pub fn new(milliseconds: u64) -> Transition {
+        Transition {
+            duration: Duration::from_millis(milliseconds),
+            easing: Easing::ease(),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Transition {
+        self.easing = easing;
+        self
+    }
+}
+
+// A value going from where it was to its target. The current
+// value only changes when sampled, so that everything drawn
+// in a frame agrees on the time.
+pub struct Animated<T> {
+    from: T,
+    to: T,
+    current: T,
+    start: Instant,
+    transition: Option<Transition>,
+}
+
+impl<T: Lerp + Clone + PartialEq> Animated<T> {
+    pub fn new(value: T) -> Animated<T> {
+        Animated {
+            from: value.clone(),
+            to: value.clone(),
+            current: value,
+            start: Instant::now(),
+            transition: None,
+        }
+    }
+
+    // Starts from the current value, even mid way through another
+    // animation. Without a transition the target is reached at once.
+    pub fn set(&mut self, target: T, transition: Option<Transition>) {
+        if target == self.to {
+            return;
+        }
+
+        self.from = self.current.clone();
+        self.to = target;
+        self.start = Instant::now();
+        self.transition = transition;
+
+        if self.transition.is_none() {
+            self.current = self.to.clone();
+        }
+    }
+
+    // Moves the current value to where it is at `now`,
+    // returns whether it changed
+    pub fn sample(&mut self, now: Instant) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+
+        let current = match self.transition {
+            Some(transition) if now < self.start + transition.duration => {
+                let elapsed = now.duration_since(self.start).as_secs_f32();
+                let t = elapsed / transition.duration.as_secs_f32();
+
+                self.from.lerp(&self.to, transition.easing.apply(t))
+            },
+            _ => self.to.clone(),
+        };
+
+        let changed = current != self.current;
+
+        self.current = current;
+
+        changed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.current != self.to
+    }
+
+    // While running, the next animation frame
+    pub fn next_tick(&self) -> Option<Instant> {
+        if self.is_running() {
+            Some(self.start)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.current
+    }
+
+    pub fn target(&self) -> &T {
+        &self.to
+    }
+}
+
+impl Animated<Point> {
+    // Moves the whole animation, for widgets carried along
+    // by their parent (scrolling) rather than laid out again
+    pub fn translate(&mut self, delta: Vector) {
+        self.from += delta;
+        self.to += delta;
+        self.current += delta;
+    }
+}
+
+// The paint-only properties of `WidgetOptions` and the layout
+// box of a widget, following them according to `options.transition`
+pub struct AnimatedStyle {
+    pub color: Animated<Color>,
+    pub radius: Animated<f32>,
+    pub opacity: Animated<f32>,
+    pub transform: Animated<Transform>,
+    pub position: Animated<Point>,
+    pub size: Animated<Size<f32>>,
+    // The first layout is not animated
+    placed: bool,
+}
+
+impl Default for AnimatedStyle {
+    fn default() -> AnimatedStyle {
+        AnimatedStyle::new(Color::default(), &WidgetOptions::default())
+    }
+}
+
+impl AnimatedStyle {
+    // `color` is the one the widget currently shows,
+    // depending on its state (hovered, pressed...)
+    pub fn new(color: Color, options: &WidgetOptions) -> AnimatedStyle {
+        AnimatedStyle {
+            color: Animated::new(color),
+            radius: Animated::new(options.radius),
+            opacity: Animated::new(options.opacity),
+            transform: Animated::new(options.transform),
+            position: Animated::new(point(0.0, 0.0)),
+            size: Animated::new(Size::new(0.0, 0.0)),
+            placed: false,
+        }
+    }
+
+    pub fn animate_to(&mut self, color: Color, options: &WidgetOptions) {
+        self.color.set(color, options.transition);
+        self.radius.set(options.radius, options.transition);
+        self.opacity.set(options.opacity, options.transition);
+        self.transform.set(options.transform, options.transition);
+    }
+
+    // The box computed by a new layout
+    pub fn place(&mut self, position: Point, size: Size<f32>, options: &WidgetOptions) {
+        let transition = if self.placed { options.transition } else { None };
+
+        self.position.set(position, transition);
+        self.size.set(size, transition);
+        self.placed = true;
+    }
+
+    pub fn sample(&mut self, now: Instant) -> bool {
+        // No short circuit, every property has to move
+        self.color.sample(now) |
+            self.radius.sample(now) |
+            self.opacity.sample(now) |
+            self.transform.sample(now) |
+            self.position.sample(now) |
+            self.size.sample(now)
+    }
+
+    pub fn next_tick(&self) -> Option<Instant> {
+        [
+            self.color.next_tick(),
+            self.radius.next_tick(),
+            self.opacity.next_tick(),
+            self.transform.next_tick(),
+            self.position.next_tick(),
+            self.size.next_tick(),
+        ].iter().filter_map(|tick| *tick).min()
+    }
+}
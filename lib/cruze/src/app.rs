@@ -1,94 +1,486 @@
 use super::window;
+use super::application::{
+    Application,
+    AnyApplication,
+};
+use super::widgets::Element;
+use super::timer::{
+    TimerId,
+    Timers,
+};
+use super::executor::{
+    self,
+    Executor,
+    TaskHandle,
+    TaskId,
+};
+use super::shortcut::{
+    Chord,
+    ShortcutScope,
+    Shortcuts,
+};
+use super::clipboard::Clipboard;
 
+use std::any::Any;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
 
 use glutin::window::WindowId;
 
 use glutin::event::{
     Event,
     WindowEvent,
-    KeyboardInput,
-    //VirtualKeyCode,
-    ElementState::Pressed
+    ElementState,
+};
+
+use glutin::event_loop::{
+    ControlFlow,
+    EventLoop,
+    EventLoopProxy,
 };
+use glutin::platform::desktop::EventLoopExtDesktop;
+
+// When `App::run` returns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitPolicy {
+    // Once no window is left open
+    LastWindowClosed,
+    // Once the first window added to the app is closed,
+    // the others (inspectors, palettes...) go with it
+    MainWindowClosed,
+    // Only through `AppHandle::exit`
+    Explicit,
+}
+
+// What other threads send to the event loop through a `Proxy`
+pub struct AppEvent(AppEventKind);
+
+enum AppEventKind {
+    Message(WindowId, Box<dyn Any + Send>),
+    // A task has to be polled again
+    Wake(TaskId),
+    // An `AppHandle` queued commands
+    Commands,
+}
+
+// Wakes the event loop up from any thread, to hand a message
+// to the application of a window. Worker threads doing I/O
+// report back through one of these.
+#[derive(Clone)]
+pub struct Proxy {
+    // `EventLoopProxy` is only Clone for Clone events
+    proxy: Arc<Mutex<EventLoopProxy<AppEvent>>>,
+}
+
+impl Proxy {
+    // Fails once the event loop is gone
+    pub fn send<M: Send + 'static>(&self, window: WindowId, message: M) -> Result<(), String> {
+        self.proxy
+            .lock()
+            .map_err(|e| e.to_string())?
+            .send_event(AppEvent(AppEventKind::Message(window, Box::new(message))))
+            .map_err(|_| "The event loop is closed".to_string())
+    }
+
+    fn wake(&self, task: TaskId) {
+        self.send_event(AppEventKind::Wake(task));
+    }
+
+    fn send_event(&self, event: AppEventKind) {
+        if let Ok(proxy) = self.proxy.lock() {
+            // Nothing is left to do once the loop is gone
+            let _ = proxy.send_event(AppEvent(event));
+        }
+    }
+}
+
+enum AppCommand {
+    OpenWindow(window::WindowOptions, Element, Option<Box<dyn AnyApplication>>),
+    CloseWindow(WindowId),
+    Exit,
+}
+
+// Lets code running inside the event loop (an `Application`
+// for instance) open and close windows once `App::run` has
+// taken the app. Commands are carried out once the event
+// loop gets to the user event waking it up.
+#[derive(Clone)]
+pub struct AppHandle {
+    commands: Rc<RefCell<Vec<AppCommand>>>,
+    proxy: Proxy,
+    timers: Rc<RefCell<Timers>>,
+    executor: Rc<RefCell<Executor>>,
+    shortcuts: Rc<RefCell<Shortcuts>>,
+    clipboard: Rc<RefCell<Clipboard>>,
+}
+
+impl AppHandle {
+    fn push(&self, command: AppCommand) {
+        self.commands.borrow_mut().push(command);
+        self.proxy.send_event(AppEventKind::Commands);
+    }
 
-use glutin::event_loop::ControlFlow;
+    pub fn open_window(&self, options: window::WindowOptions, root: Element) {
+        self.push(AppCommand::OpenWindow(options, root, None));
+    }
+
+    // The window content is the view of `application`
+    pub fn open_application_window<A: Application>(&self, options: window::WindowOptions, application: A) {
+        let root = application.view();
+
+        self.push(AppCommand::OpenWindow(options, root, Some(Box::new(application))));
+    }
+
+    pub fn close_window(&self, id: WindowId) {
+        self.push(AppCommand::CloseWindow(id));
+    }
+
+    pub fn exit(&self) {
+        self.push(AppCommand::Exit);
+    }
+
+    // `message` is handed once to the application of `window`
+    // after `delay`
+    pub fn set_timeout<M: Clone + 'static>(&self, window: WindowId, delay: Duration, message: M) -> TimerId {
+        self.timers.borrow_mut().schedule(window, delay, None, message)
+    }
+
+    // `message` is handed to the application of `window`
+    // every `interval`, until the timer is cancelled
+    pub fn set_interval<M: Clone + 'static>(&self, window: WindowId, interval: Duration, message: M) -> TimerId {
+        self.timers.borrow_mut().schedule(window, interval, Some(interval), message)
+    }
+
+    pub fn cancel_timer(&self, id: TimerId) {
+        self.timers.borrow_mut().cancel(id);
+    }
+
+    // Runs `future` on the UI thread, its output is handed to the
+    // application of `window` as a message. The task is cancelled
+    // if the window is closed first or the handle is dropped.
+    pub fn spawn<M: 'static, F: Future<Output = M> + 'static>(&self, window: WindowId, future: F) -> TaskHandle {
+        let id = self.executor.borrow_mut().spawn(window, future);
+
+        TaskHandle::new(id, Rc::downgrade(&self.executor))
+    }
+
+    // `message` is handed to the application of the window
+    // where `chord` is pressed and no widget used it. Fails when
+    // `command` or `chord` in `scope` is already bound.
+    pub fn bind_shortcut<M: Clone + 'static>(&self, command: &str, chord: Chord, scope: ShortcutScope, message: M) -> Result<(), String> {
+        self.shortcuts.borrow_mut().bind(command, chord, scope, message)
+    }
+
+    pub fn unbind_shortcut(&self, command: &str) {
+        self.shortcuts.borrow_mut().unbind(command);
+    }
+
+    // What menus show next to the command
+    pub fn shortcut(&self, command: &str) -> Option<Chord> {
+        self.shortcuts.borrow().chord(command)
+    }
+
+    // What is on the clipboard, see `Clipboard`
+    pub fn clipboard_text(&self) -> Option<String> {
+        self.clipboard.borrow_mut().text()
+    }
+
+    pub fn set_clipboard_text(&self, text: &str) {
+        self.clipboard.borrow_mut().set_text(text.to_string());
+    }
+}
 
 pub struct App {
-    pub el: glutin::event_loop::EventLoop<()>,
-    windows: HashMap<WindowId, window::Window>
+    pub el: EventLoop<AppEvent>,
+    windows: HashMap<WindowId, window::Window>,
+    main_window: Option<WindowId>,
+    exit_policy: ExitPolicy,
+    commands: Rc<RefCell<Vec<AppCommand>>>,
+    timers: Rc<RefCell<Timers>>,
+    executor: Rc<RefCell<Executor>>,
+    shortcuts: Rc<RefCell<Shortcuts>>,
+    clipboard: Rc<RefCell<Clipboard>>,
 }
 
 impl App {
     pub fn new() -> App {
-        let el = glutin::event_loop::EventLoop::new();
+        let el = EventLoop::new_user_event();
         let windows = HashMap::new();
 
+        let proxy = Proxy {
+            proxy: Arc::new(Mutex::new(el.create_proxy())),
+        };
+        let executor = Executor::new(Arc::new(move |task| proxy.wake(task)));
+
         App {
             el: el,
-            windows: windows
+            windows: windows,
+            main_window: None,
+            exit_policy: ExitPolicy::LastWindowClosed,
+            commands: Rc::new(RefCell::new(vec![])),
+            timers: Rc::new(RefCell::new(Timers::new())),
+            executor: Rc::new(RefCell::new(executor)),
+            shortcuts: Rc::new(RefCell::new(Shortcuts::new())),
+            clipboard: Rc::new(RefCell::new(Clipboard::new())),
+        }
+    }
+
+    pub fn set_exit_policy(&mut self, exit_policy: ExitPolicy) {
+        self.exit_policy = exit_policy;
+    }
+
+    pub fn proxy(&self) -> Proxy {
+        Proxy {
+            proxy: Arc::new(Mutex::new(self.el.create_proxy())),
         }
     }
 
-    pub fn run(self) {
-        let mut windows = self.windows;
+    pub fn handle(&self) -> AppHandle {
+        AppHandle {
+            commands: self.commands.clone(),
+            proxy: self.proxy(),
+            timers: self.timers.clone(),
+            executor: self.executor.clone(),
+            shortcuts: self.shortcuts.clone(),
+            clipboard: self.clipboard.clone(),
+        }
+    }
 
-        for (_id, window) in windows.iter_mut() {
+    // Returns once the exit policy says so, or with the error
+    // of a window that could not be drawn anymore
+    pub fn run(mut self) -> Result<(), String> {
+        for (_id, window) in self.windows.iter_mut() {
             // Set only if window is visible
-            window.resize(window.get_size());
-            window.draw();
+            window.resize(window.get_size())?;
+            window.draw()?;
         }
 
-        self.el.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Wait;
+        let mut exit = false;
+        let mut result = Ok(());
 
-            match event {
-                Event::LoopDestroyed => return,
-                Event::WindowEvent { event, window_id } => {
-                    let window = windows
-                        .get_mut(&window_id);
+        // With this version of glutin windows can only be created
+        // with the event loop itself at hand, so the loop is left
+        // to open them and entered again afterwards
+        let mut opening = vec![];
 
-                    match window {
-                        Some(window) => {
-                            match event {
-                                WindowEvent::Resized(logical_size) => {
-                                    window.resize(logical_size);
-                                },
-                                WindowEvent::RedrawRequested => {
-                                    window.draw();
-                                },
-                                WindowEvent::KeyboardInput { input, .. } => {
-                                    match input.state {
-                                        Pressed => {
-                                            window.send_key(input.virtual_keycode.unwrap());
-                                        },
-                                        Released => {
-                                        }
+        while !exit {
+            let App { el, windows, main_window, exit_policy, commands, timers, executor, shortcuts, clipboard } = &mut self;
+
+            el.run_return(|event, _, control_flow| {
+                let outcome = match event {
+                    Event::WindowEvent { event, window_id } => {
+                        let window = match windows.get_mut(&window_id) {
+                            Some(window) => window,
+                            // Events still in flight for a closed window
+                            None => return,
+                        };
+
+                        match event {
+                            WindowEvent::Resized(logical_size) => window.resize(logical_size),
+                            WindowEvent::RedrawRequested => window.draw(),
+                            WindowEvent::KeyboardInput { input, .. } => {
+                                match (input.state, input.virtual_keycode) {
+                                    (ElementState::Pressed, Some(key)) => {
+                                        window.send_key(key, input.modifiers);
+                                    },
+                                    _ => {
+                                        window.set_modifiers(input.modifiers);
                                     }
+                                }
+
+                                Ok(())
+                            },
+                            WindowEvent::ReceivedCharacter(c) => {
+                                window.send_char(c);
+
+                                Ok(())
+                            },
+                            WindowEvent::MouseInput { state, button, modifiers, .. } => {
+                                window.set_modifiers(modifiers);
+                                window.send_mouse_input(state, button);
+
+                                Ok(())
+                            },
+                            WindowEvent::CursorMoved { position, modifiers, .. } => {
+                                window.set_modifiers(modifiers);
+                                window.set_cursor_position(position.x, position.y);
+
+                                Ok(())
+                            },
+                            WindowEvent::MouseWheel { delta, phase, modifiers, .. } => {
+                                window.set_modifiers(modifiers);
+                                window.send_scroll(delta, phase);
+
+                                Ok(())
+                            },
+                            WindowEvent::CloseRequested => {
+                                timers.borrow_mut().cancel_window(window_id);
+                                executor.borrow_mut().cancel_window(window_id);
+
+                                if close_window(windows, *main_window, *exit_policy, window_id) {
+                                    exit = true;
+                                }
+
+                                Ok(())
+                            },
+                            _ => Ok(()),
+                        }
+                    },
+                    Event::UserEvent(AppEvent(AppEventKind::Message(window_id, message))) => {
+                        if let Some(window) = windows.get_mut(&window_id) {
+                            window.send_message(message);
+                        }
+
+                        Ok(())
+                    },
+                    Event::UserEvent(AppEvent(AppEventKind::Wake(task))) => {
+                        if let Some((window_id, message)) = executor::poll(executor, task) {
+                            if let Some(window) = windows.get_mut(&window_id) {
+                                window.send_message(message);
+                            }
+                        }
+
+                        Ok(())
+                    },
+                    Event::UserEvent(AppEvent(AppEventKind::Commands)) => {
+                        let pending: Vec<AppCommand> = commands.borrow_mut().drain(..).collect();
+
+                        for command in pending {
+                            match command {
+                                AppCommand::OpenWindow(options, root, application) => {
+                                    opening.push((options, root, application));
                                 },
-                                WindowEvent::MouseInput { state, button, .. } => {
-                                    window.send_mouse_input(state, button);
-                                },
-                                WindowEvent::CursorMoved { position, .. } => {
-                                    window.set_cursor_position(position.x, position.y);
-                                },
-                                WindowEvent::CloseRequested => {
-                                    *control_flow = ControlFlow::Exit
+                                AppCommand::CloseWindow(id) => {
+                                    timers.borrow_mut().cancel_window(id);
+                                    executor.borrow_mut().cancel_window(id);
+
+                                    if close_window(windows, *main_window, *exit_policy, id) {
+                                        exit = true;
+                                    }
                                 },
+                                AppCommand::Exit => {
+                                    exit = true;
+                                }
+                            }
+                        }
+
+                        Ok(())
+                    },
+                    // A deadline was reached, or passed while
+                    // the loop was busy with input
+                    Event::NewEvents(_) => {
+                        let now = Instant::now();
+
+                        // Taken out first, the application may schedule
+                        // new timers while handling the messages
+                        let due = timers.borrow_mut().take_due(now);
+
+                        for (window_id, message) in due {
+                            if let Some(window) = windows.get_mut(&window_id) {
+                                window.send_message(message);
+                            }
+                        }
+
+                        for (_id, window) in windows.iter_mut() {
+                            match window.next_tick() {
+                                Some(tick) if tick <= now => window.tick(now),
                                 _ => (),
-                            };
-                        },
-                        None => {
-                            println!("Qualcosa è andato storto :(");
+                            }
                         }
-                    }
-                },
-                _ => (),
+
+                        Ok(())
+                    },
+                    // Nothing ticks without input unless
+                    // a timer or a widget asked for it
+                    Event::EventsCleared => {
+                        let next_tick = windows
+                            .values()
+                            .filter_map(|window| window.next_tick())
+                            .chain(timers.borrow().next_deadline())
+                            .min();
+
+                        *control_flow = match next_tick {
+                            Some(tick) if tick <= Instant::now() => ControlFlow::Poll,
+                            Some(tick) => ControlFlow::WaitUntil(tick),
+                            None => ControlFlow::Wait,
+                        };
+
+                        Ok(())
+                    },
+                    _ => Ok(()),
+                };
+
+                if let Err(e) = outcome {
+                    result = Err(e);
+                    exit = true;
+                }
+
+                if exit || !opening.is_empty() {
+                    *control_flow = ControlFlow::Exit;
+                }
+            });
+
+            if exit {
+                break;
+            }
+
+            for (options, root, application) in opening.drain(..) {
+                let mut window = window::Window::new(el, options, root);
+
+                window.set_clipboard(clipboard.clone());
+                window.set_shortcuts(shortcuts.clone());
+
+                if let Some(application) = application {
+                    window.set_any_application(application);
+                }
+
+                window.resize(window.get_size())?;
+                window.draw()?;
+
+                windows.insert(window.id, window);
             }
-        });
+        }
+
+        result
     }
 
-    pub fn add_window(&mut self, window: window::Window) {
+    pub fn add_window(&mut self, mut window: window::Window) {
+        window.set_clipboard(self.clipboard.clone());
+        window.set_shortcuts(self.shortcuts.clone());
+
+        if self.main_window.is_none() {
+            self.main_window = Some(window.id);
+        }
+
         self.windows.insert(window.id, window);
     }
 }
+
+// Drops the window along with its GL resources,
+// returns whether the app has to exit
+fn close_window(
+    windows: &mut HashMap<WindowId, window::Window>,
+    main_window: Option<WindowId>,
+    exit_policy: ExitPolicy,
+    id: WindowId
+) -> bool {
+    if windows.remove(&id).is_none() {
+        return false;
+    }
+
+    match exit_policy {
+        ExitPolicy::LastWindowClosed => windows.is_empty(),
+        ExitPolicy::MainWindowClosed => main_window == Some(id),
+        ExitPolicy::Explicit => false,
+    }
+}
@@ -0,0 +1,45 @@
+use super::widgets::Element;
+
+use std::any::Any;
+
+// Business logic of a cruze program. Widgets emit messages,
+// which are handed to `update`, then `view` is called again
+// and the result reconciled with what is on screen.
+pub trait Application: 'static {
+    type Message: 'static;
+
+    fn update(&mut self, message: Self::Message);
+    fn view(&self) -> Element;
+    // Layers stacked on top of the view, bottom first, like the
+    // dialogs currently open. See `dialog::Dialog`.
+    fn overlays(&self) -> Vec<Element> {
+        vec![]
+    }
+}
+
+// Object safe version of `Application`, so that a
+// window can hold any of them
+pub trait AnyApplication {
+    fn update(&mut self, message: Box<dyn Any>);
+    fn view(&self) -> Element;
+    fn overlays(&self) -> Vec<Element>;
+}
+
+impl<A: Application> AnyApplication for A {
+    // Messages of another type are dropped: with several windows,
+    // a global shortcut, a timer or a `Proxy` can send one meant
+    // for the application of another window
+    fn update(&mut self, message: Box<dyn Any>) {
+        if let Ok(message) = message.downcast::<A::Message>() {
+            Application::update(self, *message);
+        }
+    }
+
+    fn view(&self) -> Element {
+        Application::view(self)
+    }
+
+    fn overlays(&self) -> Vec<Element> {
+        Application::overlays(self)
+    }
+}
@@ -3,6 +3,8 @@ extern crate lyon;
 use lyon::math::{
     point,
     Point,
+    rect,
+    Rect,
     vector,
     Vector,
     Angle,
@@ -32,12 +34,14 @@ use super::font_manager::{
     GlyphTexData
 };
 
-use super::widgets::{
-    Widget,
-    Rect
-};
+use rusttype::PositionedGlyph;
+
+use super::widgets::Widget;
 
-#[derive(Debug, Clone, Copy, Default)]
+use std::collections::HashMap;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct Color {
     r: f32,
     g: f32,
@@ -66,9 +70,21 @@ impl Color {
     pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
         Color::new(r, g, b, a)
     }
+
+    // Moves the color towards black by `amount` (0.0 - 1.0)
+    pub fn darker(&self, amount: f32) -> Color {
+        let factor = 1.0 - amount;
+
+        Color::new(self.r * factor, self.g * factor, self.b * factor, self.a)
+    }
+
+    // Scales the alpha by `opacity` (0.0 - 1.0)
+    pub fn with_opacity(&self, opacity: f32) -> Color {
+        Color::new(self.r, self.g, self.b, self.a * opacity)
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct Size<T> {
     pub width: T,
     pub height: T,
@@ -206,13 +222,248 @@ pub fn model(&self) -> cgmath::Matrix4<f32> {
 }
 */
 
+// Every vertex is X, Y, U, V, PRIMITIVE INDEX
+pub const VERTEX_SIZE: usize = 5;
+
+// Every primitive is packed in PRIMITIVE_TEXELS RGBA texels:
+// first_color, last_color, (start_pos, end_pos), bbox,
+// (gradient_type, radius, is_textured, 0), model (4 columns),
+// clip (min_x, min_y, max_x, max_y in window coordinates)
+pub const PRIMITIVE_TEXELS: usize = 10;
+pub const PRIMITIVE_DATA_SIZE: usize = PRIMITIVE_TEXELS * 4;
+
+// A run of consecutive primitives that can be drawn
+// with a single indexed draw call
+#[derive(Debug, Clone, Copy)]
+pub struct Batch {
+    pub offset: usize,
+    pub count: usize,
+}
+
+// Ranges of `CanvasData` rewritten by an incremental update, in
+// vertices, indices and primitives (not in floats)
+#[derive(Debug, Clone)]
+pub struct GeometryRange {
+    pub vertices: Range<usize>,
+    pub indices: Range<usize>,
+    pub primitives: Range<usize>,
+}
+
+#[derive(Debug)]
+pub enum MeshUpdate {
+    Unchanged,
+    Partial(Vec<GeometryRange>),
+    Full,
+}
+
+// Past this many regions a frame repaints their union instead
+const MAX_DAMAGE_REGIONS: usize = 8;
+
+// Parts of the canvas, in layout coordinates, whose
+// pixels are stale and must be painted again
+#[derive(Debug, Clone)]
+pub enum Damage {
+    Full,
+    Regions(Vec<Rect>),
+}
+
+impl Damage {
+    pub fn none() -> Damage {
+        Damage::Regions(vec![])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Damage::Full => false,
+            Damage::Regions(regions) => regions.is_empty(),
+        }
+    }
+
+    // Overlapping regions are merged, so that
+    // no pixel is painted twice in a frame
+    pub fn add_rect(&mut self, region: Rect) {
+        if region.is_empty_or_negative() {
+            return;
+        }
+
+        if let Damage::Regions(regions) = self {
+            let mut region = region;
+
+            while let Some(index) = regions.iter().position(|other| other.intersects(&region)) {
+                region = region.union(&regions.remove(index));
+            }
+
+            regions.push(region);
+
+            if regions.len() > MAX_DAMAGE_REGIONS {
+                let union = regions.iter().fold(regions[0], |union, other| union.union(other));
+
+                *regions = vec![union];
+            }
+        }
+    }
+
+    pub fn add(&mut self, other: Damage) {
+        match other {
+            Damage::Full => *self = Damage::Full,
+            Damage::Regions(regions) => {
+                for region in regions {
+                    self.add_rect(region);
+                }
+            }
+        }
+    }
+}
+
+// Geometry generated by a single widget, kept around so that it
+// can be reused until the widget becomes dirty. Paths are
+// tessellated relative to `origin`, which is applied through the
+// model matrix of each primitive, so moving a widget only
+// touches its primitive data.
+#[derive(Debug)]
+pub struct WidgetMesh {
+    key: String,
+    origin: Point,
+    // Area covered by the widget, anything
+    // drawn outside of it is not tracked
+    bounds: Rect,
+    path_vertices: Vec<CtxVertex>,
+    path_indices: Vec<u32>,
+    path_ranges: Vec<(u32, u32)>,
+    glyphs: Vec<(usize, PositionedGlyph<'static>)>,
+    primitives: Vec<Primitive>,
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    primitive_data: Vec<f32>,
+    range: GeometryRange,
+    // Set by the ancestors that clip their children
+    clip: Option<Rect>,
+}
+
+impl WidgetMesh {
+    // Lays out vertices and indices in painting order, path
+    // indices are already tessellated, glyphs become two
+    // triangles each. Glyphs must be in the font cache.
+    fn build(&mut self, font_manager: &FontManager) {
+        self.vertices.clear();
+        self.indices.clear();
+
+        for vertex in self.path_vertices.iter() {
+            self.vertices.extend_from_slice(&[
+                vertex.position.x,
+                vertex.position.y,
+                0.0,
+                0.0,
+                vertex.prim_id as f32,
+            ]);
+        }
+
+        let mut glyphs = self.glyphs.iter().peekable();
+
+        for (prim_id, primitive) in self.primitives.iter_mut().enumerate() {
+            let index_offset = self.indices.len() as u32;
+
+            match primitive.kind {
+                PrimitiveType::Path => {
+                    let (start, count) = self.path_ranges[prim_id];
+                    let (start, end) = (start as usize, (start + count) as usize);
+
+                    self.indices.extend_from_slice(&self.path_indices[start..end]);
+                },
+                PrimitiveType::Text => {
+                    while let Some((_, glyph)) = glyphs.next_if(|(id, _)| *id == prim_id) {
+                        if let Some((uv_rect, s_rect)) = font_manager.glyph_rect(glyph) {
+                            let base = (self.vertices.len() / VERTEX_SIZE) as u32;
+
+                            // TL, TR, BL, BR
+                            for (x, y, u, v) in [
+                                (s_rect.min.x, s_rect.max.y, uv_rect.min.x, uv_rect.max.y),
+                                (s_rect.max.x, s_rect.max.y, uv_rect.max.x, uv_rect.max.y),
+                                (s_rect.min.x, s_rect.min.y, uv_rect.min.x, uv_rect.min.y),
+                                (s_rect.max.x, s_rect.min.y, uv_rect.max.x, uv_rect.min.y),
+                            ].iter() {
+                                self.vertices.extend_from_slice(&[
+                                    *x as f32,
+                                    *y as f32,
+                                    *u,
+                                    *v,
+                                    prim_id as f32,
+                                ]);
+                            }
+
+                            self.indices.extend_from_slice(&[
+                                base, base + 1, base + 2,
+                                base + 1, base + 3, base + 2,
+                            ]);
+                        }
+                    }
+                }
+            }
+
+            primitive.index_offset = index_offset;
+            primitive.num_vertices = self.indices.len() as u32 - index_offset;
+        }
+
+        self.pack_primitives();
+    }
+
+    fn pack_primitives(&mut self) {
+        self.primitive_data.clear();
+
+        for primitive in self.primitives.iter() {
+            primitive.pack(&mut self.primitive_data);
+        }
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+
+        for primitive in self.primitives.iter_mut() {
+            primitive.clip = clip;
+        }
+
+        self.pack_primitives();
+    }
+
+    // What is visible of the widget
+    fn visible_bounds(&self) -> Rect {
+        match self.clip {
+            Some(clip) => self.bounds.intersection(&clip).unwrap_or(Rect::zero()),
+            None => self.bounds,
+        }
+    }
+
+    fn move_to(&mut self, origin: Point) {
+        let delta = cgmath::Matrix4::from_translation(
+            cgmath::Vector3::new(origin.x - self.origin.x, origin.y - self.origin.y, 0.0)
+        );
+
+        for primitive in self.primitives.iter_mut() {
+            primitive.model = delta * primitive.model;
+        }
+
+        self.bounds = self.bounds.translate(origin - self.origin);
+        self.origin = origin;
+
+        self.pack_primitives();
+    }
+
+    fn counts(&self) -> (usize, usize, usize) {
+        (self.vertices.len() / VERTEX_SIZE, self.indices.len(), self.primitives.len())
+    }
+}
+
 #[derive(Debug)]
 pub struct CanvasData {
     pub vertices: Vec<f32>,
     pub indices: Vec<u32>,
-    pub glyph_vertices: Vec<f32>,
+    pub primitive_data: Vec<f32>,
+    // Glyph texture regions to upload since the last update
     pub glyph_tex_data: Vec<GlyphTexData>,
-    pub primitives: Vec<Primitive>,
+    pub batches: Vec<Batch>,
+    // Regions touched by the last update
+    pub damage: Damage,
+    meshes: Vec<WidgetMesh>,
 }
 
 impl CanvasData {
@@ -220,14 +471,100 @@ impl CanvasData {
         CanvasData {
             vertices: Vec::new(),
             indices: Vec::new(),
-            glyph_vertices: Vec::new(),
+            primitive_data: Vec::new(),
             glyph_tex_data: Vec::new(),
-            primitives: Vec::new(),
+            batches: Vec::new(),
+            damage: Damage::none(),
+            meshes: Vec::new(),
+        }
+    }
+
+    // Concatenates the widget meshes, rebasing vertex
+    // indices and primitive indices
+    fn assemble(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.primitive_data.clear();
+
+        let mut meshes = std::mem::take(&mut self.meshes);
+
+        for mesh in meshes.iter_mut() {
+            let (vertices, indices, primitives) = mesh.counts();
+
+            mesh.range = GeometryRange {
+                vertices: self.vertices.len() / VERTEX_SIZE..self.vertices.len() / VERTEX_SIZE + vertices,
+                indices: self.indices.len()..self.indices.len() + indices,
+                primitives: self.primitive_data.len() / PRIMITIVE_DATA_SIZE..self.primitive_data.len() / PRIMITIVE_DATA_SIZE + primitives,
+            };
+
+            self.vertices.extend_from_slice(&mesh.vertices);
+            self.indices.extend_from_slice(&mesh.indices);
+            self.primitive_data.extend_from_slice(&mesh.primitive_data);
+
+            self.write_mesh(mesh);
+        }
+
+        self.meshes = meshes;
+
+        self.build_batches();
+    }
+
+    // Copies a mesh into its range, which must
+    // already have the right size
+    fn write_mesh(&mut self, mesh: &WidgetMesh) {
+        let range = &mesh.range;
+
+        let vertices = &mut self.vertices[range.vertices.start * VERTEX_SIZE..range.vertices.end * VERTEX_SIZE];
+
+        vertices.copy_from_slice(&mesh.vertices);
+
+        for vertex in vertices.chunks_mut(VERTEX_SIZE) {
+            vertex[4] += range.primitives.start as f32;
+        }
+
+        for (index, local) in self.indices[range.indices.clone()].iter_mut().zip(mesh.indices.iter()) {
+            *index = local + range.vertices.start as u32;
+        }
+
+        self.write_primitives(mesh);
+    }
+
+    fn write_primitives(&mut self, mesh: &WidgetMesh) {
+        let range = &mesh.range.primitives;
+
+        self.primitive_data[range.start * PRIMITIVE_DATA_SIZE..range.end * PRIMITIVE_DATA_SIZE]
+            .copy_from_slice(&mesh.primitive_data);
+    }
+
+    fn build_batches(&mut self) {
+        self.batches.clear();
+
+        for mesh in self.meshes.iter() {
+            for primitive in mesh.primitives.iter() {
+                let offset = mesh.range.indices.start + primitive.index_offset as usize;
+                let count = primitive.num_vertices as usize;
+
+                if count == 0 {
+                    continue;
+                }
+
+                match self.batches.last_mut() {
+                    Some(batch) if batch.offset + batch.count == offset => {
+                        batch.count += count;
+                    },
+                    _ => {
+                        self.batches.push(Batch {
+                            offset,
+                            count
+                        });
+                    }
+                }
+            }
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Gradient {
     pub radius: f32,
     pub gradient_type: u32,
@@ -269,23 +606,26 @@ impl Gradient {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PrimitiveType {
     Text,
     Path,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Primitive {
     pub kind: PrimitiveType,
     pub gradient: Gradient,
     pub num_vertices: u32,
+    pub index_offset: u32,
     pub center: Point,
     pub model: cgmath::Matrix4<f32>,
     pub font: String,
     pub text: String,
     pub stroke_width: f32,
     pub bbox: cgmath::Vector4<f32>,
+    // Fragments outside are discarded
+    pub clip: Option<Rect>,
 }
 
 impl Primitive {
@@ -299,14 +639,45 @@ impl Primitive {
             gradient: Gradient::new(),
             stroke_width: 0.0,
             num_vertices: 0,
+            index_offset: 0,
             bbox: cgmath::Vector4::new(0.0, 0.0, 0.0, 0.0),
+            clip: None,
+        }
+    }
+
+    // Layout described by PRIMITIVE_TEXELS
+    pub fn pack(&self, data: &mut Vec<f32>) {
+        let gradient = &self.gradient;
+        let model: &[f32; 16] = self.model.as_ref();
+
+        data.extend_from_slice(&gradient.first_color.to_vec()[..]);
+        data.extend_from_slice(&gradient.last_color.to_vec()[..]);
+        data.extend_from_slice(&[
+            gradient.start_pos.x,
+            gradient.start_pos.y,
+            gradient.end_pos.x,
+            gradient.end_pos.y,
+        ]);
+        data.extend_from_slice(&self.bbox[..]);
+        data.extend_from_slice(&[
+            gradient.gradient_type as f32,
+            gradient.radius,
+            if self.kind == PrimitiveType::Text { 1.0 } else { 0.0 },
+            0.0,
+        ]);
+        data.extend_from_slice(model);
+
+        match self.clip {
+            Some(clip) => data.extend_from_slice(&[clip.min_x(), clip.min_y(), clip.max_x(), clip.max_y()]),
+            None => data.extend_from_slice(&[f32::MIN, f32::MIN, f32::MAX, f32::MAX]),
         }
     }
 }
 
 #[derive(Clone, Debug, Copy)]
 struct CtxVertex {
-    position: Point
+    position: Point,
+    prim_id: u32,
 }
 
 // Handle conversions to the gfx vertex format
@@ -317,6 +688,7 @@ impl VertexConstructor<FillVertex, CtxVertex> for CtxVertex {
 
         CtxVertex {
             position: vertex.position,
+            prim_id: self.prim_id,
         }
     }
 }
@@ -328,6 +700,7 @@ impl VertexConstructor<StrokeVertex, CtxVertex> for CtxVertex {
 
         CtxVertex {
             position: vertex.position,
+            prim_id: self.prim_id,
         }
     }
 }
@@ -357,53 +730,68 @@ pub struct Ctx {
     stroke_tess: StrokeTessellator,
     mesh: VertexBuffers<CtxVertex, u32>,
     primitives: Vec<Primitive>,
+    glyphs: Vec<(usize, PositionedGlyph<'static>)>,
     fonts: Vec<String>,
+    origin: Point,
     prim_id: usize,
     path_direction: CtxDirection,
     gradient_direction: CtxDirection,
     commands: Vec<CtxCommand>,
+    // Widget transform and opacity, applied
+    // to every primitive through its model
+    // matrix and its gradient colors
+    transform: cgmath::Matrix4<f32>,
+    opacity: f32,
 }
 
 impl Ctx {
-    fn new() -> Ctx {
+    // Coordinates passed to the drawing functions are absolute,
+    // geometry is stored relative to `origin`
+    fn new(origin: Point) -> Ctx {
         Ctx {
             fill_tess: FillTessellator::new(),
             stroke_tess: StrokeTessellator::new(),
             mesh: VertexBuffers::new(),
             primitives: vec![],
+            glyphs: vec![],
             fonts: vec![],
+            origin,
             gradient_direction: CtxDirection::GradientY,
             path_direction: CtxDirection::CW,
             prim_id: 0,
             commands: vec![],
+            transform: cgmath::Transform::one(),
+            opacity: 1.0,
         }
     }
 
     fn begin_mesh(&mut self) {
     }
 
-    fn end_mesh(mut self, font_manager: &mut FontManager) -> CanvasData {
-        let mut vertices: Vec<f32> = Vec::new();
-        let (glyph_vertices, glyph_tex_data) = font_manager.generate_glyph_vertices();
-
-        for vertex in self.mesh.vertices.iter() {
-            vertices.push(vertex.position.x);
-            vertices.push(vertex.position.y);
-            vertices.push(0.0);
-        }
-
-        let indices = self.mesh
-            .indices
+    fn end_mesh(self, key: String, bounds: Rect) -> WidgetMesh {
+        let path_ranges = self.primitives
             .iter()
-            .map(|index| *index as u32)
+            .map(|primitive| (primitive.index_offset, primitive.num_vertices))
             .collect();
 
-        CanvasData {
-            indices,
-            vertices,
+        WidgetMesh {
+            key,
+            origin: self.origin,
+            bounds,
+            path_vertices: self.mesh.vertices,
+            path_indices: self.mesh.indices,
+            path_ranges,
+            glyphs: self.glyphs,
             primitives: self.primitives,
-            glyph_vertices,
-            glyph_tex_data
+            vertices: vec![],
+            indices: vec![],
+            primitive_data: vec![],
+            range: GeometryRange {
+                vertices: 0..0,
+                indices: 0..0,
+                primitives: 0..0,
+            },
+            clip: None,
         }
     }
 
@@ -418,10 +806,12 @@ impl Ctx {
 
         let mut builder = Path::builder();
 
+        let origin = self.origin.to_vector();
+
         for command in &self.commands {
             match command {
-                CtxCommand::LineTo(p) => builder.line_to(*p),
-                CtxCommand::MoveTo(p) => builder.move_to(*p),
+                CtxCommand::LineTo(p) => builder.line_to(*p - origin),
+                CtxCommand::MoveTo(p) => builder.move_to(*p - origin),
                 CtxCommand::Gradient(gradient_type, f_c, l_c) => {
                     match gradient_type {
                         CtxDirection::GradientY => {
@@ -447,7 +837,7 @@ impl Ctx {
                         CtxDirection::GradientRadial(c, r) => {
                             current_primitive.gradient = Gradient {
                                 gradient_type: 1,
-                                start_pos: *c,
+                                start_pos: *c - origin,
                                 end_pos: vector(0.0, 0.0),
                                 radius: *r,
                                 first_color: *f_c,
@@ -461,10 +851,10 @@ impl Ctx {
                     current_primitive.stroke_width = *w;
                 },
                 CtxCommand::Arc(c, r, s, x) => {
-                    builder.arc(*c, *r, *s, *x);
+                    builder.arc(*c - origin, *r, *s, *x);
                 },
                 CtxCommand::Text(c, t) => {
-                    current_primitive.center = *c;
+                    current_primitive.center = *c - origin;
                     current_primitive.font = "dejavu".to_string();
                     current_primitive.kind = PrimitiveType::Text;
                     current_primitive.text = t.to_string();
@@ -487,7 +877,12 @@ impl Ctx {
             bbox.center().x - bbox.size.width / 2.0,
         );
 
-        current_primitive.model = cgmath::Transform::one();
+        current_primitive.model = self.transform * cgmath::Matrix4::from_translation(
+            cgmath::Vector3::new(self.origin.x, self.origin.y, 0.0)
+        );
+
+        current_primitive.gradient.first_color = current_primitive.gradient.first_color.with_opacity(self.opacity);
+        current_primitive.gradient.last_color = current_primitive.gradient.last_color.with_opacity(self.opacity);
 
         self.fonts.push(current_primitive.font.clone());
 
@@ -503,12 +898,16 @@ impl Ctx {
         let fill_options = FillOptions
             ::tolerance(0.01);
 
+        let prim_id = self.primitives.len() as u32;
+        current_primitive.index_offset = self.mesh.indices.len() as u32;
+
         let result = self.fill_tess.tessellate_path(
             &path,
             &fill_options,
             &mut BuffersBuilder::new(&mut self.mesh, |vertex : FillVertex| {
                 CtxVertex {
-                    position: vertex.position
+                    position: vertex.position,
+                    prim_id,
                 }
             }),
         );
@@ -535,12 +934,16 @@ impl Ctx {
             ::tolerance(0.01)
             .with_line_width(current_primitive.stroke_width);
 
+        let prim_id = self.primitives.len() as u32;
+        current_primitive.index_offset = self.mesh.indices.len() as u32;
+
         let result = self.stroke_tess.tessellate_path(
             &path,
             &stroke_options,
             &mut BuffersBuilder::new(&mut self.mesh, |vertex : StrokeVertex| {
                 CtxVertex {
-                    position: vertex.position
+                    position: vertex.position,
+                    prim_id,
                 }
             }),
         );
@@ -566,9 +969,13 @@ impl Ctx {
         // when dealing with text primitives
         let (_, mut current_primitive) = self.build_path();
 
-        font_manager.position_glyphs(&mut current_primitive);
+        let prim_id = self.primitives.len();
 
-        current_primitive.model = cgmath::Matrix4::from_translation(
+        for glyph in font_manager.position_glyphs(&mut current_primitive) {
+            self.glyphs.push((prim_id, glyph));
+        }
+
+        current_primitive.model = current_primitive.model * cgmath::Matrix4::from_translation(
             cgmath::Vector3::new(
                 current_primitive.center.x,
                 current_primitive.center.y,
@@ -714,7 +1121,7 @@ impl Ctx {
         self.commands.push(CtxCommand::StrokeWidth(width));
     }
 
-    fn move_to(&mut self, p: Point) {
+    pub fn move_to(&mut self, p: Point) {
         self.commands.push(CtxCommand::MoveTo(p));
     }
 
@@ -729,11 +1136,11 @@ impl Ctx {
         ));
     }
 
-    fn line_to(&mut self, p: Point) {
+    pub fn line_to(&mut self, p: Point) {
         self.commands.push(CtxCommand::LineTo(p));
     }
 
-    fn close(&mut self) {
+    pub fn close(&mut self) {
         self.commands.push(CtxCommand::Close);
     }
 }
@@ -819,14 +1226,241 @@ pub fn generate_mesh() -> CanvasData {
 }
 */
 
-pub fn generate_mesh_from_widget(children: &Vec<Box<dyn Widget>>, font_manager: &mut FontManager) -> CanvasData {
-    let mut ctx = Ctx::new();
+pub fn generate_mesh_from_widget(children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) -> CanvasData {
+    let mut canvas_data = CanvasData::new();
 
-    ctx.begin_mesh();
+    update_mesh_from_widget(&mut canvas_data, children, font_manager);
+
+    canvas_data
+}
+
+#[derive(PartialEq)]
+enum MeshState {
+    Clean,
+    Moved,
+    Rebuilt,
+}
+
+// Widgets are expected to draw inside their layout box, the
+// extra pixel covers antialiased edges
+fn widget_bounds(widget: &dyn Widget) -> Rect {
+    let position = widget.position();
+    let size = widget.size();
+    let bounds = rect(position.x, position.y, size.width, size.height);
+
+    // A transformed widget may be drawn past its layout
+    widget.transform()
+        .bounds(position, size)
+        .union(&bounds)
+        .inflate(1.0, 1.0)
+}
+
+// What `collect_meshes` gathers while walking a layer
+struct MeshCollector<'a> {
+    old_meshes: HashMap<String, WidgetMesh>,
+    meshes: Vec<(WidgetMesh, MeshState)>,
+    damage: Damage,
+    font_manager: &'a mut FontManager,
+    // Paths of the open popups met so far
+    popups: Vec<String>,
+}
+
+fn collect_meshes(widget: &mut Box<dyn Widget>, path: String, clip: Option<Rect>, collector: &mut MeshCollector) {
+    let key = format!("{}:{}#{}", path, widget.type_name(), widget.options().id);
+
+    let entry = match collector.old_meshes.remove(&key) {
+        Some(mut mesh) if !widget.is_dirty() => {
+            if mesh.origin == widget.position() && mesh.clip == clip {
+                (mesh, MeshState::Clean)
+            } else {
+                collector.damage.add_rect(mesh.visible_bounds());
+
+                mesh.move_to(widget.position());
+                mesh.set_clip(clip);
 
-    for child in children.iter() {
-        child.draw(&mut ctx, font_manager);
+                collector.damage.add_rect(mesh.visible_bounds());
+
+                (mesh, MeshState::Moved)
+            }
+        },
+        old_mesh => {
+            if let Some(old_mesh) = old_mesh {
+                collector.damage.add_rect(old_mesh.visible_bounds());
+            }
+
+            let position = widget.position();
+            let size = widget.size();
+            let center = point(position.x + size.width / 2.0, position.y + size.height / 2.0);
+
+            let mut ctx = Ctx::new(position);
+
+            ctx.transform = widget.transform().matrix(center);
+            ctx.opacity = widget.opacity();
+
+            ctx.begin_mesh();
+
+            widget.draw(&mut ctx, collector.font_manager);
+            widget.set_dirty(false);
+
+            let mut mesh = ctx.end_mesh(key, widget_bounds(widget.as_ref()));
+
+            mesh.set_clip(clip);
+
+            collector.damage.add_rect(mesh.visible_bounds());
+
+            (mesh, MeshState::Rebuilt)
+        }
+    };
+
+    collector.meshes.push(entry);
+
+    // Nested clips intersect, a child clipped
+    // away entirely gets an empty one
+    let clip = match (clip, widget.clip()) {
+        (Some(clip), Some(own)) => Some(clip.intersection(&own).unwrap_or(Rect::zero())),
+        (clip, own) => clip.or(own),
+    };
+
+    if let Some(children) = widget.children_mut() {
+        for (index, child) in children.iter_mut().enumerate() {
+            let path = format!("{}/{}", path, index);
+
+            // Left for after the rest of the layer
+            if child.is_popup() {
+                collector.popups.push(path);
+            } else {
+                collect_meshes(child, path, clip, collector);
+            }
+        }
+    }
+}
+
+// `path` as built by `collect_meshes`
+fn widget_at_path<'a>(children: &'a mut [Box<dyn Widget>], path: &str) -> &'a mut Box<dyn Widget> {
+    let mut indexes = path.split('/').map(|index| index.parse::<usize>().unwrap());
+    let mut widget = &mut children[indexes.next().unwrap()];
+
+    for index in indexes {
+        widget = &mut widget.children_mut().unwrap()[index];
+    }
+
+    widget
+}
+
+// Re-tessellates only the widgets that are dirty (or new), moves
+// the ones whose position changed and reuses everything else.
+// When sizes are unchanged the new geometry is written in place
+// and the touched ranges are returned. The screen regions that
+// changed are left in `canvas_data.damage`.
+pub fn update_mesh_from_widget(canvas_data: &mut CanvasData, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) -> MeshUpdate {
+    let old_layout: Vec<(String, (usize, usize, usize))> = canvas_data.meshes
+        .iter()
+        .map(|mesh| (mesh.key.clone(), mesh.counts()))
+        .collect();
+
+    let old_ranges: Vec<GeometryRange> = canvas_data.meshes
+        .iter()
+        .map(|mesh| mesh.range.clone())
+        .collect();
+
+    let mut collector = MeshCollector {
+        old_meshes: std::mem::take(&mut canvas_data.meshes)
+            .into_iter()
+            .map(|mesh| (mesh.key.clone(), mesh))
+            .collect(),
+        meshes: vec![],
+        damage: Damage::none(),
+        font_manager,
+        popups: vec![],
+    };
+
+    for index in 0..children.len() {
+        collector.popups.clear();
+
+        collect_meshes(&mut children[index], index.to_string(), None, &mut collector);
+
+        // Open popups are drawn above the rest of their layer,
+        // whatever their ancestors clip
+        let mut next = 0;
+
+        while next < collector.popups.len() {
+            let path = collector.popups[next].clone();
+            let popup = widget_at_path(children, &path);
+
+            collect_meshes(popup, path, None, &mut collector);
+
+            next += 1;
+        }
     }
 
-    ctx.end_mesh(font_manager)
+    let MeshCollector { old_meshes, mut meshes, mut damage, font_manager, .. } = collector;
+
+    // Widgets that are gone leave a hole behind
+    for mesh in old_meshes.values() {
+        damage.add_rect(mesh.visible_bounds());
+    }
+
+    canvas_data.damage = damage;
+
+    // Every glyph on the canvas is queued, so that none of
+    // them gets evicted from the cache
+    let glyphs: Vec<PositionedGlyph<'static>> = meshes
+        .iter()
+        .flat_map(|(mesh, _)| mesh.glyphs.iter().map(|(_, glyph)| glyph.clone()))
+        .collect();
+
+    let (glyph_tex_data, reordered) = font_manager.cache_glyphs(&glyphs);
+
+    canvas_data.glyph_tex_data = glyph_tex_data;
+
+    for (mesh, state) in meshes.iter_mut() {
+        if *state == MeshState::Rebuilt || (reordered && !mesh.glyphs.is_empty()) {
+            mesh.build(font_manager);
+            *state = MeshState::Rebuilt;
+        }
+    }
+
+    let same_layout = !reordered
+        && old_layout.len() == meshes.len()
+        && old_layout
+            .iter()
+            .zip(meshes.iter())
+            .all(|((key, counts), (mesh, _))| *key == mesh.key && *counts == mesh.counts());
+
+    if !same_layout {
+        canvas_data.meshes = meshes.into_iter().map(|(mesh, _)| mesh).collect();
+        canvas_data.assemble();
+
+        return MeshUpdate::Full;
+    }
+
+    let mut ranges = vec![];
+
+    for ((mut mesh, state), range) in meshes.into_iter().zip(old_ranges) {
+        mesh.range = range;
+
+        match state {
+            MeshState::Rebuilt => {
+                canvas_data.write_mesh(&mesh);
+                ranges.push(mesh.range.clone());
+            },
+            MeshState::Moved => {
+                canvas_data.write_primitives(&mesh);
+                ranges.push(GeometryRange {
+                    vertices: 0..0,
+                    indices: 0..0,
+                    primitives: mesh.range.primitives.clone(),
+                });
+            },
+            MeshState::Clean => ()
+        }
+
+        canvas_data.meshes.push(mesh);
+    }
+
+    if ranges.is_empty() {
+        MeshUpdate::Unchanged
+    } else {
+        MeshUpdate::Partial(ranges)
+    }
 }
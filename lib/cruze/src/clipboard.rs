@@ -0,0 +1,57 @@
+use copypasta::{
+    ClipboardContext,
+    ClipboardProvider,
+};
+
+// Text copied by widgets, shared by the windows of an `App` and
+// reachable through `AppHandle`. It is the clipboard of the
+// system, other programs see what is copied and the other way
+// around. When the system one cannot be reached (no display, as
+// in tests) the text is kept here and only the app sees it.
+pub struct Clipboard {
+    system: Option<ClipboardContext>,
+    text: Option<String>,
+}
+
+impl Default for Clipboard {
+    fn default() -> Clipboard {
+        Clipboard::new()
+    }
+}
+
+impl Clipboard {
+    pub fn new() -> Clipboard {
+        Clipboard {
+            system: ClipboardContext::new().ok(),
+            text: None,
+        }
+    }
+
+    // Never talks to the system
+    pub fn in_memory() -> Clipboard {
+        Clipboard {
+            system: None,
+            text: None,
+        }
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        if let Some(system) = &mut self.system {
+            if system.set_contents(text.clone()).is_ok() {
+                return;
+            }
+        }
+
+        self.text = Some(text);
+    }
+
+    pub fn text(&mut self) -> Option<String> {
+        if let Some(system) = &mut self.system {
+            if let Ok(text) = system.get_contents() {
+                return Some(text);
+            }
+        }
+
+        self.text.clone()
+    }
+}
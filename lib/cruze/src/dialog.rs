@@ -0,0 +1,415 @@
+use lyon::math::{
+    Point,
+    point
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::animation::Transition;
+
+use super::widgets::{
+    self,
+    Alignment,
+    Button,
+    Change,
+    Element,
+    EventCtx,
+    Label,
+    Orientation,
+    Rect,
+    TextInput,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+
+// A modal panel with a title, some content and a row of buttons,
+// centered over the rest of the window. It is meant to be returned
+// by `Application::overlays`: while it is open, input only reaches
+// the dialog. `options.color` is the color of the panel.
+pub struct Dialog {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    on_dismiss: Option<Box<dyn Fn() -> Box<dyn Any>>>,
+    on_submit: Option<Box<dyn Fn() -> Box<dyn Any>>>,
+}
+
+impl Dialog {
+    pub fn new(options: WidgetOptions, title: &str, content: Element, buttons: Vec<Element>) -> Box<Dialog> {
+        let panel = Rect::new(
+            WidgetOptions {
+                id: "dialog_panel".to_string(),
+                orientation: Orientation::Column,
+                color: options.color,
+                radius: options.radius,
+                padding: WidgetOptions::uniform_padding(16.0),
+                flex: 0.0,
+                ..Default::default()
+            },
+            vec![
+                Label::new(
+                    WidgetOptions {
+                        id: "dialog_title".to_string(),
+                        color: options.text_color,
+                        font_size: options.font_size + 4.0,
+                        margin: WidgetOptions::uniform_padding(4.0),
+                        ..Default::default()
+                    },
+                    title.to_string()
+                ),
+                content,
+                Rect::new(
+                    WidgetOptions {
+                        id: "dialog_buttons".to_string(),
+                        horizontal_align: Alignment::End,
+                        color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                        flex: 0.0,
+                        ..Default::default()
+                    },
+                    buttons
+                ),
+            ]
+        );
+
+        Box::new(Dialog {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![panel],
+            dirty: true,
+            on_dismiss: None,
+            on_submit: None,
+        })
+    }
+
+    // Message emitted when the user presses Escape
+    pub fn on_dismiss<M: Clone + 'static>(mut self: Box<Self>, message: M) -> Box<Dialog> {
+        self.on_dismiss = Some(Box::new(move || Box::new(message.clone())));
+
+        self
+    }
+
+    // Message emitted when the user presses Enter and
+    // the focused widget did not use it
+    pub fn on_submit<M: Clone + 'static>(mut self: Box<Self>, message: M) -> Box<Dialog> {
+        self.on_submit = Some(Box::new(move || Box::new(message.clone())));
+
+        self
+    }
+}
+
+impl Widget for Dialog {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        // Dims whatever is below the dialog
+        ctx.begin_primitive();
+        ctx.color(Color::from_rgba(0.0, 0.0, 0.0, 0.4));
+        ctx.rect(self.position, self.size.width, self.size.height);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        // Covers its parent, the window, whatever else is there
+        stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    end: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    bottom: Dimension::Points(0.0),
+                },
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<Dialog>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        self.on_dismiss = new.on_dismiss;
+        self.on_submit = new.on_submit;
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::KeyPress(VirtualKeyCode::Escape) => {
+                if let Some(on_dismiss) = &self.on_dismiss {
+                    ctx.emit(on_dismiss());
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::KeyPress(VirtualKeyCode::Return) | WidgetEvent::KeyPress(VirtualKeyCode::NumpadEnter) => {
+                if let Some(on_submit) = &self.on_submit {
+                    ctx.emit(on_submit());
+                    ctx.set_handled();
+                }
+            },
+            _ => ()
+        }
+    }
+
+    fn is_modal(&self) -> bool {
+        true
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Dialog"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("Dialog -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+fn dialog_options() -> WidgetOptions {
+    WidgetOptions {
+        id: "dialog".to_string(),
+        radius: 6.0,
+        font_size: 16.0,
+        ..Default::default()
+    }
+}
+
+fn dialog_text(text: &str) -> Element {
+    Label::new(
+        WidgetOptions {
+            id: "dialog_text".to_string(),
+            color: Color::from_rgb(0.0, 0.0, 0.0),
+            font_size: 16.0,
+            margin: WidgetOptions::uniform_padding(4.0),
+            ..Default::default()
+        },
+        text.to_string()
+    )
+}
+
+fn dialog_button<M: Clone + 'static>(id: &str, text: &str, message: M) -> Element {
+    Button::new(
+        WidgetOptions {
+            id: id.to_string(),
+            font_size: 16.0,
+            radius: 4.0,
+            margin: WidgetOptions::uniform_padding(4.0),
+            color: Color::from_rgb(0.3, 0.4, 0.7),
+            hover_color: Some(Color::from_rgb(0.4, 0.5, 0.8)),
+            transition: Some(Transition::new(150)),
+            text_color: Color::from_rgb(1.0, 1.0, 1.0),
+            ..Default::default()
+        },
+        text.to_string()
+    ).on_press(message)
+}
+
+// Tells the user something, `on_close` is emitted
+// by the OK button and by Escape
+pub fn alert<M: Clone + 'static>(title: &str, text: &str, on_close: M) -> Box<Dialog> {
+    Dialog::new(
+        dialog_options(),
+        title,
+        dialog_text(text),
+        vec![
+            dialog_button("ok", "OK", on_close.clone()),
+        ]
+    ).on_dismiss(on_close)
+}
+
+// Asks before doing something that cannot be undone,
+// Escape counts as cancelling
+pub fn confirm<M: Clone + 'static>(title: &str, text: &str, on_confirm: M, on_cancel: M) -> Box<Dialog> {
+    Dialog::new(
+        dialog_options(),
+        title,
+        dialog_text(text),
+        vec![
+            dialog_button("cancel", "Cancel", on_cancel.clone()),
+            dialog_button("ok", "OK", on_confirm),
+        ]
+    ).on_dismiss(on_cancel)
+}
+
+// Asks for a line of text. Like a `TextInput`, the value is owned
+// by the application: edits emit `on_change` and the view has to
+// pass the new value back. The input has the focus once the
+// dialog opens, Enter confirms and Escape cancels.
+pub fn prompt<M, F>(title: &str, text: &str, value: &str, on_change: F, on_confirm: M, on_cancel: M) -> Box<Dialog>
+where
+    M: Clone + 'static,
+    F: Fn(String) -> M + 'static
+{
+    let content = Rect::new(
+        WidgetOptions {
+            id: "dialog_content".to_string(),
+            orientation: Orientation::Column,
+            color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+            flex: 0.0,
+            ..Default::default()
+        },
+        vec![
+            dialog_text(text),
+            TextInput::new(
+                WidgetOptions {
+                    id: "dialog_input".to_string(),
+                    font_size: 16.0,
+                    radius: 4.0,
+                    margin: WidgetOptions::uniform_padding(4.0),
+                    ..Default::default()
+                },
+                value.to_string()
+            ).on_change(on_change),
+        ]
+    );
+
+    Dialog::new(
+        dialog_options(),
+        title,
+        content,
+        vec![
+            dialog_button("cancel", "Cancel", on_cancel.clone()),
+            dialog_button("ok", "OK", on_confirm.clone()),
+        ]
+    )
+    .on_dismiss(on_cancel)
+    .on_submit(on_confirm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(dialog: &mut Dialog, key: VirtualKeyCode) -> Vec<&'static str> {
+        let mut ctx = EventCtx::new();
+
+        dialog.on_event(&WidgetEvent::KeyPress(key), &mut ctx);
+
+        ctx.into_messages()
+            .into_iter()
+            .map(|message| *message.downcast::<&'static str>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn prompt_confirms_on_enter_and_cancels_on_escape() {
+        let mut dialog = prompt("Rename", "New name", "", |_| "change", "confirm", "cancel");
+
+        assert_eq!(send(&mut dialog, VirtualKeyCode::Return), vec!["confirm"]);
+        assert_eq!(send(&mut dialog, VirtualKeyCode::Escape), vec!["cancel"]);
+    }
+
+    #[test]
+    fn confirm_ignores_enter() {
+        let mut dialog = confirm("Delete", "Sure?", "confirm", "cancel");
+
+        assert!(send(&mut dialog, VirtualKeyCode::Return).is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+use glutin::window::WindowId;
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Weak;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::task::{
+    Context,
+    Poll,
+    Wake,
+    Waker,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(u64);
+
+// A spawned future, its output boxed to be sent as a message
+type TaskFuture = Pin<Box<dyn Future<Output = Box<dyn Any>>>>;
+
+struct Task {
+    // The application of this window gets the result
+    window: WindowId,
+    // None while the task is being polled
+    future: Option<TaskFuture>,
+}
+
+// Wakers may be called from any thread, they ask the
+// event loop to poll the task again
+struct TaskWaker {
+    id: TaskId,
+    wake: Arc<dyn Fn(TaskId) + Send + Sync>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        (self.wake)(self.id);
+    }
+}
+
+// Runs futures on the UI thread, polled only when woken up,
+// so a pending task costs nothing. Futures must not block:
+// blocking work goes through `spawn_blocking`.
+pub struct Executor {
+    next_id: u64,
+    tasks: HashMap<TaskId, Task>,
+    wake: Arc<dyn Fn(TaskId) + Send + Sync>,
+}
+
+impl Executor {
+    pub fn new(wake: Arc<dyn Fn(TaskId) + Send + Sync>) -> Executor {
+        Executor {
+            next_id: 0,
+            tasks: HashMap::new(),
+            wake,
+        }
+    }
+
+    pub fn spawn<M: 'static, F: Future<Output = M> + 'static>(&mut self, window: WindowId, future: F) -> TaskId {
+        let id = TaskId(self.next_id);
+
+        self.next_id += 1;
+
+        self.tasks.insert(id, Task {
+            window,
+            future: Some(Box::pin(async move {
+                Box::new(future.await) as Box<dyn Any>
+            })),
+        });
+
+        // First poll
+        (self.wake)(id);
+
+        id
+    }
+
+    pub fn cancel(&mut self, id: TaskId) {
+        self.tasks.remove(&id);
+    }
+
+    // Tasks of a closed window have nobody to report to. A task
+    // being polled is dropped as soon as it yields.
+    pub fn cancel_window(&mut self, window: WindowId) {
+        self.tasks.retain(|_, task| task.window != window);
+    }
+
+    pub fn is_running(&self, id: TaskId) -> bool {
+        self.tasks.contains_key(&id)
+    }
+}
+
+// Polls a woken task, returning its result once it is done. The
+// executor is not borrowed while polling, so the task may spawn
+// or cancel other tasks, itself included.
+pub fn poll(executor: &RefCell<Executor>, id: TaskId) -> Option<(WindowId, Box<dyn Any>)> {
+    let (mut future, waker) = {
+        let mut executor = executor.borrow_mut();
+
+        // Cancelled, or woken twice and already done
+        let future = executor.tasks.get_mut(&id).and_then(|task| task.future.take())?;
+
+        let waker = Waker::from(Arc::new(TaskWaker {
+            id,
+            wake: executor.wake.clone(),
+        }));
+
+        (future, waker)
+    };
+
+    let result = future.as_mut().poll(&mut Context::from_waker(&waker));
+
+    let mut executor = executor.borrow_mut();
+
+    // Cancelled while it was polled, by itself or because
+    // its window was closed: the future is dropped here
+    let task = executor.tasks.get_mut(&id)?;
+
+    match result {
+        Poll::Ready(message) => {
+            let window = task.window;
+
+            executor.tasks.remove(&id);
+
+            Some((window, message))
+        },
+        Poll::Pending => {
+            task.future = Some(future);
+
+            None
+        }
+    }
+}
+
+// Returned by `AppHandle::spawn`. Dropping the handle cancels
+// the task, so a task kept by a widget or by some state goes
+// away with its owner. `detach` lets it run to completion.
+#[must_use = "the task is cancelled when its handle is dropped"]
+pub struct TaskHandle {
+    id: TaskId,
+    executor: Weak<RefCell<Executor>>,
+}
+
+impl TaskHandle {
+    pub(crate) fn new(id: TaskId, executor: Weak<RefCell<Executor>>) -> TaskHandle {
+        TaskHandle {
+            id,
+            executor,
+        }
+    }
+
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    // The result of a cancelled task is never delivered
+    pub fn cancel(&self) {
+        if let Some(executor) = self.executor.upgrade() {
+            executor.borrow_mut().cancel(self.id);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        match self.executor.upgrade() {
+            Some(executor) => executor.borrow().is_running(self.id),
+            None => false,
+        }
+    }
+
+    // Fire and forget, the result is still delivered
+    // unless the window is closed first
+    pub fn detach(mut self) {
+        self.executor = Weak::new();
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+struct BlockingState<T> {
+    result: Option<std::thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+// Resolves to what `f` returns, `f` runs on a thread of its own.
+// Like joining a thread, it is an `Err` holding the panic when
+// `f` panics, so the task still completes.
+pub struct Blocking<T> {
+    state: Arc<Mutex<BlockingState<T>>>,
+}
+
+// Reading a file or querying a database from a task
+// without freezing the UI:
+// `let data = spawn_blocking(move || std::fs::read(path)).await.unwrap();`
+pub fn spawn_blocking<T: Send + 'static, F: FnOnce() -> T + Send + 'static>(f: F) -> Blocking<T> {
+    let state = Arc::new(Mutex::new(BlockingState {
+        result: None,
+        waker: None,
+    }));
+
+    let thread_state = state.clone();
+
+    std::thread::spawn(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+
+        let mut state = thread_state.lock().unwrap();
+
+        state.result = Some(result);
+
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+
+    Blocking {
+        state,
+    }
+}
+
+impl<T> Future for Blocking<T> {
+    type Output = std::thread::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::thread::Result<T>> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::rc::Rc;
+
+    // Returns `Pending` on the first poll, after
+    // closing the window the task belongs to
+    struct CloseWindow {
+        executor: Weak<RefCell<Executor>>,
+        window: WindowId,
+        dropped: Rc<RefCell<bool>>,
+    }
+
+    impl Future for CloseWindow {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<()> {
+            self.executor.upgrade().unwrap().borrow_mut().cancel_window(self.window);
+
+            Poll::Pending
+        }
+    }
+
+    impl Drop for CloseWindow {
+        fn drop(&mut self) {
+            *self.dropped.borrow_mut() = true;
+        }
+    }
+
+    fn executor() -> Rc<RefCell<Executor>> {
+        Rc::new(RefCell::new(Executor::new(Arc::new(|_| ()))))
+    }
+
+    #[test]
+    fn closing_the_window_while_polling_drops_the_task() {
+        let executor = executor();
+        let window = unsafe { WindowId::dummy() };
+        let dropped = Rc::new(RefCell::new(false));
+
+        let id = executor.borrow_mut().spawn(window, CloseWindow {
+            executor: Rc::downgrade(&executor),
+            window,
+            dropped: dropped.clone(),
+        });
+
+        assert!(poll(&executor, id).is_none());
+        assert!(!executor.borrow().is_running(id));
+        assert!(*dropped.borrow());
+    }
+
+    struct Unpark(std::thread::Thread);
+
+    impl std::task::Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+
+            std::thread::park();
+        }
+    }
+
+    #[test]
+    fn a_panicking_blocking_job_completes() {
+        assert_eq!(block_on(spawn_blocking(|| 42)).unwrap(), 42);
+        assert!(block_on(spawn_blocking(|| -> u32 { panic!("job failed") })).is_err());
+    }
+
+    #[test]
+    fn dropping_the_handle_cancels_the_task() {
+        let executor = executor();
+        let window = unsafe { WindowId::dummy() };
+
+        let running = executor.borrow_mut().spawn(window, async {});
+        let detached = executor.borrow_mut().spawn(window, async {});
+
+        drop(TaskHandle::new(running, Rc::downgrade(&executor)));
+        TaskHandle::new(detached, Rc::downgrade(&executor)).detach();
+
+        assert!(!executor.borrow().is_running(running));
+        assert!(poll(&executor, detached).is_some());
+    }
+}
@@ -5,7 +5,10 @@ use std::collections::hash_map::Entry;
 use std::iter::FromIterator;
 
 use rusttype::{
-    gpu_cache::Cache,
+    gpu_cache::{
+        Cache,
+        CachedBy,
+    },
     Font,
     Scale,
     point,
@@ -17,7 +20,6 @@ use super::canvas;
 
 pub struct FontManager {
     font_caches: HashMap<String, Font<'static>>,
-    glyphs: Vec<PositionedGlyph<'static>>,
     glyph_cache: Cache<'static>,
 }
 
@@ -31,25 +33,22 @@ impl FontManager {
     pub fn new() -> FontManager {
         FontManager {
             font_caches: HashMap::new(),
-            glyphs: Vec::new(),
-            glyph_cache: Cache::builder().build(),
+            glyph_cache: Cache::builder()
+                .dimensions(512, 512)
+                .build(),
         }
     }
 
-    pub fn position_glyphs(&mut self, primitive: &mut canvas::Primitive) {
-        let (bbox, mut result) = self.calculate_text_bbox(primitive.stroke_width, primitive.font.to_string(), &primitive.text);
+    pub fn position_glyphs(&mut self, primitive: &mut canvas::Primitive) -> Vec<PositionedGlyph<'static>> {
+        let (bbox, result) = self.calculate_text_bbox(primitive.stroke_width, primitive.font.to_string(), &primitive.text);
 
         primitive.bbox = bbox;
-        primitive.num_vertices = primitive.text.split_whitespace().collect::<String>().len() as u32;
 
-        self.glyphs.append(&mut result);
+        result
     }
 
-    pub fn calculate_text_bbox(&mut self, stroke_width: f32, font: String, text: &str)
-        -> (cgmath::Vector4<f32>, Vec<PositionedGlyph<'static>>) {
-        let scale = Scale::uniform(stroke_width);
-
-        let font = match self.font_caches.entry(font.clone()) {
+    fn font(&mut self, font: String) -> &Font<'static> {
+        match self.font_caches.entry(font) {
             Entry::Vacant(entry) => {
                 // TODO: pick the correct font with font-kit
                 let font_data = include_bytes!("../fonts/DejaVuSans.ttf");
@@ -58,7 +57,45 @@ impl FontManager {
                 entry.insert(font)
             },
             Entry::Occupied(entry) => entry.into_mut()
-        };
+        }
+    }
+
+    // Distance between the ascent and the descent of a font
+    pub fn line_height(&mut self, font_size: f32, font: String) -> f32 {
+        let v_metrics = self.font(font).v_metrics(Scale::uniform(font_size));
+
+        v_metrics.ascent - v_metrics.descent
+    }
+
+    // Horizontal space taken by a run of text, trailing
+    // whitespace included (unlike `calculate_text_bbox`)
+    pub fn advance_width(&mut self, font_size: f32, font: String, text: &str) -> f32 {
+        let scale = Scale::uniform(font_size);
+        let font = self.font(font);
+
+        let mut width = 0.0;
+        let mut last_glyph_id = None;
+
+        for c in text.chars() {
+            let glyph = font.glyph(c);
+
+            if let Some(id) = last_glyph_id.take() {
+                width += font.pair_kerning(scale, id, glyph.id());
+            }
+
+            last_glyph_id = Some(glyph.id());
+
+            width += glyph.scaled(scale).h_metrics().advance_width;
+        }
+
+        width
+    }
+
+    pub fn calculate_text_bbox(&mut self, stroke_width: f32, font: String, text: &str)
+        -> (cgmath::Vector4<f32>, Vec<PositionedGlyph<'static>>) {
+        let scale = Scale::uniform(stroke_width);
+
+        let font = self.font(font);
 
         let v_metrics = font.v_metrics(scale);
         let _advance_height =
@@ -113,75 +150,36 @@ impl FontManager {
         (bbox, result)
     }
 
-    pub fn cache_glyphs(&mut self) -> Vec<GlyphTexData> {
-        // Get the cached instance on self
-        // not create a new one each time
-        // TODO: get dpi factor from gl context
-        let dpi_factor = 1.0;
-
-        let (cache_width, cache_height) = (
-            (512.0 * dpi_factor) as u32,
-            (512.0 * dpi_factor) as u32
-        );
-
+    // Makes sure all glyphs are in the cache, returns the texture
+    // regions to upload and whether the cache had to be rebuilt,
+    // which invalidates the UVs of glyphs cached before
+    pub fn cache_glyphs(&mut self, glyphs: &[PositionedGlyph<'static>]) -> (Vec<GlyphTexData>, bool) {
+        // TODO: get dpi factor from gl context and
+        // resize the cache (512 x 512) accordingly
         let mut glyph_tex_data = Vec::new();
 
-        self.glyph_cache
-            .to_builder()
-            .dimensions(cache_width, cache_height)
-            .rebuild(&mut self.glyph_cache);
-
-        for glyph in self.glyphs.iter() {
+        for glyph in glyphs.iter() {
             self.glyph_cache.queue_glyph(0, glyph.clone());
         }
 
-        self.glyph_cache.cache_queued(|rect, data| {
+        let cached_by = self.glyph_cache.cache_queued(|rect, data| {
             glyph_tex_data.push(GlyphTexData {
                 rect,
                 data: Vec::from_iter(data.iter().cloned())
             });
         }).expect("FontCache is too big");
 
-        glyph_tex_data
-    }
+        let reordered = match cached_by {
+            CachedBy::Reordering => true,
+            CachedBy::Adding => false,
+        };
 
-    pub fn generate_glyph_vertices(&mut self) -> (Vec<f32>, Vec<GlyphTexData>) {
-        let glyph_tex_data = self.cache_glyphs();
-
-        let mut vertices: Vec<f32> = Vec::new();
-
-        for glyph in self.glyphs.iter() {
-            if let Ok(Some((uv_rect, s_rect))) = self.glyph_cache.rect_for(0, glyph) {
-                // TL
-                vertices.push(s_rect.min.x as f32);
-                vertices.push(s_rect.max.y as f32);
-                vertices.push(1.0);
-                vertices.push(uv_rect.min.x as f32);
-                vertices.push(uv_rect.max.y as f32);
-
-                // TR
-                vertices.push(s_rect.max.x as f32);
-                vertices.push(s_rect.max.y as f32);
-                vertices.push(1.0);
-                vertices.push(uv_rect.max.x as f32);
-                vertices.push(uv_rect.max.y as f32);
-
-                // BL
-                vertices.push(s_rect.min.x as f32);
-                vertices.push(s_rect.min.y as f32);
-                vertices.push(1.0);
-                vertices.push(uv_rect.min.x as f32);
-                vertices.push(uv_rect.min.y as f32);
-
-                // BR
-                vertices.push(s_rect.max.x as f32);
-                vertices.push(s_rect.min.y as f32);
-                vertices.push(1.0);
-                vertices.push(uv_rect.max.x as f32);
-                vertices.push(uv_rect.min.y as f32);
-            }
-        }
+        (glyph_tex_data, reordered)
+    }
 
-        (vertices, glyph_tex_data)
+    // UV and screen rectangles of a glyph previously
+    // passed to `cache_glyphs`, None for blank glyphs
+    pub fn glyph_rect(&self, glyph: &PositionedGlyph<'static>) -> Option<(Rect<f32>, Rect<i32>)> {
+        self.glyph_cache.rect_for(0, glyph).unwrap_or_default()
     }
 }
@@ -11,6 +11,8 @@ use stretch::{
     geometry::Size
 };
 
+const MAX_PASSES: usize = 3;
+
 pub struct LayoutBuilder {
 }
 
@@ -21,6 +23,18 @@ impl LayoutBuilder {
     }
 
     pub fn build(&mut self, size: glutin::dpi::LogicalSize, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
+        // A pass may change the children of a widget, which
+        // then needs another one, never forever though
+        for _ in 0..MAX_PASSES {
+            self.build_pass(size, children, font_manager);
+
+            if !children.iter().any(|child| needs_layout(child.as_ref())) {
+                break;
+            }
+        }
+    }
+
+    fn build_pass(&mut self, size: glutin::dpi::LogicalSize, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
         let mut stretch = Stretch::new();
 
         let mut children_nodes  = vec![];
@@ -53,5 +67,61 @@ impl LayoutBuilder {
 
             //println!("Index: {}\nSize: {:?}\nLocation: {:?}\n#############", index, child_node_layout.size, child_node_layout.location);
         }
+
+        let window = lyon::math::rect(0.0, 0.0, size.width as f32, size.height as f32);
+
+        for child in children.iter_mut() {
+            place_popups(child, window);
+        }
+    }
+}
+
+// Once everything else is in place
+fn place_popups(widget: &mut Box<dyn Widget>, window: lyon::math::Rect) {
+    if widget.is_popup() {
+        widget.place_popup(window);
+    }
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            place_popups(child, window);
+        }
+    }
+}
+
+fn needs_layout(widget: &dyn Widget) -> bool {
+    widget.needs_layout() || widget.children().iter().any(|child| needs_layout(child.as_ref()))
+}
+
+// Serializes the computed layout of a widget tree, one widget
+// per line, indented by depth:
+// `Type#id [x, y] width x height`
+pub fn dump_layout(children: &Vec<Box<dyn Widget>>) -> String {
+    let mut dump = String::new();
+
+    for child in children.iter() {
+        dump_widget(child.as_ref(), 0, &mut dump);
+    }
+
+    dump
+}
+
+fn dump_widget(widget: &dyn Widget, depth: usize, dump: &mut String) {
+    let position = widget.position();
+    let size = widget.size();
+
+    dump.push_str(&format!(
+        "{}{}#{} [{:.1}, {:.1}] {:.1} x {:.1}\n",
+        "  ".repeat(depth),
+        widget.type_name(),
+        widget.options().id,
+        position.x,
+        position.y,
+        size.width,
+        size.height
+    ));
+
+    for child in widget.children().iter() {
+        dump_widget(child.as_ref(), depth + 1, dump);
     }
 }
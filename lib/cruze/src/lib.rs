@@ -4,10 +4,27 @@ extern crate stretch;
 
 pub mod window;
 pub mod app;
+pub mod timer;
+pub mod executor;
+pub mod application;
 
+pub mod renderer;
 pub mod render_gl;
+pub mod render_soft;
 pub mod layout_manager;
 pub mod canvas;
+pub mod animation;
 pub mod program;
 pub mod font_manager;
 pub mod widgets;
+pub mod dialog;
+pub mod scroll;
+pub mod list;
+pub mod table;
+pub mod tree;
+pub mod tabs;
+pub mod split;
+pub mod menu;
+pub mod shortcut;
+pub mod clipboard;
+pub mod testing;
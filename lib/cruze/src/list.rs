@@ -0,0 +1,999 @@
+use lyon::math::{
+    Point,
+    point,
+    rect,
+    vector,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::animation::Transition;
+
+use super::scroll::{
+    self,
+    ScrollThumb,
+    Scroller,
+    SCROLLBAR_MARGIN,
+    SCROLLBAR_WIDTH,
+    STEP_DURATION,
+};
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Orientation,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::time::Instant;
+
+// Rows built above and below the visible ones, so that
+// scrolling a little does not rebuild them every time
+const OVERSCAN: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowHeight {
+    // Every row is this tall, whatever its content
+    Fixed(f32),
+    // Rows take the height their content needs. Rows never laid
+    // out are counted with the estimate, so the scrollbar may move
+    // a little as they are measured.
+    Measured(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionMode {
+    None,
+    Single,
+    // Ctrl toggles a row, Shift selects a range from the last one
+    Multiple,
+}
+
+// Builds the row at an index, selected or not
+type RowBuilder = Box<dyn Fn(usize, bool) -> Element>;
+// Build the messages emitted with the new selection
+// and with the row activated
+type OnSelect = Box<dyn Fn(Vec<usize>) -> Box<dyn Any>>;
+type OnActivate = Box<dyn Fn(usize) -> Box<dyn Any>>;
+
+// A vertical list of `count` rows, of which only the visible ones
+// (and a few around them) exist as widgets: `builder` is called
+// with the index of a row, and whether it is selected, when the
+// row scrolls into view. The list scrolls like a ScrollView and
+// must get its size from its parent or from `options.width` and
+// `options.height`.
+//
+// Like the offset of a ScrollView, the selection is kept by the
+// list, `on_select` reports it and `with_selection` sets it. Up,
+// Down, PageUp, PageDown, Home and End move the current row,
+// outlined in `options.text_color`, Space selects it and Enter
+// emits `on_activate`.
+pub struct ListView {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The rows from `first`, then the outline of
+    // the current row and the scrollbar thumb
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    count: usize,
+    builder: RowBuilder,
+    row_height: RowHeight,
+    // With measured rows, the height of each row and
+    // where each one starts, `count + 1` of them
+    heights: Vec<f32>,
+    tops: Vec<f32>,
+    // Index of the first row built
+    first: usize,
+    // The rows changed during `update_layout`
+    relayout: bool,
+    scroller: Scroller,
+    // Offset the rows are currently placed with
+    applied: f32,
+    selection_mode: SelectionMode,
+    selection: BTreeSet<usize>,
+    // Where Shift+click selects from
+    anchor: Option<usize>,
+    current: Option<usize>,
+    // Last selection asked for by the view function
+    requested: Option<Vec<usize>>,
+    // Off when the owner uses Space (TreeView checkboxes)
+    space_selects: bool,
+    focused: bool,
+    on_select: Option<OnSelect>,
+    on_activate: Option<OnActivate>,
+}
+
+impl ListView {
+    pub fn new<F: Fn(usize, bool) -> Element + 'static>(options: WidgetOptions, count: usize, builder: F) -> Box<ListView> {
+        let outline = RowOutline::new(options.text_color);
+        let thumb = ScrollThumb::new(options.text_color.with_opacity(0.5));
+
+        Box::new(ListView {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![outline, thumb],
+            dirty: true,
+            count,
+            builder: Box::new(builder),
+            row_height: RowHeight::Fixed(24.0),
+            heights: vec![],
+            tops: vec![],
+            first: 0,
+            relayout: false,
+            scroller: Scroller::new(0.0),
+            applied: 0.0,
+            selection_mode: SelectionMode::Single,
+            selection: BTreeSet::new(),
+            anchor: None,
+            current: None,
+            requested: None,
+            space_selects: true,
+            focused: false,
+            on_select: None,
+            on_activate: None,
+        })
+    }
+
+    pub fn with_row_height(mut self: Box<Self>, row_height: RowHeight) -> Box<ListView> {
+        self.row_height = row_height;
+        self.measure_all();
+
+        self
+    }
+
+    pub fn with_selection_mode(mut self: Box<Self>, selection_mode: SelectionMode) -> Box<ListView> {
+        self.selection_mode = selection_mode;
+
+        self
+    }
+
+    // The rows selected to begin with. Once the list is on screen
+    // it selects them again whenever the value passed here changes.
+    pub fn with_selection(mut self: Box<Self>, selection: Vec<usize>) -> Box<ListView> {
+        self.selection = selection.iter().cloned().filter(|index| *index < self.count).collect();
+        self.current = selection.last().cloned().filter(|index| *index < self.count);
+        self.anchor = self.current;
+        self.requested = Some(selection);
+
+        self
+    }
+
+    // Message emitted with the selected rows, in
+    // ascending order, every time they change
+    pub fn on_select<M: 'static, F: Fn(Vec<usize>) -> M + 'static>(mut self: Box<Self>, on_select: F) -> Box<ListView> {
+        self.on_select = Some(Box::new(move |selection| Box::new(on_select(selection))));
+
+        self
+    }
+
+    // Message emitted with the current row when Enter is pressed
+    pub fn on_activate<M: 'static, F: Fn(usize) -> M + 'static>(mut self: Box<Self>, on_activate: F) -> Box<ListView> {
+        self.on_activate = Some(Box::new(move |index| Box::new(on_activate(index))));
+
+        self
+    }
+
+    pub fn selection(&self) -> Vec<usize> {
+        self.selection.iter().cloned().collect()
+    }
+
+    // The row the keys move from
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    // For owners whose builder depends on more than the index
+    // (Table columns), returns whether a layout is needed
+    pub(crate) fn rebuild_rows(&mut self) -> bool {
+        self.build_rows()
+    }
+
+    // Leaves Space to the owner, which gets it as the key bubbles
+    pub(crate) fn with_space_selection(mut self: Box<Self>, space_selects: bool) -> Box<ListView> {
+        self.space_selects = space_selects;
+
+        self
+    }
+
+    // For owners whose rows are inserted and removed (TreeView):
+    // the new number of rows, with the selection and the current
+    // row where they moved. Returns whether a layout is needed.
+    pub(crate) fn reset(&mut self, count: usize, selection: Vec<usize>, current: Option<usize>) -> bool {
+        let resized = count != self.count;
+
+        self.count = count;
+        self.measure_all();
+
+        self.selection = selection.into_iter().filter(|index| *index < self.count).collect();
+        self.current = current.filter(|index| *index < self.count);
+        self.anchor = self.current;
+
+        self.build_rows() || resized
+    }
+
+    // Selects `index` alone, as a click would
+    pub(crate) fn select_row(&mut self, index: usize, ctx: &mut EventCtx) {
+        if index < self.count {
+            self.select(index, false, false, ctx);
+        }
+    }
+
+    // Emits `on_select` for a selection the owner changed
+    pub(crate) fn emit_selection(&self, ctx: &mut EventCtx) {
+        if let Some(on_select) = &self.on_select {
+            ctx.emit(on_select(self.selection()));
+        }
+    }
+
+    // Scrolls as little as possible for the row to be visible
+    pub fn scroll_to(&mut self, index: usize, transition: Option<Transition>) {
+        if index >= self.count {
+            return;
+        }
+
+        let top = self.row_top(index);
+        let bottom = self.row_top(index + 1);
+        let target = self.scroller.target();
+
+        let offset = if top < target {
+            top
+        } else if bottom > target + self.size.height {
+            bottom - self.size.height
+        } else {
+            return;
+        };
+
+        let max_offset = self.max_offset();
+
+        self.scroller.set_offset(offset, max_offset, transition);
+    }
+
+    fn rows(&self) -> usize {
+        self.children.len() - 2
+    }
+
+    fn row_top(&self, index: usize) -> f32 {
+        match self.row_height {
+            RowHeight::Fixed(height) => index as f32 * height,
+            RowHeight::Measured(_) => self.tops[index],
+        }
+    }
+
+    // The row at `y` from the top of the content
+    fn row_at(&self, y: f32) -> usize {
+        let index = match self.row_height {
+            RowHeight::Fixed(height) => (y.max(0.0) / height) as usize,
+            RowHeight::Measured(_) => match self.tops.binary_search_by(|top| top.partial_cmp(&y).unwrap()) {
+                Ok(index) => index,
+                Err(index) => index.saturating_sub(1),
+            },
+        };
+
+        index.min(self.count.saturating_sub(1))
+    }
+
+    fn max_offset(&self) -> f32 {
+        (self.row_top(self.count) - self.size.height).max(0.0)
+    }
+
+    // Forgets the measured heights, when the rows
+    // or how they are measured changed
+    fn measure_all(&mut self) {
+        if let RowHeight::Measured(estimate) = self.row_height {
+            self.heights = vec![estimate; self.count];
+            self.update_tops();
+        }
+    }
+
+    fn update_tops(&mut self) {
+        self.tops.clear();
+        self.tops.push(0.0);
+
+        let mut top = 0.0;
+
+        for height in self.heights.iter() {
+            top += height;
+
+            self.tops.push(top);
+        }
+    }
+
+    fn visible(&self) -> Range<usize> {
+        if self.count == 0 {
+            return 0..0;
+        }
+
+        let offset = self.scroller.offset();
+
+        self.row_at(offset)..self.row_at(offset + self.size.height - 1.0) + 1
+    }
+
+    // Whether the rows built are not the ones around the visible ones
+    fn needs_rows(&self) -> bool {
+        let visible = self.visible();
+
+        visible.start < self.first || visible.end > self.first + self.rows()
+            || self.first + self.rows() > self.count
+    }
+
+    // Builds the rows around the visible ones again, the ones
+    // that were already there keep their state. Returns whether
+    // the layout must be computed again.
+    fn build_rows(&mut self) -> bool {
+        let visible = self.visible();
+
+        let start = visible.start.saturating_sub(OVERSCAN);
+        let end = (visible.end + OVERSCAN).min(self.count);
+
+        let rows: Vec<Element> = (start..end)
+            .map(|index| (self.builder)(index, self.selection.contains(&index)))
+            .collect();
+
+        let overlays = self.children.split_off(self.rows());
+        let change = widgets::reconcile(&mut self.children, rows);
+
+        self.children.extend(overlays);
+
+        // Rows are placed from the first one
+        let moved = start != self.first;
+
+        self.first = start;
+
+        change == Change::Layout || moved
+    }
+
+    // Moves the rows where the offset says, building the ones
+    // scrolled into view
+    fn apply_offset(&mut self, ctx: &mut EventCtx) {
+        let offset = self.scroller.offset();
+
+        if offset != self.applied {
+            let delta = vector(0.0, self.applied - offset);
+            let rows = self.rows();
+
+            for row in self.children[..rows].iter_mut() {
+                scroll::translate(row, delta);
+            }
+
+            self.applied = offset;
+
+            if self.needs_rows() && self.build_rows() {
+                ctx.request_layout();
+            }
+        }
+
+        self.update_overlays();
+    }
+
+    fn update_overlays(&mut self) {
+        let rows = self.rows();
+
+        // The outline of the current row, when there is one to see
+        let (position, size, opacity) = match self.current {
+            Some(current) if self.focused && current >= self.first && current < self.first + rows => {
+                let top = self.position.y + self.row_top(current) - self.applied;
+
+                (point(self.position.x, top), Size::new(self.size.width, self.row_top(current + 1) - self.row_top(current)), 1.0)
+            },
+            _ => (self.position, Size::new(0.0, 0.0), 0.0),
+        };
+
+        scroll::place_overlay(&mut self.children[rows], position, size, opacity);
+
+        let (track_start, track_length) = self.track();
+        let (start, length) = scroll::thumb_span(track_start, track_length, self.size.height, self.row_top(self.count), self.scroller.offset());
+
+        let opacity = if self.max_offset() > 0.0 { self.scroller.thumb_opacity() } else { 0.0 };
+
+        scroll::place_overlay(
+            &mut self.children[rows + 1],
+            point(self.position.x + self.size.width - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN, start),
+            Size::new(SCROLLBAR_WIDTH, length),
+            opacity
+        );
+    }
+
+    // Start and length of the scrollbar track
+    fn track(&self) -> (f32, f32) {
+        (self.position.y + SCROLLBAR_MARGIN, self.size.height - SCROLLBAR_MARGIN * 2.0)
+    }
+
+    fn track_rect(&self) -> lyon::math::Rect {
+        let (start, length) = self.track();
+        let thickness = SCROLLBAR_WIDTH + SCROLLBAR_MARGIN * 2.0;
+
+        rect(self.position.x + self.size.width - thickness, start, thickness, length)
+    }
+
+    fn thumb(&self) -> (f32, f32) {
+        let (track_start, track_length) = self.track();
+
+        scroll::thumb_span(track_start, track_length, self.size.height, self.row_top(self.count), self.scroller.offset())
+    }
+
+    // Makes `index` the current row, `extend` selects the rows
+    // from the anchor and `toggle` adds or removes the row
+    fn select(&mut self, index: usize, extend: bool, toggle: bool, ctx: &mut EventCtx) {
+        let mut selection = BTreeSet::new();
+
+        match self.selection_mode {
+            SelectionMode::None => (),
+            SelectionMode::Single => {
+                selection.insert(index);
+            },
+            SelectionMode::Multiple if extend => {
+                let anchor = self.anchor.unwrap_or(index);
+
+                selection = (anchor.min(index)..anchor.max(index) + 1).collect();
+            },
+            SelectionMode::Multiple if toggle => {
+                selection = self.selection.clone();
+
+                if !selection.remove(&index) {
+                    selection.insert(index);
+                }
+
+                self.anchor = Some(index);
+            },
+            SelectionMode::Multiple => {
+                selection.insert(index);
+
+                self.anchor = Some(index);
+            },
+        }
+
+        self.current = Some(index);
+
+        if selection != self.selection {
+            self.selection = selection;
+
+            if let Some(on_select) = &self.on_select {
+                ctx.emit(on_select(self.selection()));
+            }
+
+            // Rows show whether they are selected
+            if self.build_rows() {
+                ctx.request_layout();
+            }
+        }
+
+        self.scroll_to(index, Some(Transition::new(STEP_DURATION)));
+        self.apply_offset(ctx);
+    }
+
+    // Where the keys move the current row
+    fn target(&self, key: VirtualKeyCode) -> Option<usize> {
+        let moves = [
+            VirtualKeyCode::Up,
+            VirtualKeyCode::Down,
+            VirtualKeyCode::PageUp,
+            VirtualKeyCode::PageDown,
+            VirtualKeyCode::Home,
+            VirtualKeyCode::End,
+        ];
+
+        if !moves.contains(&key) {
+            return None;
+        }
+
+        let last = self.count.checked_sub(1)?;
+        let page = self.size.height;
+
+        let current = match self.current {
+            Some(current) => current.min(last),
+            // Without a current row, arrows and pages
+            // pick the first one visible
+            None if key != VirtualKeyCode::Home && key != VirtualKeyCode::End => return Some(self.visible().start),
+            None => 0,
+        };
+
+        match key {
+            VirtualKeyCode::Up => Some(current.saturating_sub(1)),
+            VirtualKeyCode::Down => Some((current + 1).min(last)),
+            VirtualKeyCode::PageUp => Some(self.row_at(self.row_top(current) - page)),
+            VirtualKeyCode::PageDown => Some(self.row_at(self.row_top(current) + page)),
+            VirtualKeyCode::Home => Some(0),
+            VirtualKeyCode::End => Some(last),
+            _ => None,
+        }
+    }
+}
+
+impl Widget for ListView {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut wrappers = vec![];
+
+        for row in self.children[..self.rows()].iter() {
+            let node = row.generate_stretch_node(stretch, font_manager);
+
+            let height = match self.row_height {
+                RowHeight::Fixed(height) => Dimension::Points(height),
+                RowHeight::Measured(_) => Dimension::Auto,
+            };
+
+            wrappers.push(stretch.new_node(
+                Style {
+                    flex_direction: FlexDirection::Column,
+                    flex_shrink: 0.0,
+                    size: stretch::geometry::Size {
+                        width: Dimension::Auto,
+                        height
+                    },
+                    ..Default::default()
+                },
+                vec![node]
+            ).unwrap());
+        }
+
+        // Out of the flow, so that the rows do not
+        // count in the size of the list
+        let column = stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    end: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            wrappers
+        ).unwrap();
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_grow: self.options.flex,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            vec![column]
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let column = stretch.children(*node).unwrap()[0];
+        let column_layout = stretch.layout(column).unwrap();
+        let wrappers = stretch.children(column).unwrap();
+
+        if let RowHeight::Measured(_) = self.row_height {
+            let mut measured = false;
+
+            for (index, wrapper) in wrappers.iter().enumerate() {
+                let height = stretch.layout(*wrapper).unwrap().size.height;
+
+                if self.heights[self.first + index] != height {
+                    self.heights[self.first + index] = height;
+
+                    measured = true;
+                }
+            }
+
+            if measured {
+                self.update_tops();
+            }
+        }
+
+        // The rows may have become fewer or shorter
+        let max_offset = self.max_offset();
+
+        self.scroller.clamp(max_offset);
+
+        self.applied = self.scroller.offset();
+
+        // The list got taller, or moved to rows never built
+        // yet: they get a layout during the next pass
+        self.relayout = self.needs_rows() && self.build_rows();
+
+        if !self.relayout {
+            let top = self.position.y + column_layout.location.y + self.row_top(self.first) - self.applied;
+
+            for (index, wrapper) in wrappers.iter().enumerate() {
+                let wrapper_layout = stretch.layout(*wrapper).unwrap();
+                let row = stretch.children(*wrapper).unwrap()[0];
+
+                let origin = point(
+                    self.position.x + column_layout.location.x + wrapper_layout.location.x,
+                    top + wrapper_layout.location.y
+                );
+
+                self.children[index].update_layout(stretch, &row, origin);
+            }
+        }
+
+        self.update_overlays();
+    }
+
+    fn needs_layout(&self) -> bool {
+        self.relayout
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn set_hovered(&mut self, hovered: bool) {
+        self.scroller.set_hovered(hovered);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<ListView>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            let rows = self.rows();
+
+            self.children[rows].options_mut().color = self.options.text_color;
+            self.children[rows + 1].options_mut().color = self.options.text_color.with_opacity(0.5);
+        }
+
+        if new.count != self.count || new.row_height != self.row_height {
+            self.count = new.count;
+            self.row_height = new.row_height;
+
+            self.measure_all();
+
+            self.selection = self.selection.iter().cloned().filter(|index| *index < self.count).collect();
+            self.current = self.current.filter(|index| *index < self.count);
+            self.anchor = self.anchor.filter(|index| *index < self.count);
+
+            change = Change::Layout;
+        }
+
+        if new.requested != self.requested {
+            self.requested = new.requested;
+
+            if let Some(selection) = &self.requested {
+                self.selection = selection.iter().cloned().filter(|index| *index < self.count).collect();
+                self.current = selection.last().cloned().filter(|index| *index < self.count);
+                self.anchor = self.current;
+            }
+        }
+
+        self.selection_mode = new.selection_mode;
+        self.space_selects = new.space_selects;
+        self.builder = new.builder;
+        self.on_select = new.on_select;
+        self.on_activate = new.on_activate;
+
+        // The data behind the rows may have changed, only
+        // the ones built are asked for again
+        if self.build_rows() {
+            change = Change::Layout;
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Scroll(_, delta) => {
+                let distance = scroll::scroll_distance(Orientation::Column, *delta);
+                let max_offset = self.max_offset();
+
+                if self.scroller.scroll(*delta, distance, max_offset) {
+                    self.apply_offset(ctx);
+                    ctx.set_handled();
+                }
+            },
+            WidgetEvent::ScrollEnd(_) => self.scroller.end_gesture(),
+            WidgetEvent::MousePress(position) if self.max_offset() > 0.0 && self.track_rect().contains(*position) => {
+                let (thumb_start, thumb_length) = self.thumb();
+
+                if position.y >= thumb_start && position.y < thumb_start + thumb_length {
+                    self.scroller.grab(position.y);
+                } else {
+                    // A page towards the cursor
+                    let page = if position.y < thumb_start { -self.size.height } else { self.size.height };
+                    let max_offset = self.max_offset();
+
+                    self.scroller.set_offset(self.scroller.target() + page, max_offset, Some(Transition::new(STEP_DURATION)));
+                }
+
+                self.update_overlays();
+
+                ctx.set_handled();
+            },
+            // Rows still get the press, for the buttons they contain
+            WidgetEvent::MousePress(position) if self.contains(*position) => {
+                let y = position.y - self.position.y + self.scroller.offset();
+
+                if self.count > 0 && y < self.row_top(self.count) {
+                    let modifiers = ctx.modifiers();
+
+                    self.select(self.row_at(y), modifiers.shift, modifiers.ctrl, ctx);
+                }
+            },
+            WidgetEvent::MouseMove(position) => {
+                let (_, track_length) = self.track();
+                let (_, thumb_length) = self.thumb();
+                let max_offset = self.max_offset();
+                let ratio = max_offset / (track_length - thumb_length).max(1.0);
+
+                if self.scroller.drag(position.y, ratio, max_offset) {
+                    self.apply_offset(ctx);
+                }
+            },
+            WidgetEvent::MouseRelease(_) => self.scroller.release(),
+            WidgetEvent::KeyPress(VirtualKeyCode::Space) if self.space_selects => {
+                if let Some(current) = self.current {
+                    let modifiers = ctx.modifiers();
+
+                    self.select(current, modifiers.shift, modifiers.ctrl, ctx);
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::KeyPress(VirtualKeyCode::Return) => {
+                if let (Some(current), Some(on_activate)) = (self.current, &self.on_activate) {
+                    ctx.emit(on_activate(current));
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::KeyPress(key) => {
+                let target = match self.target(*key) {
+                    Some(target) => target,
+                    None => return,
+                };
+
+                let modifiers = ctx.modifiers();
+
+                if modifiers.ctrl && self.selection_mode == SelectionMode::Multiple {
+                    // Moves without selecting, Space toggles the row then
+                    self.current = Some(target);
+
+                    self.scroll_to(target, Some(Transition::new(STEP_DURATION)));
+                    self.apply_offset(ctx);
+                } else {
+                    self.select(target, modifiers.shift, false, ctx);
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::Tick(now) => {
+                let max_offset = self.max_offset();
+
+                self.scroller.tick(*now, max_offset);
+
+                self.apply_offset(ctx);
+            },
+            _ => ()
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.scroller.next_tick()
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused != self.focused {
+            self.focused = focused;
+
+            self.update_overlays();
+        }
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ListView"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("ListView -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// Outline of the current row of a focused ListView, which
+// places it outside of the layout and sets its opacity
+struct RowOutline {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    dirty: bool,
+}
+
+impl RowOutline {
+    fn new(color: Color) -> Box<RowOutline> {
+        Box::new(RowOutline {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: "row_outline".to_string(),
+                color,
+                opacity: 0.0,
+                ..Default::default()
+            },
+            dirty: true,
+        })
+    }
+}
+
+impl Widget for RowOutline {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        // Inside, so that the list does not clip it
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.stroke_width(2.0);
+        ctx.rect(
+            point(self.position.x + 1.0, self.position.y + 1.0),
+            (self.size.width - 2.0).max(0.0),
+            (self.size.height - 2.0).max(0.0)
+        );
+        ctx.stroke();
+    }
+
+    // Never part of the layout, see `ListView::update_overlays`
+    fn generate_stretch_node(&self, stretch: &mut Stretch, _font_manager: &mut FontManager) -> stretch::node::Node {
+        stretch.new_node(Style::default(), vec![]).unwrap()
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, _new: Element) -> Change {
+        Change::None
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "RowOutline"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("RowOutline -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
@@ -0,0 +1,1651 @@
+use lyon::math::{
+    Point,
+    Vector,
+    point,
+    rect,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::shortcut::{self, Chord, Shortcuts};
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Label,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Around the text of a row, and of a menu title
+const ROW_PADDING: f32 = 4.0;
+const TITLE_PADDING: f32 = 10.0;
+// Columns of the check mark and of the submenu arrow
+const CHECK_WIDTH: f32 = 22.0;
+const ARROW_WIDTH: f32 = 18.0;
+// At least between the text and the shortcut of an item
+const SHORTCUT_GAP: f32 = 24.0;
+const SEPARATOR_HEIGHT: f32 = 9.0;
+const PANEL_PADDING: f32 = 4.0;
+
+#[derive(Clone)]
+enum MenuItemKind {
+    Action,
+    Check(bool),
+    Submenu(Vec<MenuItem>),
+    Separator,
+}
+
+// An entry of a menu. In labels, `&` marks the mnemonic, the
+// letter that picks the item while its menu is open ("&Save"),
+// and `&&` stands for a `&`.
+#[derive(Clone)]
+pub struct MenuItem {
+    label: String,
+    kind: MenuItemKind,
+    enabled: bool,
+    command: Option<String>,
+    message: Option<Rc<dyn Fn() -> Box<dyn Any>>>,
+}
+
+impl MenuItem {
+    // Emits `message` when picked
+    pub fn new<M: Clone + 'static>(label: &str, message: M) -> MenuItem {
+        MenuItem {
+            label: label.to_string(),
+            kind: MenuItemKind::Action,
+            enabled: true,
+            command: None,
+            message: Some(Rc::new(move || Box::new(message.clone()))),
+        }
+    }
+
+    // Shows a check mark when `checked`. Like the checkboxes of a
+    // TreeView, whether it is checked comes from the view: picking
+    // the item emits `message`, the application toggles it.
+    pub fn check<M: Clone + 'static>(label: &str, checked: bool, message: M) -> MenuItem {
+        MenuItem {
+            kind: MenuItemKind::Check(checked),
+            ..MenuItem::new(label, message)
+        }
+    }
+
+    pub fn submenu(label: &str, items: Vec<MenuItem>) -> MenuItem {
+        MenuItem {
+            label: label.to_string(),
+            kind: MenuItemKind::Submenu(items),
+            enabled: true,
+            command: None,
+            message: None,
+        }
+    }
+
+    pub fn separator() -> MenuItem {
+        MenuItem {
+            label: String::new(),
+            kind: MenuItemKind::Separator,
+            enabled: false,
+            command: None,
+            message: None,
+        }
+    }
+
+    // Shown greyed out, it cannot be picked
+    pub fn disabled(mut self) -> MenuItem {
+        self.enabled = false;
+        self
+    }
+
+    // Shows next to the label the chord bound to `command` with
+    // `AppHandle::bind_shortcut`, looked up each time the menu is
+    // shown. The item still emits its own message when picked.
+    pub fn with_command(mut self, command: &str) -> MenuItem {
+        self.command = Some(command.to_string());
+        self
+    }
+
+    fn selectable(&self) -> bool {
+        match self.kind {
+            MenuItemKind::Separator => false,
+            _ => self.enabled,
+        }
+    }
+
+    fn submenu_items(&self) -> Option<&[MenuItem]> {
+        match &self.kind {
+            MenuItemKind::Submenu(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+// A menu of a MenuBar
+#[derive(Clone)]
+pub struct Menu {
+    label: String,
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new(label: &str, items: Vec<MenuItem>) -> Menu {
+        Menu {
+            label: label.to_string(),
+            items,
+        }
+    }
+}
+
+// The text of a label without the `&`s, with where
+// the mnemonic is in it and the mnemonic itself
+fn parse_mnemonic(label: &str) -> (String, Option<(usize, char)>) {
+    let mut text = String::new();
+    let mut mnemonic = None;
+    let mut chars = label.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('&') => text.push('&'),
+            Some(c) => {
+                if mnemonic.is_none() {
+                    mnemonic = Some((text.len(), c));
+                }
+
+                text.push(c);
+            },
+            None => (),
+        }
+    }
+
+    (text, mnemonic)
+}
+
+fn mnemonic_char(label: &str) -> Option<char> {
+    parse_mnemonic(label).1.map(|(_, c)| c.to_ascii_lowercase())
+}
+
+// Where the first menu of a popup goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    // Under a title of a menu bar, or over it without room
+    Below(lyon::math::Rect),
+    // At the cursor, for context menus
+    At(Point),
+}
+
+// The open menu and submenus of a MenuBar or a ContextMenu
+struct MenuState {
+    items: Vec<MenuItem>,
+    // The current item of each open menu, the menu first
+    // then its submenus. All but the last one are the
+    // item of the submenu opened after it.
+    levels: Vec<Option<usize>>,
+    // Where the chords of the items are, taken
+    // from the event that opened the menu
+    shortcuts: Rc<RefCell<Shortcuts>>,
+}
+
+impl MenuState {
+    fn new() -> MenuState {
+        MenuState {
+            items: vec![],
+            levels: vec![],
+            shortcuts: Rc::new(RefCell::new(Shortcuts::new())),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+
+    fn open(&mut self, items: Vec<MenuItem>, select_first: bool, ctx: &EventCtx) {
+        self.items = items;
+        self.shortcuts = ctx.shortcuts();
+        self.levels = vec![None];
+
+        if select_first {
+            self.step(0, 1);
+        }
+    }
+
+    fn close(&mut self) {
+        self.levels.clear();
+    }
+
+    // New items from the view, the submenus that
+    // are not there anymore are closed
+    fn set_items(&mut self, items: Vec<MenuItem>) {
+        self.items = items;
+
+        let mut levels = std::mem::take(&mut self.levels);
+
+        for (level, current) in levels.drain(..).enumerate() {
+            let current = current.filter(|index| self.items(level).get(*index).is_some_and(MenuItem::selectable));
+
+            self.levels.push(current);
+
+            if current.is_none_or(|index| self.items(level)[index].submenu_items().is_none()) {
+                break;
+            }
+        }
+    }
+
+    fn items(&self, level: usize) -> &[MenuItem] {
+        let mut items = &self.items[..];
+
+        for current in self.levels[..level].iter() {
+            items = items[current.unwrap()].submenu_items().unwrap();
+        }
+
+        items
+    }
+
+    // Moves the current item of a menu over the items
+    // that cannot be picked, wrapping around
+    fn step(&mut self, level: usize, step: isize) {
+        let count = self.items(level).len() as isize;
+
+        let start = match self.levels[level] {
+            Some(current) => current as isize,
+            None if step > 0 => -1,
+            None => count,
+        };
+
+        for distance in 1..=count {
+            let index = (start + step * distance).rem_euclid(count) as usize;
+
+            if self.items(level)[index].selectable() {
+                self.levels.truncate(level + 1);
+                self.levels[level] = Some(index);
+
+                return;
+            }
+        }
+    }
+
+    fn open_submenu(&mut self, level: usize, index: usize, select_first: bool) {
+        self.levels.truncate(level + 1);
+        self.levels[level] = Some(index);
+        self.levels.push(None);
+
+        if select_first {
+            self.step(level + 1, 1);
+        }
+    }
+
+    fn hover(&mut self, level: usize, index: usize) {
+        self.levels.truncate(level + 1);
+
+        let item = &self.items(level)[index];
+
+        if !item.selectable() {
+            self.levels[level] = None;
+        } else if item.submenu_items().is_some() {
+            self.open_submenu(level, index, false);
+        } else {
+            self.levels[level] = Some(index);
+        }
+    }
+
+    // Submenus open, other items emit their message
+    // and close the whole menu
+    fn activate(&mut self, level: usize, index: usize, select_first: bool, ctx: &mut EventCtx) {
+        let item = &self.items(level)[index];
+
+        if !item.selectable() {
+            return;
+        }
+
+        if item.submenu_items().is_some() {
+            self.open_submenu(level, index, select_first);
+        } else {
+            if let Some(message) = &item.message {
+                ctx.emit(message());
+            }
+
+            self.close();
+        }
+    }
+
+    fn key(&mut self, key: VirtualKeyCode, ctx: &mut EventCtx) {
+        let level = self.levels.len() - 1;
+        let current = self.levels[level];
+
+        match key {
+            VirtualKeyCode::Up => self.step(level, -1),
+            VirtualKeyCode::Down => self.step(level, 1),
+            VirtualKeyCode::Home => {
+                self.levels[level] = None;
+                self.step(level, 1);
+            },
+            VirtualKeyCode::End => {
+                self.levels[level] = None;
+                self.step(level, -1);
+            },
+            VirtualKeyCode::Return | VirtualKeyCode::Space => {
+                if let Some(index) = current {
+                    self.activate(level, index, true, ctx);
+                }
+            },
+            // Left and Right in the menu itself go to the
+            // menus next to it, if a menu bar handles them
+            VirtualKeyCode::Right => match current {
+                Some(index) if self.items(level)[index].submenu_items().is_some() => {
+                    self.open_submenu(level, index, true);
+                },
+                _ => return,
+            },
+            VirtualKeyCode::Left if level > 0 => {
+                self.levels.pop();
+            },
+            VirtualKeyCode::Left => return,
+            VirtualKeyCode::Escape if level > 0 => {
+                self.levels.pop();
+            },
+            VirtualKeyCode::Escape => self.close(),
+            key => {
+                let c = match shortcut::key_char(key) {
+                    Some(c) => c,
+                    None => return,
+                };
+
+                let found = self.items(level)
+                    .iter()
+                    .position(|item| item.selectable() && mnemonic_char(&item.label) == Some(c));
+
+                if let Some(index) = found {
+                    self.activate(level, index, true, ctx);
+                }
+            }
+        }
+
+        ctx.set_handled();
+    }
+
+    // Whether the open menus changed
+    fn on_event(&mut self, event: &WidgetEvent, popup: &Element, ctx: &mut EventCtx) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+
+        let levels = self.levels.clone();
+
+        match event {
+            WidgetEvent::MousePress(position) => {
+                if popup.contains(*position) {
+                    // Submenus open on press, the
+                    // other items pick on release
+                    if let Some((level, index)) = row_at(popup, *position) {
+                        self.hover(level, index);
+                    }
+
+                    ctx.set_handled();
+                } else {
+                    // The click only closes the menu, the
+                    // widget under it does not get it
+                    self.close();
+
+                    ctx.set_handled();
+                }
+            },
+            WidgetEvent::MouseRelease(position) => {
+                if let Some((level, index)) = row_at(popup, *position) {
+                    if self.items(level)[index].submenu_items().is_none() {
+                        self.activate(level, index, false, ctx);
+                    }
+                }
+            },
+            WidgetEvent::MouseMove(position) => {
+                if let Some((level, index)) = row_at(popup, *position) {
+                    self.hover(level, index);
+                }
+            },
+            WidgetEvent::KeyPress(key) => self.key(*key, ctx),
+            // Nothing gets typed while a menu is open
+            WidgetEvent::Char(_) => ctx.set_handled(),
+            WidgetEvent::Scroll(position, _) if popup.contains(*position) => ctx.set_handled(),
+            WidgetEvent::ContextMenu(position) => {
+                if !popup.contains(*position) {
+                    self.close();
+                }
+
+                ctx.set_handled();
+            },
+            _ => ()
+        }
+
+        self.levels != levels
+    }
+
+    fn view(&self, options: &WidgetOptions, anchor: Anchor) -> Element {
+        let shortcuts = self.shortcuts.borrow();
+        let mut panels = vec![];
+
+        for (level, current) in self.levels.iter().enumerate() {
+            let rows = self.items(level)
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let chord = item.command.as_ref().and_then(|command| shortcuts.chord(command));
+
+                    MenuRow::item(index, item, chord, *current == Some(index), options) as Element
+                })
+                .collect();
+
+            panels.push(MenuPanel::new(level, options, rows) as Element);
+        }
+
+        let parents = self.levels[..self.levels.len().saturating_sub(1)]
+            .iter()
+            .map(|current| current.unwrap())
+            .collect();
+
+        MenuPopup::new(anchor, parents, panels)
+    }
+}
+
+// The open menu and the row of an item at `position`
+fn row_at(popup: &Element, position: Point) -> Option<(usize, usize)> {
+    // The submenus on top first
+    for (level, panel) in popup.children().iter().enumerate().rev() {
+        if panel.contains(position) {
+            return panel.children()
+                .iter()
+                .position(|row| row.contains(position))
+                .map(|index| (level, index));
+        }
+    }
+
+    None
+}
+
+// The menus of a window, side by side. A click on a title opens
+// its menu below it, then moving over the other titles opens
+// theirs. Alt with the underlined letter of a title opens it from
+// the keyboard, anywhere in the window: while a menu is open,
+// Up and Down go through its items, Right and Left through the
+// submenus and the other menus, Enter picks an item and so does
+// its own underlined letter. Escape, or a click anywhere else,
+// closes the menu.
+//
+// `options.color` is the color of the bar and of its menus.
+pub struct MenuBar {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The titles, then the popup of the open menu
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    menus: Vec<Menu>,
+    state: MenuState,
+    // Which menu is open, and which title under the cursor
+    open: Option<usize>,
+    hovered: Option<usize>,
+}
+
+impl MenuBar {
+    pub fn new(options: WidgetOptions, menus: Vec<Menu>) -> Box<MenuBar> {
+        let mut bar = MenuBar {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![],
+            dirty: true,
+            menus,
+            state: MenuState::new(),
+            open: None,
+            hovered: None,
+        };
+
+        bar.children = bar.view();
+
+        Box::new(bar)
+    }
+
+    fn popup(&self) -> &Element {
+        self.children.last().unwrap()
+    }
+
+    fn title_at(&self, position: Point) -> Option<usize> {
+        self.children[..self.menus.len()]
+            .iter()
+            .position(|title| title.contains(position))
+    }
+
+    fn open_menu(&mut self, index: usize, select_first: bool, ctx: &EventCtx) {
+        self.open = Some(index);
+        self.state.open(self.menus[index].items.clone(), select_first, ctx);
+    }
+
+    fn view(&self) -> Vec<Element> {
+        let mut children: Vec<Element> = self.menus
+            .iter()
+            .enumerate()
+            .map(|(index, menu)| {
+                let highlighted = match self.open {
+                    Some(open) => open == index,
+                    None => self.hovered == Some(index),
+                };
+
+                MenuRow::title(index, &menu.label, highlighted, &self.options) as Element
+            })
+            .collect();
+
+        // Under the title of the open menu
+        let anchor = match self.open.and_then(|open| self.children.get(open)) {
+            Some(title) => rect(title.position().x, title.position().y, title.size().width, title.size().height),
+            None => rect(self.position.x, self.position.y, 0.0, self.size.height),
+        };
+
+        children.push(self.state.view(&self.options, Anchor::Below(anchor)));
+
+        children
+    }
+
+    fn sync(&mut self, ctx: &mut EventCtx) {
+        if !self.state.is_open() {
+            self.open = None;
+        }
+
+        let view = self.view();
+
+        if widgets::reconcile(&mut self.children, view) == Change::Layout {
+            ctx.request_layout();
+        }
+    }
+}
+
+impl Widget for MenuBar {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_grow: self.options.flex,
+                min_size: size,
+                max_size: size,
+                padding: self.options.padding,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<MenuBar>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        self.menus = new.menus;
+
+        // The open menu keeps the items still there
+        match self.open {
+            Some(open) if open < self.menus.len() => self.state.set_items(self.menus[open].items.clone()),
+            _ => self.state.close(),
+        }
+
+        if !self.state.is_open() {
+            self.open = None;
+        }
+
+        self.hovered = self.hovered.filter(|hovered| *hovered < self.menus.len());
+
+        let view = self.view();
+
+        change.max(widgets::reconcile(&mut self.children, view))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        let open = self.open;
+        let hovered = self.hovered;
+        let levels = self.state.levels.clone();
+
+        match event {
+            WidgetEvent::MousePress(position) if !self.popup().contains(*position) => {
+                match self.title_at(*position) {
+                    Some(index) => {
+                        if open == Some(index) {
+                            self.state.close();
+                        } else {
+                            self.open_menu(index, false, ctx);
+                        }
+
+                        ctx.set_handled();
+                    },
+                    None => {
+                        self.state.on_event(event, self.children.last().unwrap(), ctx);
+                    }
+                }
+            },
+            WidgetEvent::MouseMove(position) => {
+                self.hovered = self.title_at(*position);
+
+                match (open, self.hovered) {
+                    (Some(open), Some(hovered)) if open != hovered => self.open_menu(hovered, false, ctx),
+                    _ => {
+                        self.state.on_event(event, self.children.last().unwrap(), ctx);
+                    }
+                }
+            },
+            WidgetEvent::KeyPress(key) if open.is_some() => {
+                self.state.on_event(event, self.children.last().unwrap(), ctx);
+
+                let step = match key {
+                    VirtualKeyCode::Left => self.menus.len() - 1,
+                    VirtualKeyCode::Right => 1,
+                    _ => 0,
+                };
+
+                if !ctx.is_handled() && step != 0 {
+                    self.open_menu((open.unwrap() + step) % self.menus.len(), true, ctx);
+
+                    ctx.set_handled();
+                }
+            },
+            WidgetEvent::Mnemonic(c) if !ctx.is_handled() => {
+                let found = self.menus
+                    .iter()
+                    .position(|menu| mnemonic_char(&menu.label) == Some(*c));
+
+                if let Some(index) = found {
+                    self.open_menu(index, true, ctx);
+
+                    ctx.set_handled();
+                }
+            },
+            event => {
+                self.state.on_event(event, self.children.last().unwrap(), ctx);
+            }
+        }
+
+        if self.open != open || self.hovered != hovered || self.state.levels != levels {
+            self.sync(ctx);
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        "MenuBar"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("MenuBar -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// Opens a menu where its content is clicked with the secondary
+// button, unless a widget inside has a context menu of its own.
+// The menu works like the ones of a MenuBar, and closes the same.
+//
+// `options.color` is the color of the menu, the context menu
+// itself draws nothing around its content.
+pub struct ContextMenu {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The content, then the popup of the menu
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    items: Vec<MenuItem>,
+    state: MenuState,
+    // Where the menu was asked for
+    at: Point,
+}
+
+impl ContextMenu {
+    pub fn new(options: WidgetOptions, content: Element, items: Vec<MenuItem>) -> Box<ContextMenu> {
+        let mut menu = ContextMenu {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![content],
+            dirty: true,
+            items,
+            state: MenuState::new(),
+            at: point(0.0, 0.0),
+        };
+
+        menu.children.push(menu.state.view(&menu.options, Anchor::At(menu.at)));
+
+        Box::new(menu)
+    }
+
+    fn sync(&mut self, ctx: &mut EventCtx) {
+        let popup = self.state.view(&self.options, Anchor::At(self.at));
+
+        if self.children[1].update(popup) == Change::Layout {
+            ctx.request_layout();
+        }
+    }
+}
+
+impl Widget for ContextMenu {
+    fn draw(&self, _ctx: &mut Ctx, _font_manager: &mut FontManager) {
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: self.options.orientation.into(),
+                flex_grow: self.options.flex,
+                min_size: size,
+                max_size: size,
+                padding: self.options.padding,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let mut new = match new.into_any().downcast::<ContextMenu>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+        }
+
+        if self.state.is_open() {
+            self.state.set_items(new.items.clone());
+        }
+
+        self.items = new.items;
+
+        new.children.truncate(1);
+        new.children.push(self.state.view(&self.options, Anchor::At(self.at)));
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        let changed = match event {
+            // Another click inside opens the menu there
+            WidgetEvent::ContextMenu(position) if !self.children[1].contains(*position) && self.contains(*position) => {
+                self.at = *position;
+                self.state.open(self.items.clone(), false, ctx);
+
+                ctx.set_handled();
+
+                true
+            },
+            event => self.state.on_event(event, &self.children[1], ctx),
+        };
+
+        if changed {
+            self.sync(ctx);
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ContextMenu"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("ContextMenu -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// The open menus, out of the layout of the owner. Each menu is a
+// MenuPanel, placed by `place_popup` next to what opened it and
+// inside the window.
+struct MenuPopup {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    dirty: bool,
+    anchor: Anchor,
+    // Row of each submenu in the menu before it
+    parents: Vec<usize>,
+}
+
+impl MenuPopup {
+    fn new(anchor: Anchor, parents: Vec<usize>, panels: Vec<Element>) -> Box<MenuPopup> {
+        Box::new(MenuPopup {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: "menu_popup".to_string(),
+                ..Default::default()
+            },
+            children: panels,
+            dirty: true,
+            anchor,
+            parents,
+        })
+    }
+}
+
+fn translate(widget: &mut Element, offset: Vector) {
+    widget.set_position(widget.position() + offset);
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            translate(child, offset);
+        }
+    }
+}
+
+impl Widget for MenuPopup {
+    fn draw(&self, _ctx: &mut Ctx, _font_manager: &mut FontManager) {
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    // Menus open after the anchor, or before it when they do not
+    // fit, submenus on the right of their item, or on the left
+    fn place_popup(&mut self, window: lyon::math::Rect) {
+        for level in 0..self.children.len() {
+            let size = self.children[level].size();
+
+            let origin: Point = if level == 0 {
+                match self.anchor {
+                    Anchor::Below(anchor) if anchor.max_y() + size.height > window.max_y() => {
+                        point(anchor.min_x(), anchor.min_y() - size.height)
+                    },
+                    Anchor::Below(anchor) => point(anchor.min_x(), anchor.max_y()),
+                    Anchor::At(at) => point(
+                        if at.x + size.width > window.max_x() { at.x - size.width } else { at.x },
+                        if at.y + size.height > window.max_y() { at.y - size.height } else { at.y }
+                    ),
+                }
+            } else {
+                let parent = &self.children[level - 1];
+                let right = parent.position().x + parent.size().width;
+
+                let row_y = parent.children()
+                    .get(self.parents[level - 1])
+                    .map_or(parent.position().y, |row| row.position().y);
+
+                point(
+                    if right + size.width > window.max_x() { parent.position().x - size.width } else { right },
+                    row_y - PANEL_PADDING
+                )
+            };
+
+            // Inside the window, whenever it fits
+            let origin = point(
+                origin.x.min(window.max_x() - size.width).max(window.min_x()),
+                origin.y.min(window.max_y() - size.height).max(window.min_y())
+            );
+
+            let offset = origin - self.children[level].position();
+
+            translate(&mut self.children[level], offset);
+        }
+    }
+
+    fn contains(&self, point: Point) -> bool {
+        self.children.iter().any(|panel| panel.contains(point))
+    }
+
+    fn is_popup(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<MenuPopup>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        // Placed again
+        let mut change = Change::None;
+
+        if new.anchor != self.anchor || new.parents != self.parents {
+            self.anchor = new.anchor;
+            self.parents = new.parents;
+
+            change = Change::Layout;
+        }
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "MenuPopup"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("MenuPopup -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// One open menu, its rows in a column
+struct MenuPanel {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    dirty: bool,
+}
+
+impl MenuPanel {
+    fn new(level: usize, options: &WidgetOptions, rows: Vec<Element>) -> Box<MenuPanel> {
+        Box::new(MenuPanel {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: format!("menu_panel_{}", level),
+                color: options.color,
+                text_color: options.text_color,
+                radius: 4.0,
+                ..Default::default()
+            },
+            children: rows,
+            dirty: true,
+        })
+    }
+}
+
+impl Widget for MenuPanel {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+
+        ctx.begin_primitive();
+        ctx.color(self.options.text_color.with_opacity(0.25));
+        ctx.stroke_width(1.0);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.stroke();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                padding: WidgetOptions::uniform_padding(PANEL_PADDING),
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<MenuPanel>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "MenuPanel"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("MenuPanel -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowKind {
+    Title,
+    Item {
+        check: Option<bool>,
+        submenu: bool,
+    },
+    Separator,
+}
+
+// A title of a menu bar, or an item of a menu: the text, then the
+// shortcut if any. The row draws the highlight, the underline of
+// the mnemonic, the check mark and the submenu arrow.
+struct MenuRow {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    dirty: bool,
+    kind: RowKind,
+    // The text before the mnemonic, and the mnemonic
+    mnemonic: Option<(String, char)>,
+}
+
+impl MenuRow {
+    fn new(id: String, kind: RowKind, label: &str, shortcut: Option<Chord>, highlighted: bool, enabled: bool, options: &WidgetOptions) -> Box<MenuRow> {
+        let (text, mnemonic) = parse_mnemonic(label);
+        let text_color = if enabled { options.text_color } else { options.text_color.with_opacity(0.4) };
+
+        let mut children = vec![];
+
+        if kind != RowKind::Separator {
+            children.push(Label::new(
+                WidgetOptions {
+                    color: text_color,
+                    font_size: options.font_size,
+                    ..Default::default()
+                },
+                text.clone()
+            ) as Element);
+        }
+
+        if let Some(chord) = shortcut {
+            children.push(Label::new(
+                WidgetOptions {
+                    id: "shortcut".to_string(),
+                    color: text_color.with_opacity(0.6),
+                    font_size: options.font_size,
+                    ..Default::default()
+                },
+                chord.to_string()
+            ) as Element);
+        }
+
+        Box::new(MenuRow {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id,
+                color: if highlighted { options.text_color.with_opacity(0.15) } else { Color::from_rgba(0.0, 0.0, 0.0, 0.0) },
+                text_color,
+                radius: 3.0,
+                ..Default::default()
+            },
+            children,
+            dirty: true,
+            kind,
+            mnemonic: mnemonic.map(|(index, c)| (text[..index].to_string(), c)),
+        })
+    }
+
+    fn title(index: usize, label: &str, highlighted: bool, options: &WidgetOptions) -> Box<MenuRow> {
+        MenuRow::new(format!("menu_title_{}", index), RowKind::Title, label, None, highlighted, true, options)
+    }
+
+    // `shortcut` is the chord bound to the command of the item
+    fn item(index: usize, item: &MenuItem, shortcut: Option<Chord>, highlighted: bool, options: &WidgetOptions) -> Box<MenuRow> {
+        let kind = match item.kind {
+            MenuItemKind::Action => RowKind::Item { check: None, submenu: false },
+            MenuItemKind::Check(checked) => RowKind::Item { check: Some(checked), submenu: false },
+            MenuItemKind::Submenu(_) => RowKind::Item { check: None, submenu: true },
+            MenuItemKind::Separator => RowKind::Separator,
+        };
+
+        MenuRow::new(format!("menu_item_{}", index), kind, &item.label, shortcut, highlighted, item.enabled, options)
+    }
+}
+
+impl Widget for MenuRow {
+    fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager) {
+        let middle = self.position.y + self.size.height / 2.0;
+
+        if self.kind == RowKind::Separator {
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color.with_opacity(0.2));
+            ctx.rect(point(self.position.x + ROW_PADDING, middle.floor()), self.size.width - ROW_PADDING * 2.0, 1.0);
+            ctx.fill();
+
+            return;
+        }
+
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+
+        if let (Some((before, c)), Some(label)) = (&self.mnemonic, self.children.first()) {
+            let font_size = label.options().font_size;
+            let x = label.position().x + font_manager.advance_width(font_size, "dejavu".to_string(), before);
+            let width = font_manager.advance_width(font_size, "dejavu".to_string(), &c.to_string());
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color);
+            ctx.rect(point(x, label.position().y - 2.0), width, 1.0);
+            ctx.fill();
+        }
+
+        if let RowKind::Item { check, submenu } = self.kind {
+            if check == Some(true) {
+                let left = self.position.x + CHECK_WIDTH / 2.0 - 4.0;
+
+                ctx.begin_primitive();
+                ctx.color(self.options.text_color);
+                ctx.stroke_width(2.0);
+                ctx.move_to(point(left, middle));
+                ctx.line_to(point(left + 2.5, middle - 3.0));
+                ctx.line_to(point(left + 7.0, middle + 3.0));
+                ctx.stroke();
+            }
+
+            if submenu {
+                let center: Point = point(self.position.x + self.size.width - ARROW_WIDTH / 2.0, middle);
+
+                ctx.begin_primitive();
+                ctx.color(self.options.text_color.with_opacity(0.7));
+                ctx.move_to(point(center.x - 2.0, center.y - 4.0));
+                ctx.line_to(point(center.x + 3.0, center.y));
+                ctx.line_to(point(center.x - 2.0, center.y + 4.0));
+                ctx.close();
+                ctx.fill();
+            }
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        if self.kind == RowKind::Separator {
+            return stretch.new_node(
+                Style {
+                    size: stretch::geometry::Size {
+                        width: Dimension::Undefined,
+                        height: Dimension::Points(SEPARATOR_HEIGHT),
+                    },
+                    ..Default::default()
+                },
+                vec![]
+            ).unwrap();
+        }
+
+        let mut children_nodes = vec![self.children[0].generate_stretch_node(stretch, font_manager)];
+
+        // The shortcut is pushed to the end of the row
+        if let Some(shortcut) = self.children.get(1) {
+            children_nodes.push(stretch.new_node(
+                Style {
+                    flex_grow: 1.0,
+                    min_size: stretch::geometry::Size {
+                        width: Dimension::Points(SHORTCUT_GAP),
+                        height: Dimension::Undefined,
+                    },
+                    ..Default::default()
+                },
+                vec![]
+            ).unwrap());
+
+            children_nodes.push(shortcut.generate_stretch_node(stretch, font_manager));
+        }
+
+        let (start, end) = match self.kind {
+            RowKind::Title => (TITLE_PADDING, TITLE_PADDING),
+            _ => (CHECK_WIDTH, ARROW_WIDTH),
+        };
+
+        stretch.new_node(
+            Style {
+                align_items: AlignItems::Center,
+                flex_shrink: 0.0,
+                padding: stretch::geometry::Rect {
+                    start: Dimension::Points(start),
+                    end: Dimension::Points(end),
+                    top: Dimension::Points(ROW_PADDING),
+                    bottom: Dimension::Points(ROW_PADDING),
+                },
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        // The text, the space before the shortcut, then the shortcut
+        let nodes = stretch.children(*node).unwrap();
+
+        for (index, child) in self.children.iter_mut().enumerate() {
+            child.update_layout(stretch, &nodes[index * 2], self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<MenuRow>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if new.kind != self.kind {
+            change = Change::Layout;
+        }
+
+        if change != Change::None || new.mnemonic != self.mnemonic {
+            self.options = new.options;
+            self.kind = new.kind;
+            self.mnemonic = new.mnemonic;
+            self.dirty = true;
+        }
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "MenuRow"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("MenuRow -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
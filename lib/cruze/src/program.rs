@@ -82,6 +82,29 @@ impl Program {
         }
     }
 
+    pub fn get_uniform_location(&self, name: &str) -> gl::types::GLint {
+        let name = CString::new(name).unwrap();
+
+        unsafe {
+            self.gl.GetUniformLocation(self.id, name.as_ptr())
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The program has to be in use, `location`
+    /// from `get_uniform_location` of this program
+    pub unsafe fn set_mat4_at(&self, location: gl::types::GLint, mat: &Matrix4<f32>) {
+        self.gl.UniformMatrix4fv(location, 1, gl::FALSE, mat.as_ptr());
+    }
+
+    /// # Safety
+    ///
+    /// Same as `set_mat4_at`
+    pub unsafe fn set_int_at(&self, location: gl::types::GLint, value: i32) {
+        self.gl.Uniform1i(location, value);
+    }
+
     pub unsafe fn set_bool(&self, name: &str, value: bool) {
         let name = CString::new(name).unwrap();
         let uniform_location = self.gl.GetUniformLocation(self.id, name.as_ptr());
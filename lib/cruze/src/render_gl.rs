@@ -5,11 +5,7 @@ use super::program::{
 };
 
 use super::canvas;
-use super::font_manager::FontManager;
-use super::widgets::{
-    Widget,
-    Rect
-};
+use super::renderer::Backend;
 
 use std;
 use std::ffi::{
@@ -22,6 +18,8 @@ use cgmath::{
 
 use cgmath::prelude::*;
 
+use lyon::math::Rect;
+
 struct GlGlyphTexture {
     name: gl::types::GLuint,
     gl: gl::Gl,
@@ -71,21 +69,124 @@ impl Drop for GlGlyphTexture {
     }
 }
 
-pub struct Renderer {
+// Holds the per primitive data (see canvas::PRIMITIVE_TEXELS)
+// as a buffer texture, so that the shader can look it up from
+// the primitive index stored in every vertex
+struct GlPrimitiveBuffer {
+    buffer: gl::types::GLuint,
+    name: gl::types::GLuint,
+    gl: gl::Gl,
+}
+
+impl GlPrimitiveBuffer {
+    fn new(gl: gl::Gl) -> Self {
+        let (mut buffer, mut name) = (0, 0);
+
+        unsafe {
+            gl.GenBuffers(1, &mut buffer);
+            gl.GenTextures(1, &mut name);
+
+            gl.BindBuffer(gl::TEXTURE_BUFFER, buffer);
+            gl.BindTexture(gl::TEXTURE_BUFFER, name);
+            gl.TexBuffer(gl::TEXTURE_BUFFER, gl::RGBA32F, buffer);
+
+            gl.BindTexture(gl::TEXTURE_BUFFER, 0);
+            gl.BindBuffer(gl::TEXTURE_BUFFER, 0);
+        }
+
+        Self {
+            buffer,
+            name,
+            gl
+        }
+    }
+}
+
+impl Drop for GlPrimitiveBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.name);
+            self.gl.DeleteBuffers(1, &self.buffer);
+        }
+    }
+}
+
+// Multisampled color buffer frames are painted into. Unlike the
+// window back buffer its content survives `swap_buffers`, so a
+// frame only needs to repaint the damaged regions before it is
+// resolved on the window.
+struct GlFramebuffer {
+    name: gl::types::GLuint,
+    renderbuffer: gl::types::GLuint,
+    gl: gl::Gl,
+}
+
+impl GlFramebuffer {
+    fn new(gl: gl::Gl, samples: i32, (width, height): (u32, u32)) -> Self {
+        let (mut name, mut renderbuffer) = (0, 0);
+
+        unsafe {
+            gl.GenRenderbuffers(1, &mut renderbuffer);
+            gl.BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+            gl.RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                samples,
+                gl::RGBA8,
+                width as _,
+                height as _,
+            );
+            gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            gl.GenFramebuffers(1, &mut name);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, name);
+            gl.FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                renderbuffer
+            );
+            gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+
+        Self {
+            name,
+            renderbuffer,
+            gl
+        }
+    }
+}
+
+impl Drop for GlFramebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteFramebuffers(1, &self.name);
+            self.gl.DeleteRenderbuffers(1, &self.renderbuffer);
+        }
+    }
+}
+
+pub struct GlBackend {
     gl: gl::Gl,
-    canvas_data: canvas::CanvasData,
     program: Program,
     projection: Matrix4<f32>,
-    model: Matrix4<f32>,
+    projection_location: gl::types::GLint,
+    font_tex_location: gl::types::GLint,
+    primitives_location: gl::types::GLint,
     texture: GlGlyphTexture,
+    primitive_buffer: GlPrimitiveBuffer,
+    framebuffer: Option<GlFramebuffer>,
+    samples: i32,
+    width: u32,
+    height: u32,
     vao: gl::types::GLuint,
-    pbo: gl::types::GLuint,
-    tbo: gl::types::GLuint,
+    vbo: gl::types::GLuint,
     ebo: gl::types::GLuint,
 }
 
-impl Renderer {
-    pub fn new(gl: &gl::Gl) -> Renderer {
+impl GlBackend {
+    // Antialiasing is done by the offscreen framebuffer, the
+    // window itself does not need to be multisampled
+    pub fn new(gl: &gl::Gl, samples: u16) -> GlBackend {
         // TODO Remove CString boilerplate, put
         // in shader
         let vert_shader = Shader::from_vert_source(
@@ -105,16 +206,32 @@ impl Renderer {
             )
             .unwrap();
 
-        let mut renderer = Renderer {
+        // Look uniforms up once, they are set every frame
+        let projection_location = program.get_uniform_location("projection");
+        let font_tex_location = program.get_uniform_location("font_tex");
+        let primitives_location = program.get_uniform_location("primitives");
+
+        let mut max_samples = 0;
+
+        unsafe {
+            gl.GetIntegerv(gl::MAX_SAMPLES, &mut max_samples);
+        }
+
+        let mut backend = GlBackend {
             texture: GlGlyphTexture::new(gl.clone(), (512, 512)),
+            primitive_buffer: GlPrimitiveBuffer::new(gl.clone()),
+            framebuffer: None,
+            samples: (samples as i32).min(max_samples),
+            width: 0,
+            height: 0,
             projection: Matrix4::identity(),
-            model: Matrix4::identity(),
+            projection_location,
+            font_tex_location,
+            primitives_location,
             gl: gl.clone(),
-            canvas_data: canvas::CanvasData::new(),
             program: program,
             vao: 0,
-            pbo: 0,
-            tbo: 0,
+            vbo: 0,
             ebo: 0,
         };
 
@@ -125,80 +242,97 @@ impl Renderer {
             gl.Enable(gl::MULTISAMPLE);
         }
 
-        renderer.create_vertex_arrays();
-
-        renderer
-    }
-
-    fn generate_geometry_primitives(&mut self, children: &Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
-        self.canvas_data = canvas::generate_mesh_from_widget(&children, font_manager);
-        //self.canvas_data = canvas::generate_mesh();
+        backend.create_vertex_arrays();
 
-        self.bind_vertex_arrays();
+        backend
     }
 
     fn create_vertex_arrays(&mut self) {
-        // pbo = PRIMITIVE Buffer Object, keeps everything related to primitive
-        // tbo = TEXT Buffer Object, keeps all data relative to font rendering
+        // vbo = VERTEX Buffer Object, paths and glyphs, see canvas::VERTEX_SIZE
         // ebo = ELEMENT Buffer Object, data relative to Indices
-        let (vao, pbo, tbo, ebo) = unsafe {
+        let (vao, vbo, ebo) = unsafe {
             let gl = self.gl.clone();
 
-            let (mut vao, mut pbo, mut tbo, mut ebo) = (0, 0, 0, 0);
+            let (mut vao, mut vbo, mut ebo) = (0, 0, 0);
 
             gl.GenVertexArrays(1, &mut vao);
             gl.BindVertexArray(vao);
 
-            gl.GenBuffers(1, &mut pbo);
+            gl.GenBuffers(1, &mut vbo);
             gl.GenBuffers(1, &mut ebo);
-            gl.GenBuffers(1, &mut tbo);
+
+            gl.BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+
+            let stride = (canvas::VERTEX_SIZE * std::mem::size_of::<f32>()) as gl::types::GLint;
+
+            // Position (0), TexCoords (1), PrimitiveIndex (2),
+            // recorded once in the VAO
+            gl.EnableVertexAttribArray(0);
+            gl.EnableVertexAttribArray(1);
+            gl.EnableVertexAttribArray(2);
+
+            gl.VertexAttribPointer(
+                0,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                std::ptr::null()
+            );
+
+            gl.VertexAttribPointer(
+                1,
+                2,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (2 * std::mem::size_of::<gl::types::GLfloat>()) as _,
+            );
+
+            gl.VertexAttribPointer(
+                2,
+                1,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (4 * std::mem::size_of::<gl::types::GLfloat>()) as _,
+            );
 
             // Unbind VAO
             gl.BindVertexArray(0);
+            gl.BindBuffer(gl::ARRAY_BUFFER, 0);
 
-            (vao, pbo, tbo, ebo)
+            (vao, vbo, ebo)
         };
 
         self.vao = vao;
-        self.pbo = pbo;
-        self.tbo = tbo;
+        self.vbo = vbo;
         self.ebo = ebo;
     }
+}
 
-    fn bind_vertex_arrays(&mut self) {
+impl Drop for GlBackend {
+    fn drop(&mut self) {
         unsafe {
-            let gl = self.gl.clone();
-
-            gl.BindBuffer(gl::ARRAY_BUFFER, self.pbo);
-            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
-
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (self.canvas_data.vertices.len() * std::mem::size_of::<gl::types::GLuint>()) as gl::types::GLsizeiptr,
-                self.canvas_data.vertices.as_ptr() as *const gl::types::GLvoid,
-                gl::STATIC_DRAW
-            );
-
-            gl.BufferData(
-                gl::ELEMENT_ARRAY_BUFFER,
-                (self.canvas_data.indices.len() * std::mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
-                self.canvas_data.indices.as_ptr() as *const gl::types::GLvoid,
-                gl::STATIC_DRAW
-            );
-
-            gl.BindBuffer(gl::ARRAY_BUFFER, self.tbo);
+            self.gl.DeleteVertexArrays(1, &self.vao);
+            self.gl.DeleteBuffers(1, &self.vbo);
+            self.gl.DeleteBuffers(1, &self.ebo);
+        }
+    }
+}
 
-            gl.BufferData(
-                gl::ARRAY_BUFFER,
-                (self.canvas_data.glyph_vertices.len() * std::mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
-                self.canvas_data.glyph_vertices.as_ptr() as *const gl::types::GLvoid,
-                gl::STATIC_DRAW
-            );
+impl GlBackend {
+    fn upload_glyphs(&mut self, canvas_data: &canvas::CanvasData) {
+        unsafe {
+            let gl = self.gl.clone();
 
             // Send texture to GPU
-            for texture_data in self.canvas_data.glyph_tex_data.iter() {
-                self.gl.BindTexture(gl::TEXTURE_2D, self.texture.name);
-                self.gl.TexSubImage2D(
+            gl.ActiveTexture(gl::TEXTURE0);
+            gl.BindTexture(gl::TEXTURE_2D, self.texture.name);
+
+            for texture_data in canvas_data.glyph_tex_data.iter() {
+                gl.TexSubImage2D(
                     gl::TEXTURE_2D,
                     0,
                     texture_data.rect.min.x as _,
@@ -210,128 +344,208 @@ impl Renderer {
                     texture_data.data.as_ptr() as _,
                 );
             }
+        }
+    }
+}
 
-            // Unbind both VBO and VAO
-            gl.BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl.BindVertexArray(0);
+impl Backend for GlBackend {
+    fn set_viewport(&mut self, width: u32, height: u32, projection: &Matrix4<f32>) {
+        self.projection = *projection;
+
+        if width != self.width || height != self.height || self.framebuffer.is_none() {
+            // The old content is lost, the renderer
+            // repaints everything after a resize
+            self.framebuffer = Some(GlFramebuffer::new(self.gl.clone(), self.samples, (width, height)));
+            self.width = width;
+            self.height = height;
+        }
+
+        unsafe {
+            self.gl.Viewport(0, 0, width as i32, height as i32);
         }
     }
 
-    pub fn draw(&mut self) {
+    fn begin_frame(&mut self) {
         unsafe {
             let gl = self.gl.clone();
 
-            gl.ClearColor(0.3, 0.3, 0.5, 0.1);
-            gl.Clear(gl::COLOR_BUFFER_BIT);
+            if let Some(framebuffer) = &self.framebuffer {
+                gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer.name);
+            }
 
             self.program.set_used();
 
-            self.program.set_mat4("projection", &self.projection);
-            self.program.set_mat4("model", &self.model);
+            self.program.set_mat4_at(self.projection_location, &self.projection);
 
-            gl.BindVertexArray(self.vao);
+            // Glyph texture on unit 0, primitive data on unit 1
+            gl.ActiveTexture(gl::TEXTURE0);
+            gl.BindTexture(gl::TEXTURE_2D, self.texture.name);
+            self.program.set_int_at(self.font_tex_location, 0);
 
-            self.draw_primitives();
+            gl.ActiveTexture(gl::TEXTURE1);
+            gl.BindTexture(gl::TEXTURE_BUFFER, self.primitive_buffer.name);
+            self.program.set_int_at(self.primitives_location, 1);
+
+            gl.ActiveTexture(gl::TEXTURE0);
+
+            gl.BindVertexArray(self.vao);
         }
     }
 
-    pub fn draw_primitives(&mut self) {
-        let gl = self.gl.clone();
-
-        let mut tris_offset = 0;
-        let mut glyph_offset = 0;
-
-        for primitive in self.canvas_data.primitives.iter() {
-            unsafe {
-                self.program.set_vec4("bbox", &primitive.bbox);
-                self.program.set_gradient(&primitive.gradient);
-                self.program.get_bool("is_textured");
-                self.program.set_mat4("model", &primitive.model);
-
-                match primitive.kind {
-                    canvas::PrimitiveType::Path => {
-                        self.program.set_bool("is_textured", false);
-
-                        gl.BindBuffer(gl::ARRAY_BUFFER, self.pbo);
-                        gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
-
-                        // Enable location "Position (0)" in Vertex Shader
-                        // This must be done AFTER buffers have been
-                        // filled with data
-                        // Disable only TexCoord;
-                        gl.DisableVertexAttribArray(1);
-                        gl.EnableVertexAttribArray(0);
-
-                        gl.VertexAttribPointer(
-                            0,
-                            3,
-                            gl::FLOAT,
-                            gl::FALSE,
-                            (3 * std::mem::size_of::<f32>()) as gl::types::GLint,
-                            std::ptr::null()
-                        );
-
-                        gl.DrawElements(
-                            gl::TRIANGLES,
-                            primitive.num_vertices as i32,
-                            gl::UNSIGNED_INT,
-                            (tris_offset * std::mem::size_of::<gl::types::GLuint>())
-                            as *const std::ffi::c_void
-                        );
-
-                        tris_offset += primitive.num_vertices as usize;
-                    },
-                    canvas::PrimitiveType::Text => {
-                        self.program.set_bool("is_textured", true);
-                        self.program.set_texture("font_tex", self.texture.name);
-
-                        gl.BindBuffer(gl::ARRAY_BUFFER, self.tbo);
-
-                        gl.EnableVertexAttribArray(0);
-                        gl.EnableVertexAttribArray(1);
-
-                        gl.VertexAttribPointer(
-                            0,
-                            3,
-                            gl::FLOAT,
-                            gl::FALSE,
-                            (5 * std::mem::size_of::<f32>()) as gl::types::GLint,
-                            std::ptr::null()
-                        );
-
-                        gl.VertexAttribPointer(
-                            1,
-                            2,
-                            gl::FLOAT,
-                            gl::FALSE,
-                            (5 * std::mem::size_of::<f32>()) as gl::types::GLint,
-                            (3 * std::mem::size_of::<gl::types::GLfloat>()) as _,
-                        );
-
-                        for _i in 0..primitive.num_vertices {
-                            gl.DrawArrays(gl::TRIANGLE_STRIP, glyph_offset, 4);
-                            glyph_offset += 4;
-                        }
-                    }
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        unsafe {
+            match clip {
+                Some(clip) => {
+                    self.gl.Enable(gl::SCISSOR_TEST);
+                    self.gl.Scissor(
+                        clip.min_x() as i32,
+                        clip.min_y() as i32,
+                        clip.size.width as i32,
+                        clip.size.height as i32
+                    );
+                },
+                None => {
+                    self.gl.Disable(gl::SCISSOR_TEST);
                 }
             }
         }
     }
 
-    pub fn resize(&mut self, size: glutin::dpi::LogicalSize, children: &Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
-        self.generate_geometry_primitives(&children, font_manager);
+    fn clear(&mut self, color: canvas::Color) {
+        unsafe {
+            let color = color.to_vec();
 
+            self.gl.ClearColor(color.x, color.y, color.z, color.w);
+            self.gl.Clear(gl::COLOR_BUFFER_BIT);
+        }
+    }
+
+    fn upload_geometry(&mut self, canvas_data: &canvas::CanvasData) {
         unsafe {
-            self.projection = cgmath::ortho(
-                0.0,
-                size.width as f32,
-                0.0,
-                size.height as f32,
-                -1.0,
-                1.0
+            let gl = self.gl.clone();
+
+            gl.BindVertexArray(self.vao);
+
+            gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl.BufferData(
+                gl::ARRAY_BUFFER,
+                (canvas_data.vertices.len() * std::mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
+                canvas_data.vertices.as_ptr() as *const gl::types::GLvoid,
+                gl::STATIC_DRAW
+            );
+
+            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl.BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                (canvas_data.indices.len() * std::mem::size_of::<gl::types::GLuint>()) as gl::types::GLsizeiptr,
+                canvas_data.indices.as_ptr() as *const gl::types::GLvoid,
+                gl::STATIC_DRAW
             );
 
-            self.gl.Viewport(0, 0, size.width as i32, size.height as i32);
+            gl.BindBuffer(gl::TEXTURE_BUFFER, self.primitive_buffer.buffer);
+            gl.BufferData(
+                gl::TEXTURE_BUFFER,
+                (canvas_data.primitive_data.len() * std::mem::size_of::<gl::types::GLfloat>()) as gl::types::GLsizeiptr,
+                canvas_data.primitive_data.as_ptr() as *const gl::types::GLvoid,
+                gl::STATIC_DRAW
+            );
+            gl.BindBuffer(gl::TEXTURE_BUFFER, 0);
+
+            // Unbind both VBO and VAO
+            gl.BindVertexArray(0);
+            gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        self.upload_glyphs(canvas_data);
+    }
+
+    fn update_geometry(&mut self, canvas_data: &canvas::CanvasData, ranges: &[canvas::GeometryRange]) {
+        let float_size = std::mem::size_of::<gl::types::GLfloat>();
+        let index_size = std::mem::size_of::<gl::types::GLuint>();
+
+        unsafe {
+            let gl = self.gl.clone();
+
+            gl.BindVertexArray(self.vao);
+            gl.BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl.BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl.BindBuffer(gl::TEXTURE_BUFFER, self.primitive_buffer.buffer);
+
+            for range in ranges.iter() {
+                let vertices = range.vertices.start * canvas::VERTEX_SIZE..range.vertices.end * canvas::VERTEX_SIZE;
+                let primitives = range.primitives.start * canvas::PRIMITIVE_DATA_SIZE..range.primitives.end * canvas::PRIMITIVE_DATA_SIZE;
+
+                if !vertices.is_empty() {
+                    gl.BufferSubData(
+                        gl::ARRAY_BUFFER,
+                        (vertices.start * float_size) as gl::types::GLintptr,
+                        (vertices.len() * float_size) as gl::types::GLsizeiptr,
+                        canvas_data.vertices[vertices].as_ptr() as *const gl::types::GLvoid
+                    );
+                }
+
+                if !range.indices.is_empty() {
+                    gl.BufferSubData(
+                        gl::ELEMENT_ARRAY_BUFFER,
+                        (range.indices.start * index_size) as gl::types::GLintptr,
+                        (range.indices.len() * index_size) as gl::types::GLsizeiptr,
+                        canvas_data.indices[range.indices.clone()].as_ptr() as *const gl::types::GLvoid
+                    );
+                }
+
+                if !primitives.is_empty() {
+                    gl.BufferSubData(
+                        gl::TEXTURE_BUFFER,
+                        (primitives.start * float_size) as gl::types::GLintptr,
+                        (primitives.len() * float_size) as gl::types::GLsizeiptr,
+                        canvas_data.primitive_data[primitives].as_ptr() as *const gl::types::GLvoid
+                    );
+                }
+            }
+
+            gl.BindBuffer(gl::TEXTURE_BUFFER, 0);
+            gl.BindVertexArray(0);
+            gl.BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        self.upload_glyphs(canvas_data);
+    }
+
+    fn draw_batch(&mut self, offset: usize, count: usize) {
+        unsafe {
+            self.gl.DrawElements(
+                gl::TRIANGLES,
+                count as i32,
+                gl::UNSIGNED_INT,
+                (offset * std::mem::size_of::<gl::types::GLuint>())
+                as *const std::ffi::c_void
+            );
+        }
+    }
+
+    fn end_frame(&mut self) {
+        unsafe {
+            let gl = self.gl.clone();
+
+            gl.BindVertexArray(0);
+
+            // Resolve the whole offscreen buffer on the window,
+            // the scissor would limit the blit as well
+            if let Some(framebuffer) = &self.framebuffer {
+                let (width, height) = (self.width as i32, self.height as i32);
+
+                gl.Disable(gl::SCISSOR_TEST);
+                gl.BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer.name);
+                gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+                gl.BlitFramebuffer(
+                    0, 0, width, height,
+                    0, 0, width, height,
+                    gl::COLOR_BUFFER_BIT,
+                    gl::NEAREST
+                );
+            }
+
+            gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
         }
     }
 }
@@ -0,0 +1,397 @@
+use super::canvas;
+use super::renderer::Backend;
+
+use cgmath::{
+    Matrix4,
+    Vector2,
+    Vector4,
+};
+
+use cgmath::prelude::*;
+
+use lyon::math::Rect;
+
+// Same size as the glyph texture allocated by the GL backend
+const ATLAS_SIZE: usize = 512;
+
+// Per-vertex data the fragment stage needs: the position before
+// `model` and `projection` are applied (used for the gradient)
+// and the texture coordinates for glyphs
+#[derive(Clone, Copy)]
+struct SoftVertex {
+    primitive: usize,
+    screen: Vector2<f32>,
+    position: Vector2<f32>,
+    tex_coords: Vector2<f32>,
+}
+
+// Per-primitive data as unpacked by `triangle.vert`
+struct SoftPrimitive {
+    first_color: Vector4<f32>,
+    last_color: Vector4<f32>,
+    start_pos: Vector2<f32>,
+    end_pos: Vector2<f32>,
+    bbox: Vector4<f32>,
+    gradient_type: u32,
+    radius: f32,
+    is_textured: bool,
+    model: Matrix4<f32>,
+    // Min and max corners in window coordinates
+    clip: Vector4<f32>,
+}
+
+impl SoftPrimitive {
+    fn unpack(data: &[f32]) -> SoftPrimitive {
+        let texel = |i: usize| Vector4::new(data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]);
+
+        SoftPrimitive {
+            first_color: texel(0),
+            last_color: texel(1),
+            start_pos: Vector2::new(data[8], data[9]),
+            end_pos: Vector2::new(data[10], data[11]),
+            bbox: texel(3),
+            gradient_type: data[16] as u32,
+            radius: data[17],
+            is_textured: data[18] as i32 == 1,
+            model: Matrix4::from_cols(texel(5), texel(6), texel(7), texel(8)),
+            clip: texel(9),
+        }
+    }
+}
+
+// Pure CPU implementation of `Backend`: it rasterizes the triangles
+// of a `CanvasData` into an RGBA buffer, mimicking what
+// `triangle.vert` and `triangle.frag` do on the GPU.
+// Rows are stored top to bottom, as they appear on screen.
+pub struct SoftwareBackend {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    projection: Matrix4<f32>,
+    vertices: Vec<f32>,
+    indices: Vec<u32>,
+    primitives: Vec<SoftPrimitive>,
+    atlas: Vec<u8>,
+    // Min and max corners in window coordinates
+    clip: Option<(u32, u32, u32, u32)>,
+}
+
+impl SoftwareBackend {
+    pub fn new(width: u32, height: u32) -> SoftwareBackend {
+        SoftwareBackend {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+            projection: Matrix4::identity(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            primitives: Vec::new(),
+            atlas: vec![0; ATLAS_SIZE * ATLAS_SIZE],
+            clip: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn to_image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(self.width, self.height, self.pixels.clone()).unwrap()
+    }
+
+    fn vertex(&self, index: usize) -> SoftVertex {
+        let data = &self.vertices[index * canvas::VERTEX_SIZE..(index + 1) * canvas::VERTEX_SIZE];
+
+        let tex_coords = Vector2::new(data[2], data[3]);
+        let primitive_index = data[4] as usize;
+        let primitive = &self.primitives[primitive_index];
+
+        // Same transformation as `triangle.vert`
+        let position = if primitive.is_textured {
+            Vector2::new(data[0], primitive.bbox.x - data[1])
+        } else {
+            Vector2::new(data[0], data[1])
+        };
+
+        let clip = self.projection * primitive.model * Vector4::new(position.x, position.y, 1.0, 1.0);
+
+        // From NDC to window coordinates, origin in the bottom left corner
+        let screen = Vector2::new(
+            (clip.x / clip.w + 1.0) / 2.0 * self.width as f32,
+            (clip.y / clip.w + 1.0) / 2.0 * self.height as f32,
+        );
+
+        SoftVertex {
+            primitive: primitive_index,
+            screen,
+            position,
+            tex_coords,
+        }
+    }
+
+    fn gradient_color(primitive: &SoftPrimitive, position: Vector2<f32>) -> Vector4<f32> {
+        // Same math as `triangle.frag`
+        // BBox is TOP, RIGHT, BOTTOM, LEFT
+        let bbox = primitive.bbox;
+
+        let x = (position.x - bbox.w) / (bbox.y - bbox.w);
+        let y = (position.y - bbox.z) / (bbox.x - bbox.z);
+
+        let factor = if primitive.gradient_type == 0 {
+            let relative_position = Vector2::new(x, y) - primitive.start_pos;
+            let gradient_direction = primitive.end_pos - primitive.start_pos;
+
+            relative_position.dot(gradient_direction) / gradient_direction.dot(gradient_direction)
+        } else {
+            // start_pos is the center
+            let relative_position = position - primitive.start_pos;
+
+            relative_position.magnitude() / primitive.radius
+        };
+
+        // A degenerate bbox gives NaN, solid colors don't care
+        let factor = if factor.is_nan() { 0.0 } else { factor.clamp(0.0, 1.0) };
+
+        primitive.first_color * (1.0 - factor) + primitive.last_color * factor
+    }
+
+    fn sample_atlas(&self, tex_coords: Vector2<f32>) -> f32 {
+        // Bilinear filtering with clamp to edge, like the GL texture
+        let size = ATLAS_SIZE as f32;
+        let x = tex_coords.x * size - 0.5;
+        let y = tex_coords.y * size - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+
+        let texel = |tx: f32, ty: f32| {
+            let tx = (tx as i32).clamp(0, ATLAS_SIZE as i32 - 1) as usize;
+            let ty = (ty as i32).clamp(0, ATLAS_SIZE as i32 - 1) as usize;
+
+            self.atlas[ty * ATLAS_SIZE + tx] as f32 / 255.0
+        };
+
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+        let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    fn upload_glyphs(&mut self, canvas_data: &canvas::CanvasData) {
+        for texture_data in canvas_data.glyph_tex_data.iter() {
+            let rect = texture_data.rect;
+            let width = rect.width() as usize;
+
+            for (row, line) in texture_data.data.chunks(width).enumerate() {
+                let start = (rect.min.y as usize + row) * ATLAS_SIZE + rect.min.x as usize;
+
+                self.atlas[start..start + width].copy_from_slice(line);
+            }
+        }
+    }
+
+    // Pixels that can be written, as min and max corners
+    fn clip_bounds(&self) -> (u32, u32, u32, u32) {
+        match self.clip {
+            Some(clip) => clip,
+            None => (0, 0, self.width, self.height),
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Vector4<f32>) {
+        // glBlendFunc(SRC_ALPHA, ONE_MINUS_SRC_ALPHA), y is
+        // flipped since rows are stored top to bottom
+        let offset = (((self.height - 1 - y) * self.width + x) * 4) as usize;
+        let alpha = color.w.clamp(0.0, 1.0);
+        let source = [color.x, color.y, color.z, color.w];
+
+        for (channel, value) in source.iter().enumerate() {
+            let destination = self.pixels[offset + channel] as f32 / 255.0;
+            let blended = value.clamp(0.0, 1.0) * alpha + destination * (1.0 - alpha);
+
+            self.pixels[offset + channel] = (blended * 255.0).round() as u8;
+        }
+    }
+
+    fn rasterize_triangle(&mut self, vertices: [SoftVertex; 3]) {
+        // Per primitive data is flat, as in the shader
+        let is_textured = self.primitives[vertices[0].primitive].is_textured;
+        let primitive_clip = self.primitives[vertices[0].primitive].clip;
+
+        let (a, b, c) = (vertices[0].screen, vertices[1].screen, vertices[2].screen);
+
+        let edge = |p: Vector2<f32>, q: Vector2<f32>, r: Vector2<f32>| {
+            (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x)
+        };
+
+        let area = edge(a, b, c);
+
+        if area == 0.0 || area.is_nan() {
+            return;
+        }
+
+        // Top-left fill rule, so that pixels on edges shared by two
+        // triangles are blended exactly once
+        let is_top_left = |p: Vector2<f32>, q: Vector2<f32>| {
+            let (p, q) = if area > 0.0 { (p, q) } else { (q, p) };
+
+            (p.y == q.y && q.x < p.x) || q.y > p.y
+        };
+
+        let bias = [
+            if is_top_left(b, c) { 0.0 } else { f32::EPSILON },
+            if is_top_left(c, a) { 0.0 } else { f32::EPSILON },
+            if is_top_left(a, b) { 0.0 } else { f32::EPSILON },
+        ];
+
+        let (clip_min_x, clip_min_y, clip_max_x, clip_max_y) = self.clip_bounds();
+
+        let min_x = (a.x.min(b.x).min(c.x).floor().max(0.0) as u32).max(clip_min_x);
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as u32).min(clip_max_x);
+        let min_y = (a.y.min(b.y).min(c.y).floor().max(0.0) as u32).max(clip_min_y);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as u32).min(clip_max_y);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // Sample at the pixel center
+                let p = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+                let w0 = edge(b, c, p) / area;
+                let w1 = edge(c, a, p) / area;
+                let w2 = edge(a, b, p) / area;
+
+                if w0 < bias[0] || w1 < bias[1] || w2 < bias[2] {
+                    continue;
+                }
+
+                // Same test as the discard in `triangle.frag`
+                if p.x < primitive_clip.x || p.y < primitive_clip.y || p.x >= primitive_clip.z || p.y >= primitive_clip.w {
+                    continue;
+                }
+
+                let position = vertices[0].position * w0
+                    + vertices[1].position * w1
+                    + vertices[2].position * w2;
+
+                let mut color = SoftwareBackend::gradient_color(&self.primitives[vertices[0].primitive], position);
+
+                if is_textured {
+                    let tex_coords = vertices[0].tex_coords * w0
+                        + vertices[1].tex_coords * w1
+                        + vertices[2].tex_coords * w2;
+
+                    color.w *= self.sample_atlas(tex_coords).clamp(0.0, 1.0);
+                }
+
+                self.blend(x, y, color);
+            }
+        }
+    }
+}
+
+impl Backend for SoftwareBackend {
+    fn set_viewport(&mut self, width: u32, height: u32, projection: &Matrix4<f32>) {
+        self.projection = *projection;
+
+        if width != self.width || height != self.height {
+            self.width = width;
+            self.height = height;
+            self.pixels = vec![0; (width * height * 4) as usize];
+            self.clip = None;
+        }
+    }
+
+    fn begin_frame(&mut self) {
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        self.clip = clip.map(|clip| {
+            let clamp = |value: f32, max: u32| (value.max(0.0) as u32).min(max);
+
+            (
+                clamp(clip.min_x(), self.width),
+                clamp(clip.min_y(), self.height),
+                clamp(clip.max_x(), self.width),
+                clamp(clip.max_y(), self.height),
+            )
+        });
+    }
+
+    fn clear(&mut self, color: canvas::Color) {
+        let color = color.to_vec();
+        let color = [color.x, color.y, color.z, color.w];
+        let color: Vec<u8> = color
+            .iter()
+            .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect();
+
+        let (min_x, min_y, max_x, max_y) = self.clip_bounds();
+
+        for y in min_y..max_y {
+            // Rows are stored top to bottom
+            let row = (self.height - 1 - y) * self.width;
+
+            for x in min_x..max_x {
+                let offset = ((row + x) * 4) as usize;
+
+                self.pixels[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn upload_geometry(&mut self, canvas_data: &canvas::CanvasData) {
+        self.vertices = canvas_data.vertices.clone();
+        self.indices = canvas_data.indices.clone();
+        self.primitives = canvas_data.primitive_data
+            .chunks(canvas::PRIMITIVE_DATA_SIZE)
+            .map(SoftPrimitive::unpack)
+            .collect();
+
+        self.upload_glyphs(canvas_data);
+    }
+
+    fn update_geometry(&mut self, canvas_data: &canvas::CanvasData, ranges: &[canvas::GeometryRange]) {
+        let vertex_size = canvas::VERTEX_SIZE;
+        let primitive_size = canvas::PRIMITIVE_DATA_SIZE;
+
+        for range in ranges.iter() {
+            let vertices = range.vertices.start * vertex_size..range.vertices.end * vertex_size;
+
+            self.vertices[vertices.clone()].copy_from_slice(&canvas_data.vertices[vertices]);
+            self.indices[range.indices.clone()].copy_from_slice(&canvas_data.indices[range.indices.clone()]);
+
+            for primitive in range.primitives.clone() {
+                self.primitives[primitive] = SoftPrimitive::unpack(
+                    &canvas_data.primitive_data[primitive * primitive_size..(primitive + 1) * primitive_size]
+                );
+            }
+        }
+
+        self.upload_glyphs(canvas_data);
+    }
+
+    fn draw_batch(&mut self, offset: usize, count: usize) {
+        for triangle in offset / 3..(offset + count) / 3 {
+            let vertices = [
+                self.vertex(self.indices[triangle * 3] as usize),
+                self.vertex(self.indices[triangle * 3 + 1] as usize),
+                self.vertex(self.indices[triangle * 3 + 2] as usize),
+            ];
+
+            self.rasterize_triangle(vertices);
+        }
+    }
+
+    fn end_frame(&mut self) {
+    }
+}
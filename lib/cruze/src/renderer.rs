@@ -0,0 +1,155 @@
+use super::canvas::{
+    self,
+    CanvasData,
+    Color,
+    Damage,
+    GeometryRange,
+    MeshUpdate,
+};
+
+use super::font_manager::FontManager;
+use super::widgets::Widget;
+
+use cgmath::Matrix4;
+
+use lyon::math::Rect;
+
+use cgmath::prelude::*;
+
+// Everything `Renderer::draw_primitives` needs from the
+// underlying graphics API. Paths and glyphs share the same
+// indexed triangles, gradients and transforms travel with
+// the geometry in `CanvasData::primitive_data`, so a batch
+// is a plain range of indices.
+// Backends keep the pixels of the previous frame around, a frame
+// may only repaint the regions passed to `set_clip`.
+pub trait Backend {
+    fn set_viewport(&mut self, width: u32, height: u32, projection: &Matrix4<f32>);
+    fn begin_frame(&mut self);
+    // Restricts `clear` and `draw_batch` to a rectangle in window
+    // coordinates (origin in the bottom left corner, whole pixels)
+    fn set_clip(&mut self, clip: Option<Rect>);
+    fn clear(&mut self, color: Color);
+    fn upload_geometry(&mut self, canvas_data: &CanvasData);
+    // Only the given ranges of the geometry changed, sizes did not
+    fn update_geometry(&mut self, canvas_data: &CanvasData, ranges: &[GeometryRange]);
+    fn draw_batch(&mut self, offset: usize, count: usize);
+    // Presents the frame
+    fn end_frame(&mut self);
+}
+
+pub struct Renderer<B: Backend> {
+    backend: B,
+    canvas_data: CanvasData,
+    projection: Matrix4<f32>,
+    // Accumulated since the last `draw`
+    damage: Damage,
+    clear_color: Color,
+}
+
+impl<B: Backend> Renderer<B> {
+    pub fn new(backend: B) -> Renderer<B> {
+        Renderer {
+            backend,
+            canvas_data: CanvasData::new(),
+            projection: Matrix4::identity(),
+            damage: Damage::Full,
+            clear_color: Color::from_rgba(0.3, 0.3, 0.5, 0.1),
+        }
+    }
+
+    // Paints the whole frame again with the new color
+    pub fn set_clear_color(&mut self, clear_color: Color) {
+        if clear_color != self.clear_color {
+            self.clear_color = clear_color;
+            self.damage = Damage::Full;
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    // Re-tessellates dirty widgets and sends
+    // only what changed to the backend
+    pub fn update(&mut self, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
+        match canvas::update_mesh_from_widget(&mut self.canvas_data, children, font_manager) {
+            MeshUpdate::Full => {
+                self.backend.upload_geometry(&self.canvas_data);
+            },
+            MeshUpdate::Partial(ranges) => {
+                self.backend.update_geometry(&self.canvas_data, &ranges);
+            },
+            MeshUpdate::Unchanged => ()
+        }
+
+        let damage = std::mem::replace(&mut self.canvas_data.damage, Damage::none());
+
+        self.damage.add(damage);
+    }
+
+    // Whether the next `draw` is going to paint anything
+    pub fn needs_redraw(&self) -> bool {
+        !self.damage.is_empty()
+    }
+
+    pub fn damage(&self) -> &Damage {
+        &self.damage
+    }
+
+    // Repaints the damaged regions only, the rest of the
+    // frame is kept from the previous one
+    pub fn draw(&mut self) {
+        let clear_color = self.clear_color;
+
+        self.backend.begin_frame();
+
+        match std::mem::replace(&mut self.damage, Damage::none()) {
+            Damage::Full => {
+                self.backend.set_clip(None);
+                self.backend.clear(clear_color);
+                self.draw_primitives();
+            },
+            Damage::Regions(regions) => {
+                // The projection maps layout coordinates
+                // one to one on window coordinates
+                for region in regions.iter() {
+                    self.backend.set_clip(Some(region.round_out()));
+                    self.backend.clear(clear_color);
+                    self.draw_primitives();
+                }
+
+                self.backend.set_clip(None);
+            }
+        }
+
+        self.backend.end_frame();
+    }
+
+    pub fn draw_primitives(&mut self) {
+        for batch in self.canvas_data.batches.iter() {
+            self.backend.draw_batch(batch.offset, batch.count);
+        }
+    }
+
+    pub fn resize(&mut self, size: glutin::dpi::LogicalSize, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
+        self.update(children, font_manager);
+
+        self.projection = cgmath::ortho(
+            0.0,
+            size.width as f32,
+            0.0,
+            size.height as f32,
+            -1.0,
+            1.0
+        );
+
+        self.backend.set_viewport(size.width as u32, size.height as u32, &self.projection);
+
+        self.damage = Damage::Full;
+    }
+}
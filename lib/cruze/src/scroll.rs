@@ -0,0 +1,840 @@
+use lyon::math::{
+    Point,
+    Vector,
+    point,
+    rect,
+    vector,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::animation::{
+    Animated,
+    Easing,
+    Transition,
+};
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Orientation,
+    ScrollDelta,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+// How far a notch of the mouse wheel goes
+const LINE_LENGTH: f32 = 48.0;
+// Wheel and page steps are smoothed over this long
+pub(crate) const STEP_DURATION: u64 = 120;
+// Kinetic scrolling loses this fraction of its
+// velocity every second, per unit of time
+const FRICTION: f32 = 4.0;
+// Below this, in pixels per second, kinetic scrolling stops
+const MIN_VELOCITY: f32 = 20.0;
+// Touchpad deltas further apart than this are separate
+// gestures, their velocity is not carried over
+const GESTURE_GAP: Duration = Duration::from_millis(100);
+
+pub(crate) const SCROLLBAR_WIDTH: f32 = 6.0;
+pub(crate) const SCROLLBAR_MARGIN: f32 = 2.0;
+const MIN_THUMB_LENGTH: f32 = 24.0;
+const SCROLLBAR_FADE: u64 = 150;
+
+// How a scrollable widget moves: the offset along its main axis,
+// smoothed wheel steps, touchpad gestures gliding on once the
+// fingers are lifted, the scrollbar thumb being dragged and the
+// scrollbar fading in and out. Offsets go from 0.0 to the
+// `max_offset` passed in, which the widget knows from its layout.
+pub struct Scroller {
+    offset: Animated<f32>,
+    // Of the touchpad, in pixels per second
+    velocity: f32,
+    last_scroll: Instant,
+    // While gliding, when the offset was last moved
+    kinetic: Option<Instant>,
+    // A touchpad gesture is moving the offset
+    tracking: bool,
+    // Cursor position along the main axis and
+    // offset when the thumb was grabbed
+    drag: Option<(f32, f32)>,
+    hovered: bool,
+    thumb_opacity: Animated<f32>,
+}
+
+impl Scroller {
+    pub fn new(offset: f32) -> Scroller {
+        Scroller {
+            offset: Animated::new(offset.max(0.0)),
+            velocity: 0.0,
+            last_scroll: Instant::now(),
+            kinetic: None,
+            tracking: false,
+            drag: None,
+            hovered: false,
+            thumb_opacity: Animated::new(0.0),
+        }
+    }
+
+    pub fn offset(&self) -> f32 {
+        *self.offset.get()
+    }
+
+    // Where the offset is going
+    pub fn target(&self) -> f32 {
+        *self.offset.target()
+    }
+
+    // Without a transition the offset jumps there at once
+    pub fn set_offset(&mut self, offset: f32, max_offset: f32, transition: Option<Transition>) {
+        self.kinetic = None;
+        self.offset.set(offset.max(0.0).min(max_offset), transition);
+
+        self.fade();
+    }
+
+    // Once the content got shorter
+    pub fn clamp(&mut self, max_offset: f32) {
+        if self.offset() > max_offset || self.target() > max_offset {
+            self.kinetic = None;
+            self.offset = Animated::new(max_offset.min(self.offset()));
+        }
+    }
+
+    // `distance` is positive towards the end of the content. Returns
+    // whether the offset could move: at the end already, the event
+    // should go on to the scrollable widgets around.
+    pub fn scroll(&mut self, delta: ScrollDelta, distance: f32, max_offset: f32) -> bool {
+        let now = Instant::now();
+
+        match delta {
+            ScrollDelta::Lines(_) => {
+                let target = (self.target() + distance * LINE_LENGTH).max(0.0).min(max_offset);
+
+                if target == self.target() {
+                    return false;
+                }
+
+                self.kinetic = None;
+                self.offset.set(target, Some(Transition::new(STEP_DURATION).with_easing(Easing::ease_out())));
+            },
+            ScrollDelta::Pixels(_) => {
+                let target = (self.offset() + distance).max(0.0).min(max_offset);
+
+                if target == self.offset() {
+                    return false;
+                }
+
+                let elapsed = now.duration_since(self.last_scroll);
+
+                // Smoothed, touchpad deltas come in unevenly
+                self.velocity = if self.tracking && elapsed < GESTURE_GAP && elapsed.as_secs_f32() > 0.0 {
+                    self.velocity * 0.2 + distance / elapsed.as_secs_f32() * 0.8
+                } else {
+                    0.0
+                };
+
+                self.tracking = true;
+                self.kinetic = None;
+                self.offset.set(target, None);
+            }
+        }
+
+        self.last_scroll = now;
+
+        self.fade();
+
+        true
+    }
+
+    // The fingers left the touchpad, a fling goes on for a while
+    pub fn end_gesture(&mut self) {
+        if !self.tracking {
+            return;
+        }
+
+        self.tracking = false;
+
+        if self.velocity.abs() > MIN_VELOCITY && self.last_scroll.elapsed() < GESTURE_GAP {
+            self.kinetic = Some(Instant::now());
+            self.fade();
+        }
+    }
+
+    pub fn grab(&mut self, cursor: f32) {
+        self.drag = Some((cursor, self.offset()));
+        self.kinetic = None;
+
+        self.fade();
+    }
+
+    // `ratio` is how far the offset goes for every pixel the
+    // thumb moves, returns whether the thumb is being dragged
+    pub fn drag(&mut self, cursor: f32, ratio: f32, max_offset: f32) -> bool {
+        match self.drag {
+            Some((start, start_offset)) => {
+                let offset = start_offset + (cursor - start) * ratio;
+
+                self.offset.set(offset.max(0.0).min(max_offset), None);
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn release(&mut self) {
+        if self.drag.take().is_some() {
+            self.fade();
+        }
+    }
+
+    pub fn set_hovered(&mut self, hovered: bool) {
+        if hovered != self.hovered {
+            self.hovered = hovered;
+
+            self.fade();
+        }
+    }
+
+    // Moves the offset to where it is at `now`, returns whether it changed
+    pub fn tick(&mut self, now: Instant, max_offset: f32) -> bool {
+        let before = self.offset();
+
+        self.offset.sample(now);
+
+        if let Some(last) = self.kinetic {
+            let elapsed = now.duration_since(last).as_secs_f32();
+            let target = (self.offset() + self.velocity * elapsed).max(0.0).min(max_offset);
+
+            self.velocity *= (-FRICTION * elapsed).exp();
+
+            let stopped = self.velocity.abs() < MIN_VELOCITY || target == 0.0 || target == max_offset;
+
+            self.kinetic = if stopped { None } else { Some(now) };
+            self.offset.set(target, None);
+        }
+
+        // The content stopped, the scrollbar can go
+        if !self.thumb_opacity.sample(now) {
+            self.fade();
+        }
+
+        self.offset() != before
+    }
+
+    pub fn thumb_opacity(&self) -> f32 {
+        *self.thumb_opacity.get()
+    }
+
+    pub fn next_tick(&self) -> Option<Instant> {
+        [
+            self.offset.next_tick(),
+            self.thumb_opacity.next_tick(),
+            // Every frame while gliding
+            self.kinetic,
+        ].iter().filter_map(|tick| *tick).min()
+    }
+
+    // The scrollbar shows while the cursor is over
+    // the widget and while the content moves
+    fn fade(&mut self) {
+        let visible = self.hovered || self.drag.is_some() || self.kinetic.is_some() || self.offset.is_running();
+
+        self.thumb_opacity.set(if visible { 1.0 } else { 0.0 }, Some(Transition::new(SCROLLBAR_FADE)));
+    }
+}
+
+// Start and length of the scrollbar thumb in a track,
+// along the main axis
+pub(crate) fn thumb_span(track_start: f32, track_length: f32, viewport: f32, content: f32, offset: f32) -> (f32, f32) {
+    let max_offset = (content - viewport).max(0.0);
+
+    let length = (track_length * viewport / content.max(1.0))
+        .max(MIN_THUMB_LENGTH)
+        .min(track_length);
+
+    let progress = if max_offset > 0.0 { offset / max_offset } else { 0.0 };
+
+    (track_start + (track_length - length) * progress, length)
+}
+
+// Distance along the main axis, positive towards the end of the
+// content, winit deltas go the other way on the vertical axis
+pub(crate) fn scroll_distance(orientation: Orientation, delta: ScrollDelta) -> f32 {
+    let delta = match delta {
+        ScrollDelta::Lines(delta) | ScrollDelta::Pixels(delta) => delta,
+    };
+
+    match orientation {
+        Orientation::Column => -delta.y,
+        // A wheel without a horizontal axis still scrolls rows
+        Orientation::Row if delta.x != 0.0 => delta.x,
+        Orientation::Row => -delta.y,
+    }
+}
+
+// Builds the message emitted with the new scroll offset
+type OnScroll = Box<dyn Fn(f32) -> Box<dyn Any>>;
+
+// Shows a part of its child, which is laid out with no limit
+// along `options.orientation` (Column scrolls vertically, Row
+// horizontally) and clipped to the view. It scrolls with the
+// mouse wheel, the touchpad (with kinetic scrolling once the
+// fingers are lifted), PageUp, PageDown, Home and End, and by
+// dragging the scrollbar drawn over the content while the cursor
+// is over the view. The view must get its size from its parent
+// or from `options.width` and `options.height`, the scrollbar
+// is drawn in `options.text_color`.
+pub struct ScrollView {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The content, then the scrollbar thumb
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    scroller: Scroller,
+    // Offset the children are currently placed with
+    applied: f32,
+    content_size: Size<f32>,
+    // Last offset asked for by the view function
+    requested: Option<f32>,
+    on_scroll: Option<OnScroll>,
+}
+
+impl ScrollView {
+    pub fn new(options: WidgetOptions, content: Element) -> Box<ScrollView> {
+        let thumb = ScrollThumb::new(options.text_color.with_opacity(0.5));
+
+        Box::new(ScrollView {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![content, thumb],
+            dirty: true,
+            scroller: Scroller::new(0.0),
+            applied: 0.0,
+            content_size: Size::new(0.0, 0.0),
+            requested: None,
+            on_scroll: None,
+        })
+    }
+
+    // Where the view starts out. Once it is on screen it scrolls
+    // there again whenever the value passed here changes.
+    pub fn with_offset(mut self: Box<Self>, offset: f32) -> Box<ScrollView> {
+        self.scroller = Scroller::new(offset);
+        self.requested = Some(offset);
+
+        self
+    }
+
+    // Message emitted with the new offset every time it changes,
+    // for every frame of an animation too
+    pub fn on_scroll<M: 'static, F: Fn(f32) -> M + 'static>(mut self: Box<Self>, on_scroll: F) -> Box<ScrollView> {
+        self.on_scroll = Some(Box::new(move |offset| Box::new(on_scroll(offset))));
+
+        self
+    }
+
+    // How far the content is scrolled, from 0.0 to `max_offset`
+    pub fn offset(&self) -> f32 {
+        self.scroller.offset()
+    }
+
+    pub fn max_offset(&self) -> f32 {
+        (self.main(vector(self.content_size.width, self.content_size.height)) - self.viewport_length()).max(0.0)
+    }
+
+    // Without a transition the content jumps there at once
+    pub fn set_offset(&mut self, offset: f32, transition: Option<Transition>) {
+        let max_offset = self.max_offset();
+
+        self.scroller.set_offset(offset, max_offset, transition);
+
+        self.apply_offset(None);
+    }
+
+    fn main(&self, v: Vector) -> f32 {
+        match self.options.orientation {
+            Orientation::Column => v.y,
+            Orientation::Row => v.x,
+        }
+    }
+
+    fn along_main(&self, length: f32) -> Vector {
+        match self.options.orientation {
+            Orientation::Column => vector(0.0, length),
+            Orientation::Row => vector(length, 0.0),
+        }
+    }
+
+    fn viewport_length(&self) -> f32 {
+        self.main(vector(self.size.width, self.size.height))
+    }
+
+    // Start and length of the track along the main axis
+    fn track(&self) -> (f32, f32) {
+        let start = self.main(self.position.to_vector()) + SCROLLBAR_MARGIN;
+
+        (start, self.viewport_length() - SCROLLBAR_MARGIN * 2.0)
+    }
+
+    // The strip along the edge of the view where the scrollbar is
+    fn track_rect(&self) -> lyon::math::Rect {
+        let (start, length) = self.track();
+        let thickness = SCROLLBAR_WIDTH + SCROLLBAR_MARGIN * 2.0;
+
+        match self.options.orientation {
+            Orientation::Column => rect(self.position.x + self.size.width - thickness, start, thickness, length),
+            Orientation::Row => rect(start, self.position.y + self.size.height - thickness, length, thickness),
+        }
+    }
+
+    // Start and length of the thumb along the main axis
+    fn thumb(&self) -> (f32, f32) {
+        let (track_start, track_length) = self.track();
+
+        thumb_span(
+            track_start,
+            track_length,
+            self.viewport_length(),
+            self.main(vector(self.content_size.width, self.content_size.height)),
+            self.offset()
+        )
+    }
+
+    // Moves the content where the offset says, `ctx` is
+    // None when the change does not come from an event
+    fn apply_offset(&mut self, ctx: Option<&mut EventCtx>) {
+        let offset = self.offset();
+
+        if offset != self.applied {
+            let delta = self.along_main(self.applied - offset);
+
+            translate(&mut self.children[0], delta);
+
+            self.applied = offset;
+
+            if let (Some(ctx), Some(on_scroll)) = (ctx, &self.on_scroll) {
+                ctx.emit(on_scroll(offset));
+            }
+        }
+
+        self.update_thumb();
+    }
+
+    fn update_thumb(&mut self) {
+        let (start, length) = self.thumb();
+        let thickness = SCROLLBAR_WIDTH;
+
+        let (position, size): (Point, _) = match self.options.orientation {
+            Orientation::Column => (
+                point(self.position.x + self.size.width - thickness - SCROLLBAR_MARGIN, start),
+                Size::new(thickness, length)
+            ),
+            Orientation::Row => (
+                point(start, self.position.y + self.size.height - thickness - SCROLLBAR_MARGIN),
+                Size::new(length, thickness)
+            ),
+        };
+
+        // Nothing to scroll, nothing to show
+        let opacity = if self.max_offset() > 0.0 { self.scroller.thumb_opacity() } else { 0.0 };
+
+        place_overlay(&mut self.children[1], position, size, opacity);
+    }
+}
+
+// Puts a widget drawn over the others, outside
+// of the layout (a scrollbar thumb), where it belongs
+pub(crate) fn place_overlay(overlay: &mut Element, position: Point, size: Size<f32>, opacity: f32) {
+    overlay.set_position(position);
+    overlay.set_size(size);
+
+    if overlay.options().opacity != opacity {
+        overlay.options_mut().opacity = opacity;
+    }
+}
+
+// Moves a widget and everything inside it, without a new layout
+pub(crate) fn translate(widget: &mut Element, delta: Vector) {
+    widget.set_position(widget.position() + delta);
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            translate(child, delta);
+        }
+    }
+}
+
+impl Widget for ScrollView {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let content = self.children[0].generate_stretch_node(stretch, font_manager);
+
+        // Does not shrink along the main axis, so that
+        // it is as long as the content needs
+        let wrapper = stretch.new_node(
+            Style {
+                flex_direction: self.options.orientation.into(),
+                flex_shrink: 0.0,
+                ..Default::default()
+            },
+            vec![content]
+        ).unwrap();
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: self.options.orientation.into(),
+                flex_grow: self.options.flex,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                padding: self.options.padding,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            vec![wrapper]
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let wrapper = stretch.children(*node).unwrap()[0];
+        let wrapper_layout = stretch.layout(wrapper).unwrap();
+
+        self.content_size = Size::new(wrapper_layout.size.width, wrapper_layout.size.height);
+
+        // The content may have become shorter
+        let max_offset = self.max_offset();
+
+        self.scroller.clamp(max_offset);
+
+        self.applied = self.offset();
+
+        let origin = point(
+            self.position.x + wrapper_layout.location.x,
+            self.position.y + wrapper_layout.location.y
+        ) - self.along_main(self.applied);
+
+        let content = stretch.children(wrapper).unwrap()[0];
+
+        self.children[0].update_layout(stretch, &content, origin);
+
+        self.update_thumb();
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn set_hovered(&mut self, hovered: bool) {
+        self.scroller.set_hovered(hovered);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let mut new = match new.into_any().downcast::<ScrollView>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            let color = self.options.text_color.with_opacity(0.5);
+
+            self.children[1].options_mut().color = color;
+        }
+
+        self.on_scroll = new.on_scroll;
+
+        if new.requested != self.requested {
+            self.requested = new.requested;
+
+            if let Some(offset) = self.requested {
+                self.set_offset(offset, Some(Transition::new(STEP_DURATION)));
+            }
+        }
+
+        // Only the content comes from the view, the thumb stays
+        let thumb = self.children.pop().unwrap();
+
+        new.children.truncate(1);
+
+        let change = change.max(widgets::reconcile(&mut self.children, new.children));
+
+        self.children.push(thumb);
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Scroll(_, delta) => {
+                let distance = scroll_distance(self.options.orientation, *delta);
+                let max_offset = self.max_offset();
+
+                if self.scroller.scroll(*delta, distance, max_offset) {
+                    self.apply_offset(Some(ctx));
+                    ctx.set_handled();
+                }
+            },
+            WidgetEvent::ScrollEnd(_) => self.scroller.end_gesture(),
+            WidgetEvent::MousePress(position) if self.max_offset() > 0.0 && self.track_rect().contains(*position) => {
+                let cursor = self.main(position.to_vector());
+                let (thumb_start, thumb_length) = self.thumb();
+
+                if cursor >= thumb_start && cursor < thumb_start + thumb_length {
+                    self.scroller.grab(cursor);
+                } else {
+                    // A page towards the cursor
+                    let page = if cursor < thumb_start { -self.viewport_length() } else { self.viewport_length() };
+
+                    self.set_offset(self.scroller.target() + page, Some(Transition::new(STEP_DURATION)));
+                }
+
+                self.update_thumb();
+
+                // The content below the scrollbar does not get it
+                ctx.set_handled();
+            },
+            WidgetEvent::MouseMove(position) => {
+                let (_, track_length) = self.track();
+                let (_, thumb_length) = self.thumb();
+                let max_offset = self.max_offset();
+                let ratio = max_offset / (track_length - thumb_length).max(1.0);
+
+                if self.scroller.drag(self.main(position.to_vector()), ratio, max_offset) {
+                    self.apply_offset(Some(ctx));
+                }
+            },
+            WidgetEvent::MouseRelease(_) => self.scroller.release(),
+            WidgetEvent::KeyPress(key) => {
+                let page = self.viewport_length();
+                let step = Some(Transition::new(STEP_DURATION));
+
+                match key {
+                    VirtualKeyCode::PageUp => self.set_offset(self.scroller.target() - page, step),
+                    VirtualKeyCode::PageDown => self.set_offset(self.scroller.target() + page, step),
+                    VirtualKeyCode::Home => self.set_offset(0.0, step),
+                    VirtualKeyCode::End => self.set_offset(self.max_offset(), step),
+                    _ => return,
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::Tick(now) => {
+                let max_offset = self.max_offset();
+
+                self.scroller.tick(*now, max_offset);
+
+                // Moves the content, and the thumb as it fades
+                self.apply_offset(Some(ctx));
+            },
+            _ => ()
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.scroller.next_tick()
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ScrollView"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("ScrollView -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// The scrollbar of a ScrollView, which places it
+// outside of the layout and sets its opacity
+pub(crate) struct ScrollThumb {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    dirty: bool,
+}
+
+impl ScrollThumb {
+    pub(crate) fn new(color: Color) -> Box<ScrollThumb> {
+        Box::new(ScrollThumb {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: "scroll_thumb".to_string(),
+                color,
+                opacity: 0.0,
+                ..Default::default()
+            },
+            dirty: true,
+        })
+    }
+}
+
+impl Widget for ScrollThumb {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.size.width.min(self.size.height) / 2.0);
+        ctx.fill();
+    }
+
+    // Never part of the layout, see `ScrollView::update_thumb`
+    fn generate_stretch_node(&self, stretch: &mut Stretch, _font_manager: &mut FontManager) -> stretch::node::Node {
+        stretch.new_node(Style::default(), vec![]).unwrap()
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, _new: Element) -> Change {
+        Change::None
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "ScrollThumb"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("ScrollThumb -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
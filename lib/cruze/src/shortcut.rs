@@ -0,0 +1,304 @@
+use glutin::event::{
+    ModifiersState,
+    VirtualKeyCode,
+};
+use glutin::window::WindowId;
+
+use std::any::Any;
+use std::fmt;
+
+// Names used to parse and show keys, the first one
+// of a key is the one shown
+const KEY_NAMES: &[(VirtualKeyCode, &str)] = &[
+    (VirtualKeyCode::A, "A"), (VirtualKeyCode::B, "B"), (VirtualKeyCode::C, "C"),
+    (VirtualKeyCode::D, "D"), (VirtualKeyCode::E, "E"), (VirtualKeyCode::F, "F"),
+    (VirtualKeyCode::G, "G"), (VirtualKeyCode::H, "H"), (VirtualKeyCode::I, "I"),
+    (VirtualKeyCode::J, "J"), (VirtualKeyCode::K, "K"), (VirtualKeyCode::L, "L"),
+    (VirtualKeyCode::M, "M"), (VirtualKeyCode::N, "N"), (VirtualKeyCode::O, "O"),
+    (VirtualKeyCode::P, "P"), (VirtualKeyCode::Q, "Q"), (VirtualKeyCode::R, "R"),
+    (VirtualKeyCode::S, "S"), (VirtualKeyCode::T, "T"), (VirtualKeyCode::U, "U"),
+    (VirtualKeyCode::V, "V"), (VirtualKeyCode::W, "W"), (VirtualKeyCode::X, "X"),
+    (VirtualKeyCode::Y, "Y"), (VirtualKeyCode::Z, "Z"),
+    (VirtualKeyCode::Key0, "0"), (VirtualKeyCode::Key1, "1"), (VirtualKeyCode::Key2, "2"),
+    (VirtualKeyCode::Key3, "3"), (VirtualKeyCode::Key4, "4"), (VirtualKeyCode::Key5, "5"),
+    (VirtualKeyCode::Key6, "6"), (VirtualKeyCode::Key7, "7"), (VirtualKeyCode::Key8, "8"),
+    (VirtualKeyCode::Key9, "9"),
+    (VirtualKeyCode::F1, "F1"), (VirtualKeyCode::F2, "F2"), (VirtualKeyCode::F3, "F3"),
+    (VirtualKeyCode::F4, "F4"), (VirtualKeyCode::F5, "F5"), (VirtualKeyCode::F6, "F6"),
+    (VirtualKeyCode::F7, "F7"), (VirtualKeyCode::F8, "F8"), (VirtualKeyCode::F9, "F9"),
+    (VirtualKeyCode::F10, "F10"), (VirtualKeyCode::F11, "F11"), (VirtualKeyCode::F12, "F12"),
+    (VirtualKeyCode::Escape, "Esc"), (VirtualKeyCode::Escape, "Escape"),
+    (VirtualKeyCode::Tab, "Tab"),
+    (VirtualKeyCode::Space, "Space"),
+    (VirtualKeyCode::Return, "Enter"), (VirtualKeyCode::Return, "Return"),
+    (VirtualKeyCode::Back, "Backspace"),
+    (VirtualKeyCode::Delete, "Delete"), (VirtualKeyCode::Delete, "Del"),
+    (VirtualKeyCode::Insert, "Insert"), (VirtualKeyCode::Insert, "Ins"),
+    (VirtualKeyCode::Home, "Home"),
+    (VirtualKeyCode::End, "End"),
+    (VirtualKeyCode::PageUp, "PageUp"),
+    (VirtualKeyCode::PageDown, "PageDown"),
+    (VirtualKeyCode::Left, "Left"),
+    (VirtualKeyCode::Right, "Right"),
+    (VirtualKeyCode::Up, "Up"),
+    (VirtualKeyCode::Down, "Down"),
+    (VirtualKeyCode::Comma, ","),
+    (VirtualKeyCode::Period, "."),
+    (VirtualKeyCode::Minus, "-"),
+    (VirtualKeyCode::Equals, "="),
+    (VirtualKeyCode::Add, "Plus"),
+    (VirtualKeyCode::Slash, "/"),
+];
+
+// A key with the modifiers held down, like Ctrl+Shift+P
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub key: VirtualKeyCode,
+    pub modifiers: ModifiersState,
+}
+
+impl Chord {
+    pub fn new(key: VirtualKeyCode) -> Chord {
+        Chord {
+            key,
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    pub fn ctrl(mut self) -> Chord {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Chord {
+        self.modifiers.shift = true;
+        self
+    }
+
+    pub fn alt(mut self) -> Chord {
+        self.modifiers.alt = true;
+        self
+    }
+
+    // Command on Mac, Windows key on PC
+    pub fn logo(mut self) -> Chord {
+        self.modifiers.logo = true;
+        self
+    }
+
+    // Modifiers and key separated by `+`, case insensitive:
+    // "Ctrl+S", "ctrl+shift+p", "F5"
+    pub fn parse(chord: &str) -> Result<Chord, String> {
+        let parts: Vec<&str> = chord.split('+').map(|part| part.trim()).collect();
+        let (key, modifiers) = parts.split_last().unwrap();
+
+        // "Ctrl++" ends with an empty part
+        let key = if key.is_empty() && parts.len() > 1 { "Plus" } else { key };
+
+        let key = KEY_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(key))
+            .map(|(key, _)| *key)
+            .ok_or(format!("Unknown key in shortcut {}", chord))?;
+
+        let mut result = Chord::new(key);
+
+        for modifier in modifiers.iter().filter(|modifier| !modifier.is_empty()) {
+            result = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => result.ctrl(),
+                "shift" => result.shift(),
+                "alt" | "option" => result.alt(),
+                "logo" | "cmd" | "command" | "super" | "win" => result.logo(),
+                _ => return Err(format!("Unknown modifier {} in shortcut {}", modifier, chord)),
+            };
+        }
+
+        Ok(result)
+    }
+}
+
+// The label shown next to menu items
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+
+        if self.modifiers.logo {
+            write!(f, "Logo+")?;
+        }
+
+        match KEY_NAMES.iter().find(|(key, _)| *key == self.key) {
+            Some((_, name)) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self.key),
+        }
+    }
+}
+
+// The letter or digit of a key, lowercase, as used by mnemonics
+pub(crate) fn key_char(key: VirtualKeyCode) -> Option<char> {
+    let name = Chord::new(key).to_string();
+    let mut chars = name.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(c.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+// Where a shortcut works. A chord is bound at most once in a
+// scope, when it is in several active ones the innermost wins:
+// the widget scopes from the focused widget outwards, then the
+// window, then the global one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShortcutScope {
+    // In any window
+    Global,
+    Window(WindowId),
+    // While the focus is inside the widget with this id,
+    // in whatever window it is
+    Widget(String),
+}
+
+impl ShortcutScope {
+    // How far from the focus the scope is, the
+    // lowest wins. None when it is not active.
+    fn depth(&self, window: WindowId, focus: &[String]) -> Option<usize> {
+        match self {
+            ShortcutScope::Global => Some(focus.len() + 1),
+            ShortcutScope::Window(id) if *id == window => Some(focus.len()),
+            ShortcutScope::Window(_) => None,
+            ShortcutScope::Widget(id) => focus.iter().position(|focused| focused == id),
+        }
+    }
+}
+
+struct Binding {
+    command: String,
+    chord: Chord,
+    scope: ShortcutScope,
+    message: Box<dyn Fn() -> Box<dyn Any>>,
+}
+
+// Commands bound to chords. The message of a command goes to
+// the application of the window the keys were pressed in, when
+// no widget handled them.
+pub struct Shortcuts {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Shortcuts {
+        Shortcuts::new()
+    }
+}
+
+impl Shortcuts {
+    pub fn new() -> Shortcuts {
+        Shortcuts {
+            bindings: vec![],
+        }
+    }
+
+    // Fails if the command is already bound, or
+    // the chord already is in the same scope
+    pub fn bind<M: Clone + 'static>(&mut self, command: &str, chord: Chord, scope: ShortcutScope, message: M) -> Result<(), String> {
+        for binding in self.bindings.iter() {
+            if binding.command == command {
+                return Err(format!("{} is already bound to {}", command, binding.chord));
+            }
+
+            if binding.chord == chord && binding.scope == scope {
+                return Err(format!("{} is already bound to {}", chord, binding.command));
+            }
+        }
+
+        self.bindings.push(Binding {
+            command: command.to_string(),
+            chord,
+            scope,
+            message: Box::new(move || Box::new(message.clone())),
+        });
+
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, command: &str) {
+        self.bindings.retain(|binding| binding.command != command);
+    }
+
+    pub fn chord(&self, command: &str) -> Option<Chord> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.command == command)
+            .map(|binding| binding.chord)
+    }
+
+    // `focus` holds the ids of the focused widget and
+    // of its ancestors, innermost first
+    pub fn lookup(&self, chord: Chord, window: WindowId, focus: &[String]) -> Option<Box<dyn Any>> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.chord == chord)
+            .filter_map(|binding| binding.scope.depth(window, focus).map(|depth| (depth, binding)))
+            .min_by_key(|(depth, _)| *depth)
+            .map(|(_, binding)| (binding.message)())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chords_conflict_only_in_the_same_scope() {
+        let window = unsafe { WindowId::dummy() };
+        let chord = Chord::parse("Ctrl+Up").unwrap();
+        let mut shortcuts = Shortcuts::new();
+
+        shortcuts.bind("move_up", chord, ShortcutScope::Widget("list".to_string()), ()).unwrap();
+
+        assert!(shortcuts.bind("top", chord, ShortcutScope::Widget("tree".to_string()), ()).is_ok());
+        assert!(shortcuts.bind("scroll_up", chord, ShortcutScope::Window(window), ()).is_ok());
+        assert!(shortcuts.bind("global_up", chord, ShortcutScope::Global, ()).is_ok());
+        assert!(shortcuts.bind("first", chord, ShortcutScope::Widget("list".to_string()), ()).is_err());
+    }
+
+    #[test]
+    fn the_innermost_scope_wins() {
+        let window = unsafe { WindowId::dummy() };
+        let chord = Chord::parse("Ctrl+Up").unwrap();
+        let mut shortcuts = Shortcuts::new();
+        let focus = ["row".to_string(), "list".to_string(), "panel".to_string()];
+
+        shortcuts.bind("global_up", chord, ShortcutScope::Global, "global_up").unwrap();
+        shortcuts.bind("panel_up", chord, ShortcutScope::Widget("panel".to_string()), "panel_up").unwrap();
+        shortcuts.bind("list_up", chord, ShortcutScope::Widget("list".to_string()), "list_up").unwrap();
+
+        let command = |focus: &[String]| *shortcuts.lookup(chord, window, focus).unwrap().downcast::<&str>().unwrap();
+
+        assert_eq!(command(&focus), "list_up");
+        assert_eq!(command(&focus[2..]), "panel_up");
+        assert_eq!(command(&[]), "global_up");
+    }
+
+    #[test]
+    fn widget_shortcuts_need_the_focus_inside() {
+        let window = unsafe { WindowId::dummy() };
+        let chord = Chord::parse("Ctrl+Up").unwrap();
+        let mut shortcuts = Shortcuts::new();
+
+        shortcuts.bind("move_up", chord, ShortcutScope::Widget("list".to_string()), "move_up").unwrap();
+
+        assert!(shortcuts.lookup(chord, window, &[]).is_none());
+        assert!(shortcuts.lookup(chord, window, &["row".to_string(), "list".to_string()]).is_some());
+    }
+}
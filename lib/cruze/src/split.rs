@@ -0,0 +1,735 @@
+use lyon::math::{
+    Point,
+    point,
+    rect,
+};
+
+use super::canvas::{
+    Size,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Orientation,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::time::{Duration, Instant};
+
+// Thickness of the dividers, which can be grabbed
+// anywhere across, the line drawn is thinner
+const DIVIDER: f32 = 6.0;
+// Two presses on a divider this close make a double click
+const DOUBLE_CLICK: u64 = 400;
+
+// A pane of a SplitPane, with the sizes it may take
+// along the orientation of the split
+pub struct Pane {
+    content: Element,
+    pub min: f32,
+    pub max: Option<f32>,
+    // Collapsed by double clicking a divider next to it
+    pub collapsible: bool,
+}
+
+impl Pane {
+    pub fn new(content: Element) -> Pane {
+        Pane {
+            content,
+            min: 0.0,
+            max: None,
+            collapsible: false,
+        }
+    }
+
+    pub fn with_min(mut self, min: f32) -> Pane {
+        self.min = min;
+        self
+    }
+
+    pub fn with_max(mut self, max: f32) -> Pane {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn collapsible(mut self) -> Pane {
+        self.collapsible = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limits {
+    min: f32,
+    max: Option<f32>,
+    collapsible: bool,
+}
+
+struct DividerDrag {
+    divider: usize,
+    start: f32,
+    // Of the panes on both sides when the drag started
+    sizes: (f32, f32),
+    moved: bool,
+}
+
+// Builds the message emitted with the new ratios
+type OnResize = Box<dyn Fn(Vec<f32>) -> Box<dyn Any>>;
+
+// Panes side by side (`Orientation::Row`) or stacked (`Column`),
+// sharing the space along the orientation by ratios, with a
+// divider between two panes that is dragged to resize them, within
+// their min and max sizes. Double clicking a divider collapses the
+// collapsible pane next to it, the smaller one if both are, and
+// double clicking it again brings the pane back.
+//
+// The ratios are kept by the split pane, `on_resize` reports them
+// so that they can be saved, and `with_ratios` sets them.
+pub struct SplitPane {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // A PaneView for each pane
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    limits: Vec<Limits>,
+    ratios: Vec<f32>,
+    // Ratio each collapsed pane had
+    collapsed: Vec<Option<f32>>,
+    drag: Option<DividerDrag>,
+    hovered: Option<usize>,
+    last_press: Option<(usize, Instant)>,
+    // Last ratios asked for by the view function
+    requested: Option<Vec<f32>>,
+    on_resize: Option<OnResize>,
+}
+
+impl SplitPane {
+    pub fn new(options: WidgetOptions, panes: Vec<Pane>) -> Box<SplitPane> {
+        let count = panes.len();
+
+        let mut limits = vec![];
+        let mut children = vec![];
+
+        for pane in panes {
+            limits.push(Limits {
+                min: pane.min,
+                max: pane.max,
+                collapsible: pane.collapsible,
+            });
+
+            children.push(PaneView::new(pane.content) as Element);
+        }
+
+        Box::new(SplitPane {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children,
+            dirty: true,
+            limits,
+            ratios: vec![1.0 / count as f32; count],
+            collapsed: vec![None; count],
+            drag: None,
+            hovered: None,
+            last_press: None,
+            requested: None,
+            on_resize: None,
+        })
+    }
+
+    // The share of each pane to begin with. Once the split pane is
+    // on screen it takes them again whenever the value passed here
+    // changes.
+    pub fn with_ratios(mut self: Box<Self>, ratios: Vec<f32>) -> Box<SplitPane> {
+        self.set_ratios(&ratios);
+        self.requested = Some(ratios);
+
+        self
+    }
+
+    // Message emitted with the ratios, adding up to 1.0, once
+    // a divider was dragged or a pane collapsed or restored
+    pub fn on_resize<M: 'static, F: Fn(Vec<f32>) -> M + 'static>(mut self: Box<Self>, on_resize: F) -> Box<SplitPane> {
+        self.on_resize = Some(Box::new(move |ratios| Box::new(on_resize(ratios))));
+
+        self
+    }
+
+    pub fn ratios(&self) -> Vec<f32> {
+        self.ratios.clone()
+    }
+
+    fn set_ratios(&mut self, ratios: &[f32]) {
+        if ratios.len() != self.ratios.len() {
+            return;
+        }
+
+        let total: f32 = ratios.iter().map(|ratio| ratio.max(0.0)).sum();
+
+        if total > 0.0 {
+            self.ratios = ratios.iter().map(|ratio| ratio.max(0.0) / total).collect();
+            self.collapsed = vec![None; self.ratios.len()];
+        }
+    }
+
+    fn is_row(&self) -> bool {
+        match self.options.orientation {
+            Orientation::Row => true,
+            Orientation::Column => false,
+        }
+    }
+
+    // Position along the orientation
+    fn main(&self, position: Point) -> f32 {
+        if self.is_row() { position.x } else { position.y }
+    }
+
+    fn pane_size(&self, index: usize) -> f32 {
+        let size = self.children[index].size();
+
+        if self.is_row() { size.width } else { size.height }
+    }
+
+    // Where the divider after pane `index` is
+    fn divider_rect(&self, index: usize) -> lyon::math::Rect {
+        let pane = &self.children[index];
+        let end = self.main(pane.position()) + self.pane_size(index);
+
+        if self.is_row() {
+            rect(end, self.position.y, DIVIDER, self.size.height)
+        } else {
+            rect(self.position.x, end, self.size.width, DIVIDER)
+        }
+    }
+
+    fn divider_at(&self, position: Point) -> Option<usize> {
+        (0..self.children.len().saturating_sub(1)).find(|index| self.divider_rect(*index).contains(position))
+    }
+
+    // Ratios from the sizes of the panes, with the
+    // ones on both sides of a divider changed
+    fn resize(&mut self, divider: usize, sizes: (f32, f32)) {
+        let total: f32 = (0..self.children.len()).map(|index| self.pane_size(index)).sum();
+
+        if total <= 0.0 {
+            return;
+        }
+
+        for index in 0..self.children.len() {
+            let size = if index == divider {
+                sizes.0
+            } else if index == divider + 1 {
+                sizes.1
+            } else {
+                self.pane_size(index)
+            };
+
+            self.ratios[index] = size / total;
+        }
+    }
+
+    // Collapses the pane next to the divider, or brings it back,
+    // returns whether anything changed
+    fn toggle_collapse(&mut self, divider: usize) -> bool {
+        let (first, second) = (divider, divider + 1);
+
+        // A pane collapsed from this divider comes back
+        let collapsed = [first, second].iter().cloned().find(|index| self.collapsed[*index].is_some());
+
+        if let Some(index) = collapsed {
+            let ratio = self.collapsed[index].take().unwrap();
+            let others: f32 = self.ratios.iter().enumerate().filter(|(other, _)| *other != index).map(|(_, ratio)| ratio).sum();
+
+            // The others make room, keeping their proportions
+            for (other, other_ratio) in self.ratios.iter_mut().enumerate() {
+                if other != index && others > 0.0 {
+                    *other_ratio *= (1.0 - ratio) / others;
+                }
+            }
+
+            self.ratios[index] = ratio;
+
+            return true;
+        }
+
+        let index = match (self.limits[first].collapsible, self.limits[second].collapsible) {
+            (true, true) if self.ratios[second] < self.ratios[first] => second,
+            (true, _) => first,
+            (false, true) => second,
+            (false, false) => return false,
+        };
+
+        let neighbour = if index == first { second } else { first };
+
+        self.collapsed[index] = Some(self.ratios[index]);
+        self.ratios[neighbour] += self.ratios[index];
+        self.ratios[index] = 0.0;
+
+        true
+    }
+}
+
+impl Widget for SplitPane {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+
+        for index in 0..self.children.len().saturating_sub(1) {
+            let divider = self.divider_rect(index);
+
+            let active = self.hovered == Some(index) || self.drag.as_ref().is_some_and(|drag| drag.divider == index);
+            let opacity = if active { 0.5 } else { 0.2 };
+
+            // A line along the middle of the divider
+            let line = if self.is_row() {
+                rect(divider.origin.x + (DIVIDER - 1.0) / 2.0, divider.origin.y, 1.0, divider.size.height)
+            } else {
+                rect(divider.origin.x, divider.origin.y + (DIVIDER - 1.0) / 2.0, divider.size.width, 1.0)
+            };
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color.with_opacity(opacity));
+            ctx.rect(line.origin, line.size.width, line.size.height);
+            ctx.fill();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let row = self.is_row();
+        let main = |dimension: Dimension| if row {
+            stretch::geometry::Size { width: dimension, height: Dimension::Undefined }
+        } else {
+            stretch::geometry::Size { width: Dimension::Undefined, height: dimension }
+        };
+
+        let mut nodes = vec![];
+
+        for (index, pane) in self.children.iter().enumerate() {
+            if index > 0 {
+                nodes.push(stretch.new_node(
+                    Style {
+                        flex_shrink: 0.0,
+                        size: main(Dimension::Points(DIVIDER)),
+                        ..Default::default()
+                    },
+                    vec![]
+                ).unwrap());
+            }
+
+            let node = pane.generate_stretch_node(stretch, font_manager);
+            let limits = self.limits[index];
+
+            let (min, max) = match self.collapsed[index] {
+                Some(_) => (Dimension::Points(0.0), Dimension::Points(0.0)),
+                None => (Dimension::Points(limits.min), limits.max.map_or(Dimension::Undefined, Dimension::Points)),
+            };
+
+            // Flex grows adding up to less than 1.0 would
+            // leave part of the space free
+            stretch.set_style(node, Style {
+                flex_grow: self.ratios[index] * 100.0,
+                flex_shrink: 1.0,
+                flex_basis: Dimension::Points(0.0),
+                min_size: main(min),
+                max_size: main(max),
+                ..Default::default()
+            }).unwrap();
+
+            nodes.push(node);
+        }
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: self.options.orientation.into(),
+                flex_grow: self.options.flex,
+                min_size: size,
+                max_size: size,
+                padding: self.options.padding,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        // Panes and dividers alternate
+        let nodes = stretch.children(*node).unwrap();
+
+        for (index, pane) in self.children.iter_mut().enumerate() {
+            pane.update_layout(stretch, &nodes[index * 2], self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        if position != self.position {
+            self.dirty = true;
+        }
+
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<SplitPane>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        if new.limits != self.limits {
+            change = Change::Layout;
+        }
+
+        // Other panes, the ratios start over
+        if new.ratios.len() != self.ratios.len() {
+            self.ratios = new.ratios;
+            self.collapsed = new.collapsed;
+            self.drag = None;
+            self.hovered = None;
+
+            change = Change::Layout;
+        }
+
+        if new.requested != self.requested {
+            if let Some(ratios) = &new.requested {
+                self.set_ratios(ratios);
+
+                change = Change::Layout;
+            }
+
+            self.requested = new.requested;
+        }
+
+        self.limits = new.limits;
+        self.on_resize = new.on_resize;
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::MousePress(position) => {
+                let divider = match self.divider_at(*position) {
+                    Some(divider) => divider,
+                    None => return,
+                };
+
+                let now = Instant::now();
+
+                let double = match self.last_press {
+                    Some((last, at)) => last == divider && now - at < Duration::from_millis(DOUBLE_CLICK),
+                    None => false,
+                };
+
+                if double {
+                    self.last_press = None;
+
+                    if self.toggle_collapse(divider) {
+                        if let Some(on_resize) = &self.on_resize {
+                            ctx.emit(on_resize(self.ratios()));
+                        }
+
+                        self.dirty = true;
+
+                        ctx.request_layout();
+                    }
+                } else {
+                    self.last_press = Some((divider, now));
+
+                    self.drag = Some(DividerDrag {
+                        divider,
+                        start: self.main(*position),
+                        sizes: (self.pane_size(divider), self.pane_size(divider + 1)),
+                        moved: false,
+                    });
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::MouseMove(position) => {
+                let hovered = self.divider_at(*position);
+
+                if hovered != self.hovered {
+                    self.hovered = hovered;
+                    self.dirty = true;
+                }
+
+                let (divider, start, (first, second)) = match &mut self.drag {
+                    Some(drag) => {
+                        drag.moved = true;
+
+                        (drag.divider, drag.start, drag.sizes)
+                    },
+                    None => return,
+                };
+
+                let total = first + second;
+                let (first_limits, second_limits) = (self.limits[divider], self.limits[divider + 1]);
+
+                // Within the limits of both panes
+                let lowest = first_limits.min.max(second_limits.max.map_or(0.0, |max| total - max));
+                let highest = (total - second_limits.min).min(first_limits.max.unwrap_or(total));
+
+                let size = (first + self.main(*position) - start).min(highest).max(lowest).max(0.0).min(total);
+
+                // Dragging a collapsed pane open
+                self.collapsed[divider] = None;
+                self.collapsed[divider + 1] = None;
+
+                self.resize(divider, (size, total - size));
+                self.dirty = true;
+
+                ctx.request_layout();
+            },
+            WidgetEvent::MouseRelease(_) => {
+                if let Some(DividerDrag { moved: true, .. }) = self.drag.take() {
+                    if let Some(on_resize) = &self.on_resize {
+                        ctx.emit(on_resize(self.ratios()));
+                    }
+                }
+
+                self.dirty = true;
+            },
+            _ => ()
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        "SplitPane"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("SplitPane -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// A pane of a SplitPane, which sets its style. The content is
+// laid out out of the flow, so that it takes the size of the
+// pane whatever it contains, and clipped to it.
+struct PaneView {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    dirty: bool,
+}
+
+impl PaneView {
+    fn new(content: Element) -> Box<PaneView> {
+        Box::new(PaneView {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions::default(),
+            children: vec![content],
+            dirty: true,
+        })
+    }
+}
+
+impl Widget for PaneView {
+    fn draw(&self, _ctx: &mut Ctx, _font_manager: &mut FontManager) {
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let content = self.children[0].generate_stretch_node(stretch, font_manager);
+
+        let wrapper = stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    end: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    bottom: Dimension::Points(0.0),
+                },
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            vec![content]
+        ).unwrap();
+
+        stretch.new_node(Style::default(), vec![wrapper]).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let wrapper = stretch.children(*node).unwrap()[0];
+        let wrapper_layout = stretch.layout(wrapper).unwrap();
+        let content = stretch.children(wrapper).unwrap()[0];
+
+        let origin = point(
+            self.position.x + wrapper_layout.location.x,
+            self.position.y + wrapper_layout.location.y
+        );
+
+        self.children[0].update_layout(stretch, &content, origin);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<PaneView>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        widgets::reconcile(&mut self.children, new.children)
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "PaneView"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("PaneView -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
@@ -0,0 +1,943 @@
+use lyon::math::{
+    Point,
+    point,
+    rect,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::list::{
+    ListView,
+    RowHeight,
+    SelectionMode,
+};
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Label,
+    Orientation,
+    Rect,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const CELL_PADDING: f32 = 4.0;
+// How close to the right edge of a header cell
+// a press starts resizing the column
+const RESIZE_HANDLE: f32 = 4.0;
+// A header press moving further than this drags the column
+const DRAG_THRESHOLD: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+// What the selection and Ctrl+C are about
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionUnit {
+    Row,
+    // The cells of the selected rows in the current column,
+    // which Left and Right move
+    Cell,
+}
+
+// A column of a Table. `text` gives the content of a cell as
+// text, it is what gets copied and, without a renderer, what
+// the cell shows.
+#[derive(Clone)]
+pub struct Column {
+    pub id: String,
+    pub title: String,
+    pub width: f32,
+    pub min_width: f32,
+    pub sortable: bool,
+    text: Rc<dyn Fn(usize) -> String>,
+    renderer: Option<Rc<dyn Fn(usize) -> Element>>,
+}
+
+impl Column {
+    pub fn new<F: Fn(usize) -> String + 'static>(id: &str, title: &str, width: f32, text: F) -> Column {
+        Column {
+            id: id.to_string(),
+            title: title.to_string(),
+            width,
+            min_width: 24.0,
+            sortable: false,
+            text: Rc::new(text),
+            renderer: None,
+        }
+    }
+
+    // Clicking the header emits `Table::on_sort`
+    pub fn sortable(mut self) -> Column {
+        self.sortable = true;
+        self
+    }
+
+    pub fn with_min_width(mut self, min_width: f32) -> Column {
+        self.min_width = min_width;
+        self.width = self.width.max(min_width);
+        self
+    }
+
+    // Builds the content of the cell of a row
+    pub fn with_renderer<F: Fn(usize) -> Element + 'static>(mut self, renderer: F) -> Column {
+        self.renderer = Some(Rc::new(renderer));
+        self
+    }
+}
+
+// What the header and the rows are built from, shared
+// with the builder of the ListView of the body
+struct TableState {
+    // In the order they are shown, with the widths
+    // the user gave them
+    columns: Vec<Column>,
+    unit: SelectionUnit,
+    current_column: usize,
+    sort: Option<(String, SortOrder)>,
+    text_color: Color,
+    font_size: f32,
+}
+
+impl TableState {
+    fn header(&self) -> Element {
+        let cells = self.columns.iter().map(|column| {
+            let mut content = vec![
+                Label::new(
+                    WidgetOptions {
+                        id: "title".to_string(),
+                        color: self.text_color,
+                        font_size: self.font_size,
+                        ..Default::default()
+                    },
+                    column.title.clone()
+                ) as Element,
+            ];
+
+            match &self.sort {
+                Some((id, order)) if *id == column.id => {
+                    let indicator = match order {
+                        SortOrder::Ascending => "▲",
+                        SortOrder::Descending => "▼",
+                    };
+
+                    content.push(Label::new(
+                        WidgetOptions {
+                            id: "sort_indicator".to_string(),
+                            color: self.text_color,
+                            font_size: self.font_size * 0.75,
+                            margin: stretch::geometry::Rect {
+                                start: Dimension::Points(CELL_PADDING),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        indicator.to_string()
+                    ));
+                },
+                _ => ()
+            }
+
+            TableCell::new(&column.id, column.width, Color::from_rgba(0.0, 0.0, 0.0, 0.0), content)
+                .with_separator(self.text_color.with_opacity(0.25)) as Element
+        }).collect();
+
+        Rect::new(
+            WidgetOptions {
+                id: "table_header".to_string(),
+                orientation: Orientation::Row,
+                // Drawn by the table, see `Table::generate_stretch_node`
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                flex: 0.0,
+                ..Default::default()
+            },
+            cells
+        )
+    }
+
+    fn row(&self, index: usize, selected: bool) -> Element {
+        let cells = self.columns.iter().enumerate().map(|(position, column)| {
+            let content = match &column.renderer {
+                Some(renderer) => renderer(index),
+                None => Label::new(
+                    WidgetOptions {
+                        color: self.text_color,
+                        font_size: self.font_size,
+                        ..Default::default()
+                    },
+                    (column.text)(index)
+                ),
+            };
+
+            let current = self.unit == SelectionUnit::Cell && selected && position == self.current_column;
+            let color = if current {
+                self.text_color.with_opacity(0.2)
+            } else {
+                Color::from_rgba(0.0, 0.0, 0.0, 0.0)
+            };
+
+            TableCell::new(&column.id, column.width, color, vec![content]) as Element
+        }).collect();
+
+        // A selected row stands out as a whole only when rows are selected
+        let color = if selected && self.unit == SelectionUnit::Row {
+            self.text_color.with_opacity(0.15)
+        } else {
+            Color::from_rgba(0.0, 0.0, 0.0, 0.0)
+        };
+
+        Rect::new(
+            WidgetOptions {
+                orientation: Orientation::Row,
+                color,
+                flex: 1.0,
+                ..Default::default()
+            },
+            cells
+        )
+    }
+
+    // Rows separated by new lines, cells by tabs
+    fn copy_text(&self, rows: &[usize]) -> String {
+        rows.iter().map(|row| match self.unit {
+            SelectionUnit::Row => self.columns
+                .iter()
+                .map(|column| (column.text)(*row))
+                .collect::<Vec<String>>()
+                .join("\t"),
+            SelectionUnit::Cell => match self.columns.get(self.current_column) {
+                Some(column) => (column.text)(*row),
+                None => String::new(),
+            },
+        }).collect::<Vec<String>>().join("\n")
+    }
+}
+
+enum HeaderDrag {
+    Resize { column: usize, start: f32, width: f32 },
+    // Sorts when released without moving
+    Move { column: usize, start: f32, moved: bool },
+}
+
+// Builds the message emitted with the column to sort by
+type OnSort = Box<dyn Fn(String, SortOrder) -> Box<dyn Any>>;
+
+// Rows of records under a header that stays in place. Only the
+// visible rows are built, like in a ListView: `count` is the
+// number of rows, each column turns the index of a row into its
+// cell. The order of the rows is up to the application: clicking
+// the header of a sortable column emits `on_sort`, and the view
+// should then hand out the rows sorted.
+//
+// Columns are resized by dragging the right edge of their header
+// and reordered by dragging the header, the table keeps the
+// widths and the order across updates. Ctrl+C copies the selected
+// rows, or cells, as tab separated text.
+pub struct Table {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The header, then the body
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    state: Rc<RefCell<TableState>>,
+    drag: Option<HeaderDrag>,
+    // Last sort asked for by the view function
+    requested_sort: Option<(String, SortOrder)>,
+    on_sort: Option<OnSort>,
+}
+
+impl Table {
+    pub fn new(options: WidgetOptions, count: usize, columns: Vec<Column>) -> Box<Table> {
+        let state = Rc::new(RefCell::new(TableState {
+            columns,
+            unit: SelectionUnit::Row,
+            current_column: 0,
+            sort: None,
+            text_color: options.text_color,
+            font_size: options.font_size,
+        }));
+
+        let header = state.borrow().header();
+
+        let rows = state.clone();
+        let body = ListView::new(
+            WidgetOptions {
+                id: "table_body".to_string(),
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                text_color: options.text_color,
+                flex: 1.0,
+                ..Default::default()
+            },
+            count,
+            move |index, selected| rows.borrow().row(index, selected)
+        ).with_row_height(RowHeight::Fixed((options.font_size * 1.25 + CELL_PADDING * 2.0).round()));
+
+        Box::new(Table {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![header, body],
+            dirty: true,
+            state,
+            drag: None,
+            requested_sort: None,
+            on_sort: None,
+        })
+    }
+
+    pub fn with_row_height(self: Box<Self>, row_height: RowHeight) -> Box<Table> {
+        self.map_body(|body| body.with_row_height(row_height))
+    }
+
+    pub fn with_selection_mode(self: Box<Self>, selection_mode: SelectionMode) -> Box<Table> {
+        self.map_body(|body| body.with_selection_mode(selection_mode))
+    }
+
+    pub fn with_selection_unit(self: Box<Self>, unit: SelectionUnit) -> Box<Table> {
+        self.state.borrow_mut().unit = unit;
+
+        self
+    }
+
+    // See `ListView::with_selection`
+    pub fn with_selection(self: Box<Self>, selection: Vec<usize>) -> Box<Table> {
+        self.map_body(|body| body.with_selection(selection))
+    }
+
+    // The column the rows are sorted by, shown in its header. Once
+    // the table is on screen it shows it again whenever the value
+    // passed here changes.
+    pub fn with_sort(mut self: Box<Self>, column: &str, order: SortOrder) -> Box<Table> {
+        let sort = Some((column.to_string(), order));
+
+        self.state.borrow_mut().sort = sort.clone();
+        self.requested_sort = sort;
+
+        let header = self.state.borrow().header();
+
+        self.children[0] = header;
+
+        self
+    }
+
+    // Message emitted with the id of a sortable column when its
+    // header is clicked, descending if it was ascending already
+    pub fn on_sort<M: 'static, F: Fn(String, SortOrder) -> M + 'static>(mut self: Box<Self>, on_sort: F) -> Box<Table> {
+        self.on_sort = Some(Box::new(move |column, order| Box::new(on_sort(column, order))));
+
+        self
+    }
+
+    pub fn on_select<M: 'static, F: Fn(Vec<usize>) -> M + 'static>(self: Box<Self>, on_select: F) -> Box<Table> {
+        self.map_body(|body| body.on_select(on_select))
+    }
+
+    pub fn on_activate<M: 'static, F: Fn(usize) -> M + 'static>(self: Box<Self>, on_activate: F) -> Box<Table> {
+        self.map_body(|body| body.on_activate(on_activate))
+    }
+
+    // The body is the last child
+    fn map_body<F: FnOnce(Box<ListView>) -> Box<ListView>>(mut self: Box<Self>, f: F) -> Box<Table> {
+        if let Some(body) = self.children.last_mut() {
+            widgets::map_part(body, f);
+        }
+
+        self
+    }
+
+    fn with_body<R: Default, F: FnOnce(&mut ListView) -> R>(&mut self, f: F) -> R {
+        self.children
+            .last_mut()
+            .and_then(|body| widgets::with_part(body, f))
+            .unwrap_or_default()
+    }
+
+    // Builds the header and the rows again after the columns
+    // changed, returns whether the layout must be computed again
+    fn rebuild(&mut self) -> bool {
+        let header = self.state.borrow().header();
+
+        let body = self.children.split_off(1);
+        let change = widgets::reconcile(&mut self.children, vec![header]);
+
+        self.children.extend(body);
+
+        let rows = self.with_body(|body| body.rebuild_rows());
+
+        change == Change::Layout || rows
+    }
+
+    // Index of the column whose header is under `x`
+    fn column_at(&self, x: f32) -> Option<usize> {
+        self.children[0].children().iter().position(|cell| {
+            x >= cell.position().x && x < cell.position().x + cell.size().width
+        })
+    }
+
+    // The header is as wide as the table, whatever
+    // the width of its widget
+    fn header_rect(&self) -> lyon::math::Rect {
+        rect(self.position.x, self.position.y, self.size.width, self.children[0].size().height)
+    }
+
+    fn sort_by(&mut self, column: usize, ctx: &mut EventCtx) {
+        let (id, order) = {
+            let mut state = self.state.borrow_mut();
+            let id = state.columns[column].id.clone();
+
+            let order = match &state.sort {
+                Some((sorted, SortOrder::Ascending)) if *sorted == id => SortOrder::Descending,
+                _ => SortOrder::Ascending,
+            };
+
+            state.sort = Some((id.clone(), order));
+
+            (id, order)
+        };
+
+        if let Some(on_sort) = &self.on_sort {
+            ctx.emit(on_sort(id, order));
+        }
+
+        if self.rebuild() {
+            ctx.request_layout();
+        }
+    }
+
+    fn copy(&mut self, ctx: &mut EventCtx) {
+        let (mut rows, current) = self.with_body(|body| (body.selection(), body.current()));
+
+        if rows.is_empty() {
+            rows.extend(current);
+        }
+
+        if !rows.is_empty() {
+            let text = self.state.borrow().copy_text(&rows);
+
+            ctx.copy(text);
+        }
+    }
+}
+
+impl Widget for Table {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+
+        let header = self.header_rect();
+
+        ctx.begin_primitive();
+        ctx.color(self.options.text_color.with_opacity(0.08));
+        ctx.rect(header.origin, header.size.width, header.size.height);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let header = self.children[0].generate_stretch_node(stretch, font_manager);
+
+        // Columns wider than the table overflow the header and get
+        // clipped, with no width of its own the header does not make
+        // the table wider
+        let wrapper = stretch.new_node(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_shrink: 0.0,
+                size: stretch::geometry::Size {
+                    width: Dimension::Points(0.0),
+                    height: Dimension::Auto
+                },
+                ..Default::default()
+            },
+            vec![header]
+        ).unwrap();
+
+        let body = self.children[1].generate_stretch_node(stretch, font_manager);
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_grow: self.options.flex,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            vec![wrapper, body]
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let nodes = stretch.children(*node).unwrap();
+        let wrapper_layout = stretch.layout(nodes[0]).unwrap();
+        let header = stretch.children(nodes[0]).unwrap()[0];
+
+        let origin = point(
+            self.position.x + wrapper_layout.location.x,
+            self.position.y + wrapper_layout.location.y
+        );
+
+        self.children[0].update_layout(stretch, &header, origin);
+        self.children[1].update_layout(stretch, &nodes[1], self.position);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let mut new = match new.into_any().downcast::<Table>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        // The columns come from the view, their widths
+        // and their order from the user
+        {
+            let old = self.state.borrow();
+            let mut state = new.state.borrow_mut();
+
+            let mut columns: Vec<Column> = old.columns
+                .iter()
+                .filter_map(|column| {
+                    state.columns.iter().find(|new| new.id == column.id).map(|new| Column {
+                        width: column.width.max(new.min_width),
+                        ..new.clone()
+                    })
+                })
+                .collect();
+
+            for column in state.columns.iter() {
+                if !columns.iter().any(|kept| kept.id == column.id) {
+                    columns.push(column.clone());
+                }
+            }
+
+            state.columns = columns;
+            state.current_column = old.current_column.min(state.columns.len().saturating_sub(1));
+
+            if new.requested_sort == self.requested_sort {
+                state.sort = old.sort.clone();
+            }
+        }
+
+        self.requested_sort = new.requested_sort;
+        self.on_sort = new.on_sort;
+
+        // The builder of the new body reads the new state
+        self.state = new.state;
+
+        let body = new.children.pop().unwrap();
+
+        change = change.max(self.children[1].update(body));
+
+        if self.rebuild() {
+            change = Change::Layout;
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::MousePress(position) if self.header_rect().contains(*position) => {
+                if let Some(column) = self.column_at(position.x) {
+                    let cell = &self.children[0].children()[column];
+                    let right = cell.position().x + cell.size().width;
+
+                    self.drag = if position.x >= right - RESIZE_HANDLE {
+                        Some(HeaderDrag::Resize { column, start: position.x, width: cell.size().width })
+                    } else {
+                        Some(HeaderDrag::Move { column, start: position.x, moved: false })
+                    };
+                }
+
+                ctx.set_handled();
+            },
+            // The body selects the row, the table the column
+            WidgetEvent::MousePress(position) if self.contains(*position) && self.state.borrow().unit == SelectionUnit::Cell => {
+                if let Some(column) = self.column_at(position.x) {
+                    self.state.borrow_mut().current_column = column;
+
+                    if self.rebuild() {
+                        ctx.request_layout();
+                    }
+                }
+            },
+            WidgetEvent::MouseMove(position) => {
+                let changed = match &mut self.drag {
+                    Some(HeaderDrag::Resize { column, start, width }) => {
+                        let mut state = self.state.borrow_mut();
+                        let column = &mut state.columns[*column];
+
+                        column.width = (*width + position.x - *start).max(column.min_width);
+
+                        true
+                    },
+                    Some(HeaderDrag::Move { column, start, moved }) => {
+                        if (position.x - *start).abs() > DRAG_THRESHOLD {
+                            *moved = true;
+                        }
+
+                        let target = self.children[0].children().iter().position(|cell| {
+                            position.x >= cell.position().x && position.x < cell.position().x + cell.size().width
+                        });
+
+                        match target {
+                            Some(target) if *moved && target != *column => {
+                                let mut state = self.state.borrow_mut();
+                                let dragged = state.columns.remove(*column);
+
+                                state.columns.insert(target, dragged);
+
+                                // The current cell stays in the same column
+                                if state.current_column == *column {
+                                    state.current_column = target;
+                                } else if *column < state.current_column && target >= state.current_column {
+                                    state.current_column -= 1;
+                                } else if *column > state.current_column && target <= state.current_column {
+                                    state.current_column += 1;
+                                }
+
+                                *column = target;
+
+                                true
+                            },
+                            _ => false,
+                        }
+                    },
+                    None => false,
+                };
+
+                if changed && self.rebuild() {
+                    ctx.request_layout();
+                }
+            },
+            WidgetEvent::MouseRelease(_) => {
+                if let Some(HeaderDrag::Move { column, moved: false, .. }) = self.drag.take() {
+                    if self.state.borrow().columns[column].sortable {
+                        self.sort_by(column, ctx);
+                    }
+                }
+            },
+            // Keys the body did not use
+            WidgetEvent::KeyPress(VirtualKeyCode::C) if ctx.modifiers().ctrl => {
+                self.copy(ctx);
+
+                ctx.set_handled();
+            },
+            WidgetEvent::KeyPress(key @ VirtualKeyCode::Left) | WidgetEvent::KeyPress(key @ VirtualKeyCode::Right) => {
+                {
+                    let mut state = self.state.borrow_mut();
+
+                    if state.unit != SelectionUnit::Cell {
+                        return;
+                    }
+
+                    state.current_column = match key {
+                        VirtualKeyCode::Left => state.current_column.saturating_sub(1),
+                        _ => (state.current_column + 1).min(state.columns.len().saturating_sub(1)),
+                    };
+                }
+
+                if self.rebuild() {
+                    ctx.request_layout();
+                }
+
+                ctx.set_handled();
+            },
+            _ => ()
+        }
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Table"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("Table -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// A cell of a Table, as wide as its column whatever the
+// content, which is clipped to it
+struct TableCell {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    // Line drawn along the right edge, between header cells
+    separator: Option<Color>,
+    dirty: bool,
+}
+
+impl TableCell {
+    fn new(id: &str, width: f32, color: Color, children: Vec<Element>) -> Box<TableCell> {
+        Box::new(TableCell {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: id.to_string(),
+                color,
+                width: Dimension::Points(width),
+                padding: WidgetOptions::uniform_padding(CELL_PADDING),
+                ..Default::default()
+            },
+            children,
+            separator: None,
+            dirty: true,
+        })
+    }
+
+    fn with_separator(mut self: Box<Self>, color: Color) -> Box<TableCell> {
+        self.separator = Some(color);
+
+        self
+    }
+}
+
+impl Widget for TableCell {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.rect(self.position, self.size.width, self.size.height);
+        ctx.fill();
+
+        if let Some(separator) = self.separator {
+            ctx.begin_primitive();
+            ctx.color(separator);
+            ctx.rect(
+                point(self.position.x + self.size.width - 1.0, self.position.y + CELL_PADDING),
+                1.0,
+                (self.size.height - CELL_PADDING * 2.0).max(0.0)
+            );
+            ctx.fill();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: Dimension::Auto
+        };
+
+        stretch.new_node(
+            Style {
+                align_items: AlignItems::Center,
+                flex_shrink: 0.0,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                padding: self.options.padding,
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<TableCell>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None || new.separator != self.separator {
+            self.options = new.options;
+            self.separator = new.separator;
+            self.dirty = true;
+        }
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "TableCell"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("TableCell -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
@@ -0,0 +1,1000 @@
+use lyon::math::{
+    Point,
+    point,
+    rect,
+    vector,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::animation::Transition;
+
+use super::scroll::{
+    self,
+    Scroller,
+    STEP_DURATION,
+};
+
+use super::widgets::{
+    self,
+    Alignment,
+    Change,
+    Element,
+    EventCtx,
+    Label,
+    Orientation,
+    Rect,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::time::Instant;
+
+const TAB_PADDING: f32 = 10.0;
+// Line under the tabs, drawn in the text
+// color while the strip has focus
+const FOCUS_LINE: f32 = 2.0;
+// A press on a tab moving further than this drags the tab
+const DRAG_THRESHOLD: f32 = 4.0;
+
+// A page of Tabs, with what its tab shows
+pub struct Tab {
+    pub id: String,
+    pub title: String,
+    // A glyph drawn before the title
+    pub icon: Option<String>,
+    // Shows a button emitting `Tabs::on_close`
+    pub closable: bool,
+    content: Element,
+}
+
+impl Tab {
+    pub fn new(id: &str, title: &str, content: Element) -> Tab {
+        Tab {
+            id: id.to_string(),
+            title: title.to_string(),
+            icon: None,
+            closable: false,
+            content,
+        }
+    }
+
+    pub fn with_icon(mut self, icon: &str) -> Tab {
+        self.icon = Some(icon.to_string());
+        self
+    }
+
+    pub fn closable(mut self) -> Tab {
+        self.closable = true;
+        self
+    }
+}
+
+// What the tab of a page is built from
+struct TabData {
+    id: String,
+    title: String,
+    icon: Option<String>,
+    closable: bool,
+}
+
+struct TabDrag {
+    index: usize,
+    start: f32,
+    moved: bool,
+}
+
+// Build the messages emitted with the tab selected or closed,
+// and with the ids of the tabs in their new order
+type OnTab = Box<dyn Fn(String) -> Box<dyn Any>>;
+type OnReorder = Box<dyn Fn(Vec<String>) -> Box<dyn Any>>;
+
+// Pages under a strip of tabs, one visible at a time. The pages
+// not shown are kept aside, with their state, until their tab is
+// selected again by clicking it, with Ctrl+Tab and Ctrl+Shift+Tab
+// or Ctrl+PageDown and Ctrl+PageUp from inside the pages, or with
+// the arrow keys, Home and End while the strip has focus.
+//
+// Like the columns of a Table, tabs are reordered by dragging
+// them and the order is kept across updates. The strip scrolls
+// when the tabs do not fit, with the wheel, and to show the tab
+// selected. Closing a tab emits `on_close`: the view should then
+// leave it out.
+pub struct Tabs {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The strip of tabs, then the page shown
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    // In the order they are shown
+    tabs: Vec<TabData>,
+    // The pages, except for the one shown
+    pages: Vec<Option<Element>>,
+    active: usize,
+    drag: Option<TabDrag>,
+    // Last tab asked for by the view function
+    requested_active: Option<String>,
+    on_select: Option<OnTab>,
+    on_close: Option<OnTab>,
+    on_reorder: Option<OnReorder>,
+}
+
+impl Tabs {
+    pub fn new(options: WidgetOptions, tabs: Vec<Tab>) -> Box<Tabs> {
+        let height = (options.font_size * 1.25 + TAB_PADDING).round() + FOCUS_LINE;
+
+        let strip = TabStrip::new(
+            WidgetOptions {
+                id: "tab_strip".to_string(),
+                color: options.text_color.with_opacity(0.08),
+                text_color: options.text_color,
+                height: Dimension::Points(height),
+                ..Default::default()
+            },
+            Rect::new(WidgetOptions { flex: 0.0, ..Default::default() }, vec![])
+        );
+
+        let mut data = vec![];
+        let mut pages = vec![];
+
+        for tab in tabs {
+            data.push(TabData {
+                id: tab.id,
+                title: tab.title,
+                icon: tab.icon,
+                closable: tab.closable,
+            });
+
+            pages.push(Some(tab.content));
+        }
+
+        let mut tabs = Box::new(Tabs {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![strip],
+            dirty: true,
+            tabs: data,
+            pages,
+            active: 0,
+            drag: None,
+            requested_active: None,
+            on_select: None,
+            on_close: None,
+            on_reorder: None,
+        });
+
+        tabs.show(0);
+        tabs.rebuild_strip();
+
+        tabs
+    }
+
+    // The tab selected to begin with. Once the tabs are on screen
+    // they select it again whenever the value passed here changes.
+    pub fn with_active(mut self: Box<Self>, id: &str) -> Box<Tabs> {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.id == id) {
+            self.show(index);
+            self.rebuild_strip();
+        }
+
+        self.requested_active = Some(id.to_string());
+
+        self
+    }
+
+    // Message emitted with the id of the tab selected
+    pub fn on_select<M: 'static, F: Fn(String) -> M + 'static>(mut self: Box<Self>, on_select: F) -> Box<Tabs> {
+        self.on_select = Some(Box::new(move |id| Box::new(on_select(id))));
+
+        self
+    }
+
+    // Message emitted with the id of a tab whose close button was pressed
+    pub fn on_close<M: 'static, F: Fn(String) -> M + 'static>(mut self: Box<Self>, on_close: F) -> Box<Tabs> {
+        self.on_close = Some(Box::new(move |id| Box::new(on_close(id))));
+
+        self
+    }
+
+    // Message emitted with the ids of the tabs in their
+    // new order, once a tab was dragged somewhere else
+    pub fn on_reorder<M: 'static, F: Fn(Vec<String>) -> M + 'static>(mut self: Box<Self>, on_reorder: F) -> Box<Tabs> {
+        self.on_reorder = Some(Box::new(move |ids| Box::new(on_reorder(ids))));
+
+        self
+    }
+
+    // Id of the tab shown
+    pub fn active(&self) -> Option<&str> {
+        self.tabs.get(self.active).map(|tab| tab.id.as_str())
+    }
+
+    // Puts the page shown back aside and shows the one of `index`
+    fn show(&mut self, index: usize) {
+        if self.children.len() > 1 {
+            let page = self.children.pop().unwrap();
+
+            self.pages[self.active] = Some(page);
+        }
+
+        if let Some(mut page) = self.pages.get_mut(index).and_then(|page| page.take()) {
+            // The meshes of the page shown before are
+            // cached for the same place in the tree
+            mark_dirty(&mut page);
+
+            self.children.push(page);
+        }
+
+        self.active = index;
+    }
+
+    // Builds the tabs again after they changed, returns
+    // whether the layout must be computed again
+    fn rebuild_strip(&mut self) -> bool {
+        let tabs = self.tabs
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| self.tab(tab, index == self.active))
+            .collect();
+
+        let row = Rect::new(
+            WidgetOptions {
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                padding: stretch::geometry::Rect {
+                    bottom: Dimension::Points(FOCUS_LINE),
+                    ..Default::default()
+                },
+                flex: 0.0,
+                ..Default::default()
+            },
+            tabs
+        );
+
+        let strip = &mut self.children[0];
+        let color = self.options.text_color.with_opacity(0.08);
+
+        if strip.options().color != color || strip.options().text_color != self.options.text_color {
+            strip.options_mut().color = color;
+            strip.options_mut().text_color = self.options.text_color;
+        }
+
+        strip.children_mut().unwrap()[0].update(row) == Change::Layout
+    }
+
+    fn tab(&self, tab: &TabData, active: bool) -> Element {
+        let color = self.options.text_color;
+        let mut content = vec![];
+
+        if let Some(icon) = &tab.icon {
+            content.push(Label::new(
+                WidgetOptions {
+                    id: "icon".to_string(),
+                    color,
+                    font_size: self.options.font_size,
+                    margin: stretch::geometry::Rect {
+                        end: Dimension::Points(TAB_PADDING / 2.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                icon.clone()
+            ) as Element);
+        }
+
+        content.push(Label::new(
+            WidgetOptions {
+                id: "title".to_string(),
+                color: if active { color } else { color.with_opacity(0.7) },
+                font_size: self.options.font_size,
+                ..Default::default()
+            },
+            tab.title.clone()
+        ));
+
+        if tab.closable {
+            content.push(Label::new(
+                WidgetOptions {
+                    id: "close".to_string(),
+                    color: color.with_opacity(0.6),
+                    font_size: self.options.font_size,
+                    margin: stretch::geometry::Rect {
+                        start: Dimension::Points(TAB_PADDING),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                "×".to_string()
+            ));
+        }
+
+        Rect::new(
+            WidgetOptions {
+                id: tab.id.clone(),
+                orientation: Orientation::Row,
+                // The one shown looks like a part of the page
+                color: if active { self.options.color } else { Color::from_rgba(0.0, 0.0, 0.0, 0.0) },
+                hover_color: if active { None } else { Some(color.with_opacity(0.05)) },
+                vertical_align: Alignment::Center,
+                padding: stretch::geometry::Rect {
+                    start: Dimension::Points(TAB_PADDING),
+                    end: Dimension::Points(TAB_PADDING),
+                    ..Default::default()
+                },
+                flex: 0.0,
+                ..Default::default()
+            },
+            content
+        )
+    }
+
+    fn tab_widgets(&self) -> &[Element] {
+        self.children[0].children()[0].children()
+    }
+
+    // The tab under `position`, and whether it is on its close button
+    fn tab_at(&self, position: Point) -> Option<(usize, bool)> {
+        if !self.children[0].contains(position) {
+            return None;
+        }
+
+        let index = self.tab_widgets().iter().position(|tab| tab.contains(position))?;
+
+        // The whole height of the tab, from a little before the glyph
+        let close = self.tab_widgets()[index]
+            .children()
+            .iter()
+            .any(|child| child.options().id == "close" && position.x >= child.position().x - TAB_PADDING / 2.0);
+
+        Some((index, close))
+    }
+
+    // The strip is the first child
+    fn with_strip<R: Default, F: FnOnce(&mut TabStrip) -> R>(&mut self, f: F) -> R {
+        widgets::with_part(&mut self.children[0], f).unwrap_or_default()
+    }
+
+    // Shows the page of `index`, scrolling the strip to its tab
+    fn select(&mut self, index: usize, ctx: &mut EventCtx) {
+        if index >= self.tabs.len() {
+            return;
+        }
+
+        if index != self.active {
+            self.show(index);
+            self.rebuild_strip();
+
+            if let Some(on_select) = &self.on_select {
+                ctx.emit(on_select(self.tabs[index].id.clone()));
+            }
+
+            ctx.request_layout();
+        }
+
+        let tab = &self.tab_widgets()[index];
+        let left = tab.position().x - self.children[0].children()[0].position().x;
+        let right = left + tab.size().width;
+
+        self.with_strip(|strip| strip.scroll_to(left, right));
+    }
+
+    // Moves the tab of `index` to `target`, the pages with them
+    fn reorder(&mut self, index: usize, target: usize) {
+        let tab = self.tabs.remove(index);
+        let page = self.pages.remove(index);
+
+        self.tabs.insert(target, tab);
+        self.pages.insert(target, page);
+
+        // The page shown moves with its tab
+        if self.active == index {
+            self.active = target;
+        } else if index < self.active && target >= self.active {
+            self.active -= 1;
+        } else if index > self.active && target <= self.active {
+            self.active += 1;
+        }
+    }
+
+    fn ids(&self) -> Vec<String> {
+        self.tabs.iter().map(|tab| tab.id.clone()).collect()
+    }
+
+    // Every page, in the order of the tabs
+    fn take_pages(&mut self) -> Vec<Element> {
+        if self.children.len() > 1 {
+            let page = self.children.pop().unwrap();
+
+            self.pages[self.active] = Some(page);
+        }
+
+        self.pages.drain(..).map(|page| page.unwrap()).collect()
+    }
+}
+
+// Every widget of a page drawn again
+fn mark_dirty(widget: &mut Element) {
+    widget.set_dirty(true);
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            mark_dirty(child);
+        }
+    }
+}
+
+impl Widget for Tabs {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut nodes = vec![self.children[0].generate_stretch_node(stretch, font_manager)];
+
+        // The page fills what the strip leaves
+        if let Some(page) = self.children.get(1) {
+            let page = page.generate_stretch_node(stretch, font_manager);
+
+            nodes.push(stretch.new_node(
+                Style {
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    ..Default::default()
+                },
+                vec![page]
+            ).unwrap());
+        }
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_grow: self.options.flex,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let nodes = stretch.children(*node).unwrap();
+
+        self.children[0].update_layout(stretch, &nodes[0], self.position);
+
+        if let Some(wrapper) = nodes.get(1) {
+            let wrapper_layout = stretch.layout(*wrapper).unwrap();
+            let page = stretch.children(*wrapper).unwrap()[0];
+
+            let origin = point(
+                self.position.x + wrapper_layout.location.x,
+                self.position.y + wrapper_layout.location.y
+            );
+
+            self.children[1].update_layout(stretch, &page, origin);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let mut new = match new.into_any().downcast::<Tabs>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options.clone();
+            self.dirty = true;
+        }
+
+        let shown = self.active().map(|id| id.to_string());
+        let active = match &new.requested_active {
+            Some(id) if new.requested_active != self.requested_active => Some(id.clone()),
+            _ => shown.clone(),
+        };
+
+        // The tabs come from the view, their order from the
+        // user, and the pages keep their state
+        let new_pages = new.take_pages();
+        let mut new_tabs: Vec<Option<(TabData, Element)>> = new.tabs.drain(..).zip(new_pages).map(Some).collect();
+
+        let old_tabs: Vec<TabData> = self.tabs.drain(..).collect();
+        let old_pages = self.take_pages();
+
+        for (tab, page) in old_tabs.into_iter().zip(old_pages) {
+            let found = new_tabs.iter().position(|new| match new {
+                Some((new, _)) => new.id == tab.id,
+                None => false,
+            });
+
+            if let Some(found) = found {
+                let (tab, content) = new_tabs[found].take().unwrap();
+                let mut page = vec![page];
+
+                change = change.max(widgets::reconcile(&mut page, vec![content]));
+
+                self.tabs.push(tab);
+                self.pages.push(page.pop());
+            }
+        }
+
+        for (tab, page) in new_tabs.into_iter().flatten() {
+            self.tabs.push(tab);
+            self.pages.push(Some(page));
+        }
+
+        // A tab closed leaves its place to the next one
+        let index = active
+            .and_then(|id| self.tabs.iter().position(|tab| tab.id == id))
+            .unwrap_or(self.active.min(self.tabs.len().saturating_sub(1)));
+
+        self.active = index;
+        self.show(index);
+
+        if self.active().map(|id| id.to_string()) != shown {
+            change = Change::Layout;
+        }
+
+        self.requested_active = new.requested_active;
+        self.on_select = new.on_select;
+        self.on_close = new.on_close;
+        self.on_reorder = new.on_reorder;
+
+        if self.rebuild_strip() {
+            change = Change::Layout;
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::MousePress(position) => match self.tab_at(*position) {
+                Some((index, true)) => {
+                    if let Some(on_close) = &self.on_close {
+                        ctx.emit(on_close(self.tabs[index].id.clone()));
+                    }
+
+                    ctx.set_handled();
+                },
+                Some((index, false)) => {
+                    self.select(index, ctx);
+
+                    self.drag = Some(TabDrag { index, start: position.x, moved: false });
+                },
+                None => ()
+            },
+            WidgetEvent::MouseMove(position) => {
+                let (index, target) = match &mut self.drag {
+                    Some(drag) => {
+                        if (position.x - drag.start).abs() > DRAG_THRESHOLD {
+                            drag.moved = true;
+                        }
+
+                        if !drag.moved {
+                            return;
+                        }
+
+                        let target = self.children[0].children()[0].children().iter().position(|tab| {
+                            position.x >= tab.position().x && position.x < tab.position().x + tab.size().width
+                        });
+
+                        match target {
+                            Some(target) if target != drag.index => {
+                                let index = drag.index;
+
+                                drag.index = target;
+
+                                (index, target)
+                            },
+                            _ => return,
+                        }
+                    },
+                    None => return,
+                };
+
+                self.reorder(index, target);
+
+                if self.rebuild_strip() {
+                    ctx.request_layout();
+                }
+            },
+            WidgetEvent::MouseRelease(_) => {
+                if let Some(TabDrag { moved: true, .. }) = self.drag.take() {
+                    if let Some(on_reorder) = &self.on_reorder {
+                        ctx.emit(on_reorder(self.ids()));
+                    }
+                }
+            },
+            // Keys the pages did not use
+            WidgetEvent::KeyPress(key) => {
+                let modifiers = ctx.modifiers();
+                let count = self.tabs.len();
+
+                if count == 0 {
+                    return;
+                }
+
+                let next = (self.active + 1) % count;
+                let previous = (self.active + count - 1) % count;
+
+                let target = match key {
+                    VirtualKeyCode::Tab if modifiers.ctrl && modifiers.shift => previous,
+                    VirtualKeyCode::Tab if modifiers.ctrl => next,
+                    VirtualKeyCode::PageDown if modifiers.ctrl => next,
+                    VirtualKeyCode::PageUp if modifiers.ctrl => previous,
+                    _ if !self.children[0].is_focused() => return,
+                    VirtualKeyCode::Right => next,
+                    VirtualKeyCode::Left => previous,
+                    VirtualKeyCode::Home => 0,
+                    VirtualKeyCode::End => count - 1,
+                    _ => return,
+                };
+
+                self.select(target, ctx);
+
+                ctx.set_handled();
+            },
+            _ => ()
+        }
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Tabs"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("Tabs -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// The strip of a Tabs: a row of tabs laid out with no limit
+// and scrolled horizontally when it does not fit. It takes the
+// focus when clicked, so that the arrow keys go to the tabs.
+struct TabStrip {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    scroller: Scroller,
+    // Width of the row and offset it is placed with
+    content: f32,
+    applied: f32,
+    focused: bool,
+    dirty: bool,
+}
+
+impl TabStrip {
+    fn new(options: WidgetOptions, row: Element) -> Box<TabStrip> {
+        Box::new(TabStrip {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![row],
+            scroller: Scroller::new(0.0),
+            content: 0.0,
+            applied: 0.0,
+            focused: false,
+            dirty: true,
+        })
+    }
+
+    fn max_offset(&self) -> f32 {
+        (self.content - self.size.width).max(0.0)
+    }
+
+    // Scrolls as little as possible for the part of the
+    // row from `left` to `right` to be visible
+    fn scroll_to(&mut self, left: f32, right: f32) {
+        let target = self.scroller.target();
+
+        let offset = if left < target {
+            left
+        } else if right > target + self.size.width {
+            right - self.size.width
+        } else {
+            return;
+        };
+
+        let max_offset = self.max_offset();
+
+        self.scroller.set_offset(offset, max_offset, Some(Transition::new(STEP_DURATION)));
+    }
+
+    fn apply_offset(&mut self) {
+        let offset = self.scroller.offset();
+
+        if offset != self.applied {
+            scroll::translate(&mut self.children[0], vector(self.applied - offset, 0.0));
+
+            self.applied = offset;
+        }
+    }
+}
+
+impl Widget for TabStrip {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.rect(self.position, self.size.width, self.size.height);
+        ctx.fill();
+
+        if self.focused {
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color);
+            ctx.rect(
+                point(self.position.x, self.position.y + self.size.height - FOCUS_LINE),
+                self.size.width,
+                FOCUS_LINE
+            );
+            ctx.fill();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let row = self.children[0].generate_stretch_node(stretch, font_manager);
+
+        // Out of the flow, so that the tabs do
+        // not count in the width of the strip
+        let wrapper = stretch.new_node(
+            Style {
+                position_type: PositionType::Absolute,
+                position: stretch::geometry::Rect {
+                    start: Dimension::Points(0.0),
+                    top: Dimension::Points(0.0),
+                    bottom: Dimension::Points(0.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            vec![row]
+        ).unwrap();
+
+        stretch.new_node(
+            Style {
+                flex_shrink: 0.0,
+                size: stretch::geometry::Size {
+                    width: Dimension::Auto,
+                    height: self.options.height
+                },
+                ..Default::default()
+            },
+            vec![wrapper]
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let wrapper = stretch.children(*node).unwrap()[0];
+        let wrapper_layout = stretch.layout(wrapper).unwrap();
+        let row = stretch.children(wrapper).unwrap()[0];
+
+        self.content = stretch.layout(row).unwrap().size.width;
+
+        let max_offset = self.max_offset();
+
+        self.scroller.clamp(max_offset);
+
+        self.applied = self.scroller.offset();
+
+        let origin = point(
+            self.position.x + wrapper_layout.location.x - self.applied,
+            self.position.y + wrapper_layout.location.y
+        );
+
+        self.children[0].update_layout(stretch, &row, origin);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, _new: Element) -> Change {
+        // Built once by the Tabs, which updates the row
+        Change::None
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Scroll(_, delta) => {
+                let distance = scroll::scroll_distance(Orientation::Row, *delta);
+                let max_offset = self.max_offset();
+
+                if self.scroller.scroll(*delta, distance, max_offset) {
+                    self.apply_offset();
+                    ctx.set_handled();
+                }
+            },
+            WidgetEvent::ScrollEnd(_) => self.scroller.end_gesture(),
+            WidgetEvent::Tick(now) => {
+                let max_offset = self.max_offset();
+
+                self.scroller.tick(*now, max_offset);
+
+                self.apply_offset();
+            },
+            _ => ()
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.scroller.next_tick()
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused != self.focused {
+            self.focused = focused;
+            self.dirty = true;
+        }
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "TabStrip"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("TabStrip -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
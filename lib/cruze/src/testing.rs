@@ -0,0 +1,225 @@
+use super::font_manager::FontManager;
+use super::layout_manager::{
+    self,
+    LayoutBuilder,
+};
+use super::render_soft::SoftwareBackend;
+use super::renderer::Renderer;
+use super::widgets::Widget;
+
+use std::path::PathBuf;
+
+// Set this environment variable to rewrite the stored goldens
+// instead of comparing against them
+pub const UPDATE_GOLDENS_VAR: &str = "CRUZE_UPDATE_GOLDENS";
+
+pub fn should_update_goldens() -> bool {
+    match std::env::var(UPDATE_GOLDENS_VAR) {
+        Ok(value) => !value.is_empty() && value != "0",
+        Err(_) => false,
+    }
+}
+
+// Lays a widget tree out at a fixed size and compares either the
+// computed layout or the image rendered through the software
+// backend with the expectation stored in `goldens_dir`
+pub struct Snapshot {
+    pub width: u32,
+    pub height: u32,
+    // Maximum difference allowed on each channel of each pixel
+    pub tolerance: u8,
+    pub goldens_dir: PathBuf,
+}
+
+impl Snapshot {
+    pub fn new(width: u32, height: u32) -> Snapshot {
+        Snapshot {
+            width,
+            height,
+            tolerance: 2,
+            goldens_dir: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("goldens"),
+        }
+    }
+
+    fn size(&self) -> glutin::dpi::LogicalSize {
+        glutin::dpi::LogicalSize {
+            width: self.width as f64,
+            height: self.height as f64,
+        }
+    }
+
+    pub fn layout(&self, children: &mut Vec<Box<dyn Widget>>, font_manager: &mut FontManager) {
+        LayoutBuilder::new().build(self.size(), children, font_manager);
+    }
+
+    pub fn render(&self, mut children: Vec<Box<dyn Widget>>) -> image::RgbaImage {
+        let size = self.size();
+
+        let mut font_manager = FontManager::new();
+        let mut renderer = Renderer::new(SoftwareBackend::new(self.width, self.height));
+
+        self.layout(&mut children, &mut font_manager);
+
+        renderer.resize(size, &mut children, &mut font_manager);
+        renderer.draw();
+
+        renderer.backend().to_image()
+    }
+
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.goldens_dir.join(format!("{}.png", name))
+    }
+
+    pub fn diff_path(&self, name: &str) -> PathBuf {
+        self.goldens_dir.join(format!("{}.diff.png", name))
+    }
+
+    pub fn layout_path(&self, name: &str) -> PathBuf {
+        self.goldens_dir.join(format!("{}.layout.txt", name))
+    }
+
+    pub fn compare(&self, name: &str, children: Vec<Box<dyn Widget>>) -> Result<(), String> {
+        let actual = self.render(children);
+        let golden_path = self.golden_path(name);
+
+        if should_update_goldens() {
+            std::fs::create_dir_all(&self.goldens_dir).map_err(|e| e.to_string())?;
+            actual.save(&golden_path).map_err(|e| e.to_string())?;
+
+            return Ok(());
+        }
+
+        let expected = match image::open(&golden_path) {
+            Ok(expected) => expected.to_rgba(),
+            Err(e) => {
+                return Err(format!(
+                    "Cannot open golden {}: {} (run with {}=1 to create it)",
+                    golden_path.display(),
+                    e,
+                    UPDATE_GOLDENS_VAR
+                ));
+            }
+        };
+
+        if expected.dimensions() != actual.dimensions() {
+            return Err(format!(
+                "Golden {} is {:?}, rendered image is {:?}",
+                golden_path.display(),
+                expected.dimensions(),
+                actual.dimensions()
+            ));
+        }
+
+        let (diff, mismatches) = diff_images(&expected, &actual, self.tolerance);
+
+        if mismatches == 0 {
+            return Ok(());
+        }
+
+        let diff_path = self.diff_path(name);
+
+        diff.save(&diff_path).map_err(|e| e.to_string())?;
+
+        Err(format!(
+            "{} pixels differ from golden {} by more than {}, diff written to {}",
+            mismatches,
+            golden_path.display(),
+            self.tolerance,
+            diff_path.display()
+        ))
+    }
+
+    pub fn assert_matches(&self, name: &str, children: Vec<Box<dyn Widget>>) {
+        if let Err(e) = self.compare(name, children) {
+            panic!("Snapshot '{}' failed: {}", name, e);
+        }
+    }
+
+    // Same as `compare`, but checks the output of
+    // `layout_manager::dump_layout` instead of pixels
+    pub fn compare_layout(&self, name: &str, mut children: Vec<Box<dyn Widget>>) -> Result<(), String> {
+        let mut font_manager = FontManager::new();
+
+        self.layout(&mut children, &mut font_manager);
+
+        let actual = layout_manager::dump_layout(&children);
+        let layout_path = self.layout_path(name);
+
+        if should_update_goldens() {
+            std::fs::create_dir_all(&self.goldens_dir).map_err(|e| e.to_string())?;
+            std::fs::write(&layout_path, &actual).map_err(|e| e.to_string())?;
+
+            return Ok(());
+        }
+
+        let expected = std::fs::read_to_string(&layout_path).map_err(|e| format!(
+            "Cannot open layout {}: {} (run with {}=1 to create it)",
+            layout_path.display(),
+            e,
+            UPDATE_GOLDENS_VAR
+        ))?;
+
+        if expected == actual {
+            return Ok(());
+        }
+
+        let mut mismatches = String::new();
+
+        for (line, (e, a)) in expected.lines().zip(actual.lines()).enumerate() {
+            if e != a {
+                mismatches.push_str(&format!("line {}:\n  expected: {}\n  actual:   {}\n", line + 1, e, a));
+            }
+        }
+
+        if expected.lines().count() != actual.lines().count() {
+            mismatches.push_str(&format!(
+                "expected {} widgets, got {}\n",
+                expected.lines().count(),
+                actual.lines().count()
+            ));
+        }
+
+        Err(format!("Layout differs from {}\n{}", layout_path.display(), mismatches))
+    }
+
+    pub fn assert_layout(&self, name: &str, children: Vec<Box<dyn Widget>>) {
+        if let Err(e) = self.compare_layout(name, children) {
+            panic!("Layout snapshot '{}' failed: {}", name, e);
+        }
+    }
+}
+
+// Returns an image where matching pixels are a faded copy of the
+// expected image and mismatching ones are painted in red, along
+// with the number of mismatching pixels
+pub fn diff_images(expected: &image::RgbaImage, actual: &image::RgbaImage, tolerance: u8) -> (image::RgbaImage, usize) {
+    let (width, height) = expected.dimensions();
+
+    let mut diff = image::RgbaImage::new(width, height);
+    let mut mismatches = 0;
+
+    for (x, y, expected_pixel) in expected.enumerate_pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+
+        let differs = expected_pixel.0
+            .iter()
+            .zip(actual_pixel.0.iter())
+            .any(|(e, a)| (*e as i16 - *a as i16).abs() > tolerance as i16);
+
+        let pixel = if differs {
+            mismatches += 1;
+
+            image::Rgba([255, 0, 0, 255])
+        } else {
+            let channels = expected_pixel.0;
+            let luma = (channels[0] as u32 + channels[1] as u32 + channels[2] as u32) / 3;
+            let faded = (128 + luma / 2) as u8;
+
+            image::Rgba([faded, faded, faded, 255])
+        };
+
+        diff.put_pixel(x, y, pixel);
+    }
+
+    (diff, mismatches)
+}
@@ -0,0 +1,96 @@
+use glutin::window::WindowId;
+
+use std::any::Any;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    // The application of this window gets the message
+    window: WindowId,
+    deadline: Instant,
+    // Repeating timers are armed again after firing
+    interval: Option<Duration>,
+    message: Box<dyn Fn() -> Box<dyn Any>>,
+}
+
+// The timers scheduled through `AppHandle`, the event
+// loop sleeps until the earliest one is due
+pub struct Timers {
+    next_id: u64,
+    timers: Vec<Timer>,
+}
+
+impl Default for Timers {
+    fn default() -> Timers {
+        Timers::new()
+    }
+}
+
+impl Timers {
+    pub fn new() -> Timers {
+        Timers {
+            next_id: 0,
+            timers: vec![],
+        }
+    }
+
+    pub fn schedule<M: Clone + 'static>(&mut self, window: WindowId, delay: Duration, interval: Option<Duration>, message: M) -> TimerId {
+        let id = TimerId(self.next_id);
+
+        self.next_id += 1;
+
+        self.timers.push(Timer {
+            id,
+            window,
+            deadline: Instant::now() + delay,
+            interval,
+            message: Box::new(move || Box::new(message.clone())),
+        });
+
+        id
+    }
+
+    pub fn cancel(&mut self, id: TimerId) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    // Timers of a closed window can not fire anymore
+    pub fn cancel_window(&mut self, window: WindowId) {
+        self.timers.retain(|timer| timer.window != window);
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    // Returns the messages of the timers due at `now`, oldest first.
+    // A repeating timer that fell behind fires once, not once for
+    // every interval it missed.
+    pub fn take_due(&mut self, now: Instant) -> Vec<(WindowId, Box<dyn Any>)> {
+        let mut due: Vec<(Instant, WindowId, Box<dyn Any>)> = vec![];
+
+        for timer in self.timers.iter_mut().filter(|timer| timer.deadline <= now) {
+            due.push((timer.deadline, timer.window, (timer.message)()));
+
+            if let Some(interval) = timer.interval {
+                timer.deadline += interval;
+
+                if timer.deadline <= now {
+                    timer.deadline = now + interval;
+                }
+            }
+        }
+
+        self.timers.retain(|timer| timer.deadline > now);
+
+        due.sort_by_key(|(deadline, _, _)| *deadline);
+
+        due.into_iter().map(|(_, window, message)| (window, message)).collect()
+    }
+}
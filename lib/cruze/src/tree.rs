@@ -0,0 +1,911 @@
+use lyon::math::{
+    Point,
+    point,
+    rect,
+};
+
+use super::canvas::{
+    Size,
+    Color,
+    Ctx
+};
+
+use super::font_manager::FontManager;
+
+use super::list::{
+    ListView,
+    RowHeight,
+    SelectionMode,
+};
+
+use super::widgets::{
+    self,
+    Change,
+    Element,
+    EventCtx,
+    Label,
+    Widget,
+    WidgetEvent,
+    WidgetOptions,
+};
+
+use glutin::event::VirtualKeyCode;
+
+use stretch::{
+    Stretch,
+    style::*,
+};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+
+// Space before the rows and from one level to the next,
+// the disclosure triangle takes one of them
+const ROW_PADDING: f32 = 4.0;
+const INDENT: f32 = 16.0;
+const CHECKBOX_SIZE: f32 = 12.0;
+const CHECKBOX_GAP: f32 = 6.0;
+
+// A node of a TreeView. `children` is None for a node which has
+// children that were not loaded yet: expanding it emits
+// `TreeView::on_expand`, and the view should then give them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub id: String,
+    pub label: String,
+    pub children: Option<Vec<TreeNode>>,
+    pub checked: bool,
+}
+
+impl TreeNode {
+    pub fn new(id: &str, label: &str) -> TreeNode {
+        TreeNode {
+            id: id.to_string(),
+            label: label.to_string(),
+            children: Some(vec![]),
+            checked: false,
+        }
+    }
+
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> TreeNode {
+        self.children = Some(children);
+        self
+    }
+
+    // Has children, loaded when the node is expanded
+    pub fn lazy(mut self) -> TreeNode {
+        self.children = None;
+        self
+    }
+
+    pub fn checked(mut self, checked: bool) -> TreeNode {
+        self.checked = checked;
+        self
+    }
+}
+
+// A node as a row of the list
+struct TreeRowData {
+    id: String,
+    label: String,
+    depth: usize,
+    parent: Option<usize>,
+    // Whether the node is expanded, None without children
+    expanded: Option<bool>,
+    checked: bool,
+    // Stands for children still loading
+    placeholder: bool,
+}
+
+// What the rows are built from, shared with
+// the builder of the ListView of the tree
+struct TreeState {
+    roots: Vec<TreeNode>,
+    expanded: BTreeSet<String>,
+    // The nodes visible, parents first
+    rows: Vec<TreeRowData>,
+    // Index of the row of each node id
+    index: HashMap<String, usize>,
+    checkboxes: bool,
+    text_color: Color,
+    font_size: f32,
+}
+
+impl TreeState {
+    fn flatten(&mut self) {
+        let mut rows = vec![];
+
+        for node in self.roots.iter() {
+            push_rows(node, 0, None, &self.expanded, &mut rows);
+        }
+
+        self.index = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| !row.placeholder)
+            .map(|(index, row)| (row.id.clone(), index))
+            .collect();
+
+        self.rows = rows;
+    }
+
+    fn row(&self, index: usize, selected: bool) -> Element {
+        let row = &self.rows[index];
+
+        let color = if selected && !row.placeholder {
+            self.text_color.with_opacity(0.15)
+        } else {
+            Color::from_rgba(0.0, 0.0, 0.0, 0.0)
+        };
+
+        let label = Label::new(
+            WidgetOptions {
+                color: if row.placeholder { self.text_color.with_opacity(0.5) } else { self.text_color },
+                font_size: self.font_size,
+                ..Default::default()
+            },
+            row.label.clone()
+        );
+
+        let checkbox = if self.checkboxes && !row.placeholder { Some(row.checked) } else { None };
+
+        TreeRow::new(&row.id, color, self.text_color, row.depth, row.expanded, checkbox, label)
+    }
+
+    // Ids of the nodes of some rows
+    fn ids(&self, rows: &[usize]) -> Vec<String> {
+        rows.iter()
+            .filter_map(|index| self.rows.get(*index))
+            .filter(|row| !row.placeholder)
+            .map(|row| row.id.clone())
+            .collect()
+    }
+}
+
+fn push_rows(node: &TreeNode, depth: usize, parent: Option<usize>, expanded: &BTreeSet<String>, rows: &mut Vec<TreeRowData>) {
+    let index = rows.len();
+    let has_children = node.children.as_ref().is_none_or(|children| !children.is_empty());
+    let is_expanded = has_children && expanded.contains(&node.id);
+
+    rows.push(TreeRowData {
+        id: node.id.clone(),
+        label: node.label.clone(),
+        depth,
+        parent,
+        expanded: if has_children { Some(is_expanded) } else { None },
+        checked: node.checked,
+        placeholder: false,
+    });
+
+    if is_expanded {
+        match &node.children {
+            Some(children) => {
+                for child in children.iter() {
+                    push_rows(child, depth + 1, Some(index), expanded, rows);
+                }
+            },
+            None => rows.push(TreeRowData {
+                id: format!("{}/loading", node.id),
+                label: "Loading...".to_string(),
+                depth: depth + 1,
+                parent: Some(index),
+                expanded: None,
+                checked: false,
+                placeholder: true,
+            }),
+        }
+    }
+}
+
+// Left of the content of a row at `depth`, from the left of the row
+fn indent(depth: usize) -> f32 {
+    ROW_PADDING + depth as f32 * INDENT
+}
+
+enum RowPart {
+    Disclosure,
+    Checkbox,
+    Label,
+}
+
+// Build the messages emitted with the node expanded,
+// and with a node and whether it is now checked
+type OnExpand = Box<dyn Fn(String) -> Box<dyn Any>>;
+type OnCheck = Box<dyn Fn(String, bool) -> Box<dyn Any>>;
+
+// Nested nodes as indented rows, built only when visible like the
+// rows of a ListView. The tree keeps which nodes are expanded:
+// clicking the triangle of a node, or Right and Left on the current
+// one, expands and collapses it. Left on a collapsed node moves to
+// its parent, Right on an expanded one to its first child.
+//
+// The selection is reported as node ids, and follows the nodes as
+// rows are inserted and removed. With `with_checkboxes`, each node
+// shows a checkbox, toggled by clicking it or with Space, which
+// emits `on_check`: whether a node is checked comes from the view.
+pub struct TreeView {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    // The ListView of the rows
+    pub children: Vec<Element>,
+    pub dirty: bool,
+    state: Rc<RefCell<TreeState>>,
+    // Last expanded nodes and selection asked for by the view function
+    requested_expanded: Option<Vec<String>>,
+    requested_selection: Option<Vec<String>>,
+    on_expand: Option<OnExpand>,
+    on_check: Option<OnCheck>,
+}
+
+impl TreeView {
+    pub fn new(options: WidgetOptions, roots: Vec<TreeNode>) -> Box<TreeView> {
+        let state = Rc::new(RefCell::new(TreeState {
+            roots,
+            expanded: BTreeSet::new(),
+            rows: vec![],
+            index: HashMap::new(),
+            checkboxes: false,
+            text_color: options.text_color,
+            font_size: options.font_size,
+        }));
+
+        state.borrow_mut().flatten();
+
+        let count = state.borrow().rows.len();
+
+        let rows = state.clone();
+        let body = ListView::new(
+            WidgetOptions {
+                id: "tree_body".to_string(),
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                text_color: options.text_color,
+                flex: 1.0,
+                ..Default::default()
+            },
+            count,
+            move |index, selected| rows.borrow().row(index, selected)
+        ).with_row_height(RowHeight::Fixed((options.font_size * 1.25 + ROW_PADDING * 2.0).round()));
+
+        Box::new(TreeView {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            children: vec![body],
+            dirty: true,
+            state,
+            requested_expanded: None,
+            requested_selection: None,
+            on_expand: None,
+            on_check: None,
+        })
+    }
+
+    pub fn with_row_height(self: Box<Self>, row_height: RowHeight) -> Box<TreeView> {
+        self.map_body(|body| body.with_row_height(row_height))
+    }
+
+    pub fn with_selection_mode(self: Box<Self>, selection_mode: SelectionMode) -> Box<TreeView> {
+        self.map_body(|body| body.with_selection_mode(selection_mode))
+    }
+
+    // Shows a checkbox before each node, Space toggles
+    // the one of the current node instead of selecting it
+    pub fn with_checkboxes(self: Box<Self>) -> Box<TreeView> {
+        self.state.borrow_mut().checkboxes = true;
+
+        self.map_body(|body| body.with_space_selection(false))
+    }
+
+    // The nodes expanded to begin with. Once the tree is on screen
+    // it expands them again whenever the value passed here changes.
+    pub fn with_expanded(mut self: Box<Self>, expanded: Vec<String>) -> Box<TreeView> {
+        let kept = self.remember();
+
+        self.state.borrow_mut().expanded = expanded.iter().cloned().collect();
+        self.requested_expanded = Some(expanded);
+        self.refresh(kept);
+
+        self
+    }
+
+    // The nodes selected to begin with, see `ListView::with_selection`
+    pub fn with_selection(mut self: Box<Self>, selection: Vec<String>) -> Box<TreeView> {
+        let current = selection.last().cloned();
+
+        self.requested_selection = Some(selection.clone());
+        self.refresh((selection, current));
+
+        self
+    }
+
+    // Message emitted with the id of a node when it is expanded,
+    // the view should give its children if they are not loaded
+    pub fn on_expand<M: 'static, F: Fn(String) -> M + 'static>(mut self: Box<Self>, on_expand: F) -> Box<TreeView> {
+        self.on_expand = Some(Box::new(move |id| Box::new(on_expand(id))));
+
+        self
+    }
+
+    // Message emitted with the id of a node and
+    // whether it should now be checked
+    pub fn on_check<M: 'static, F: Fn(String, bool) -> M + 'static>(mut self: Box<Self>, on_check: F) -> Box<TreeView> {
+        self.on_check = Some(Box::new(move |id, checked| Box::new(on_check(id, checked))));
+
+        self
+    }
+
+    // Message emitted with the ids of the selected nodes,
+    // in the order they are shown, every time they change
+    pub fn on_select<M: 'static, F: Fn(Vec<String>) -> M + 'static>(self: Box<Self>, on_select: F) -> Box<TreeView> {
+        let state = self.state.clone();
+
+        self.map_body(move |body| body.on_select(move |rows: Vec<usize>| on_select(state.borrow().ids(&rows))))
+    }
+
+    // Message emitted with the id of the current node when Enter is pressed
+    pub fn on_activate<M: 'static, F: Fn(String) -> M + 'static>(self: Box<Self>, on_activate: F) -> Box<TreeView> {
+        let state = self.state.clone();
+
+        self.map_body(move |body| body.on_activate(move |row| on_activate(state.borrow().rows[row].id.clone())))
+    }
+
+    pub fn is_expanded(&self, id: &str) -> bool {
+        self.state.borrow().expanded.contains(id)
+    }
+
+    // The body is the last child
+    fn map_body<F: FnOnce(Box<ListView>) -> Box<ListView>>(mut self: Box<Self>, f: F) -> Box<TreeView> {
+        if let Some(body) = self.children.last_mut() {
+            widgets::map_part(body, f);
+        }
+
+        self
+    }
+
+    fn with_body<R: Default, F: FnOnce(&mut ListView) -> R>(&mut self, f: F) -> R {
+        self.children
+            .last_mut()
+            .and_then(|body| widgets::with_part(body, f))
+            .unwrap_or_default()
+    }
+
+    // Ids of the selected nodes and of the current one
+    fn remember(&mut self) -> (Vec<String>, Option<String>) {
+        let (rows, current) = self.with_body(|body| (body.selection(), body.current()));
+        let state = self.state.borrow();
+
+        (state.ids(&rows), current.and_then(|row| state.ids(&[row]).pop()))
+    }
+
+    // Builds the rows again from the nodes, selecting the ones that
+    // were selected if they are still visible. Returns whether the
+    // layout must be computed again, and whether some were not.
+    fn refresh(&mut self, (selection, current): (Vec<String>, Option<String>)) -> (bool, bool) {
+        let (count, rows, current) = {
+            let mut state = self.state.borrow_mut();
+
+            state.flatten();
+
+            let rows: Vec<usize> = selection.iter().filter_map(|id| state.index.get(id).cloned()).collect();
+            let current = current.and_then(|id| state.index.get(&id).cloned());
+
+            (state.rows.len(), rows, current)
+        };
+
+        let hidden = rows.len() != selection.len();
+
+        (self.with_body(|body| body.reset(count, rows, current)), hidden)
+    }
+
+    // Expands or collapses the node of a row
+    fn toggle(&mut self, row: usize, ctx: &mut EventCtx) {
+        let kept = self.remember();
+
+        let (id, expanded) = {
+            let mut state = self.state.borrow_mut();
+            let id = state.rows[row].id.clone();
+            let expanded = !state.expanded.remove(&id);
+
+            if expanded {
+                state.expanded.insert(id.clone());
+            }
+
+            (id, expanded)
+        };
+
+        let (layout, hidden) = self.refresh(kept);
+
+        if layout {
+            ctx.request_layout();
+        }
+
+        // Collapsing hid selected nodes
+        if hidden {
+            self.with_body(|body| body.emit_selection(ctx));
+        }
+
+        if let (true, Some(on_expand)) = (expanded, &self.on_expand) {
+            ctx.emit(on_expand(id));
+        }
+    }
+
+    fn check(&self, row: usize, ctx: &mut EventCtx) {
+        let state = self.state.borrow();
+        let row = &state.rows[row];
+
+        if let (false, Some(on_check)) = (row.placeholder, &self.on_check) {
+            ctx.emit(on_check(row.id.clone(), !row.checked));
+        }
+    }
+
+    // The row under `position`, and which part of it
+    fn row_at(&self, position: Point) -> Option<(usize, RowPart)> {
+        let body = &self.children[0];
+
+        if !body.contains(position) {
+            return None;
+        }
+
+        // The rows, then the outline and the scrollbar thumb
+        let rows = body.children().len() - 2;
+        let widget = body.children()[..rows].iter().find(|row| row.contains(position))?;
+
+        let state = self.state.borrow();
+        let index = *state.index.get(&widget.options().id)?;
+        let row = &state.rows[index];
+
+        let x = position.x - widget.position().x - indent(row.depth);
+
+        let part = if row.expanded.is_some() && (0.0..INDENT).contains(&x) {
+            RowPart::Disclosure
+        } else if state.checkboxes && !row.placeholder && (INDENT..INDENT + CHECKBOX_SIZE).contains(&x) {
+            RowPart::Checkbox
+        } else {
+            RowPart::Label
+        };
+
+        Some((index, part))
+    }
+}
+
+impl Widget for TreeView {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.fill();
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let body = self.children[0].generate_stretch_node(stretch, font_manager);
+
+        let size = stretch::geometry::Size {
+            width: self.options.width,
+            height: self.options.height
+        };
+
+        stretch.new_node(
+            Style {
+                flex_direction: FlexDirection::Column,
+                flex_grow: self.options.flex,
+                overflow: Overflow::Hidden,
+                min_size: size,
+                max_size: size,
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            vec![body]
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        let body = stretch.children(*node).unwrap()[0];
+
+        self.children[0].update_layout(stretch, &body, self.position);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let mut new = match new.into_any().downcast::<TreeView>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        // The selection of the rows built from the old nodes
+        let mut kept = self.remember();
+
+        // The nodes come from the view, which of them
+        // are expanded from the user
+        if new.requested_expanded == self.requested_expanded {
+            new.state.borrow_mut().expanded = self.state.borrow().expanded.clone();
+        }
+
+        if new.requested_selection != self.requested_selection {
+            if let Some(selection) = &new.requested_selection {
+                kept = (selection.clone(), selection.last().cloned());
+            }
+        }
+
+        self.requested_expanded = new.requested_expanded;
+        self.requested_selection = new.requested_selection;
+        self.on_expand = new.on_expand;
+        self.on_check = new.on_check;
+
+        // The builder of the new body reads the new state
+        self.state = new.state;
+
+        let body = new.children.pop().unwrap();
+
+        change = change.max(self.children[0].update(body));
+
+        if self.refresh(kept).0 {
+            change = Change::Layout;
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            // Other presses select the row, in the body
+            WidgetEvent::MousePress(position) => match self.row_at(*position) {
+                Some((row, RowPart::Disclosure)) => {
+                    self.toggle(row, ctx);
+
+                    ctx.set_handled();
+                },
+                Some((row, RowPart::Checkbox)) => {
+                    self.check(row, ctx);
+
+                    ctx.set_handled();
+                },
+                _ => ()
+            },
+            // Keys the body did not use
+            WidgetEvent::KeyPress(VirtualKeyCode::Space) => {
+                if let Some(current) = self.with_body(|body| body.current()) {
+                    self.check(current, ctx);
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::KeyPress(key @ VirtualKeyCode::Left) | WidgetEvent::KeyPress(key @ VirtualKeyCode::Right) => {
+                let current = match self.with_body(|body| body.current()) {
+                    Some(current) => current,
+                    None => return,
+                };
+
+                let (expanded, parent, first_child) = {
+                    let state = self.state.borrow();
+                    let row = &state.rows[current];
+
+                    let first_child = state.rows
+                        .get(current + 1)
+                        .filter(|child| child.parent == Some(current) && !child.placeholder)
+                        .map(|_| current + 1);
+
+                    (row.expanded, row.parent, first_child)
+                };
+
+                match (key, expanded) {
+                    (VirtualKeyCode::Right, Some(false)) | (VirtualKeyCode::Left, Some(true)) => self.toggle(current, ctx),
+                    (VirtualKeyCode::Right, Some(true)) => {
+                        if let Some(child) = first_child {
+                            self.with_body(|body| body.select_row(child, ctx));
+                        }
+                    },
+                    (VirtualKeyCode::Left, _) => {
+                        if let Some(parent) = parent {
+                            self.with_body(|body| body.select_row(parent, ctx));
+                        }
+                    },
+                    _ => ()
+                }
+
+                ctx.set_handled();
+            },
+            _ => ()
+        }
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "TreeView"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("TreeView -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// A row of a TreeView: the disclosure triangle of the node,
+// its checkbox and its label, indented by its depth
+struct TreeRow {
+    size: Size<f32>,
+    position: Point,
+    options: WidgetOptions,
+    children: Vec<Element>,
+    depth: usize,
+    expanded: Option<bool>,
+    checkbox: Option<bool>,
+    dirty: bool,
+}
+
+impl TreeRow {
+    fn new(id: &str, color: Color, text_color: Color, depth: usize, expanded: Option<bool>, checkbox: Option<bool>, label: Element) -> Box<TreeRow> {
+        let start = indent(depth) + INDENT + if checkbox.is_some() { CHECKBOX_SIZE + CHECKBOX_GAP } else { 0.0 };
+
+        Box::new(TreeRow {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options: WidgetOptions {
+                id: id.to_string(),
+                color,
+                text_color,
+                flex: 1.0,
+                padding: stretch::geometry::Rect {
+                    start: Dimension::Points(start),
+                    end: Dimension::Points(ROW_PADDING),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            children: vec![label],
+            depth,
+            expanded,
+            checkbox,
+            dirty: true,
+        })
+    }
+}
+
+impl Widget for TreeRow {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        ctx.begin_primitive();
+        ctx.color(self.options.color);
+        ctx.rect(self.position, self.size.width, self.size.height);
+        ctx.fill();
+
+        let left = self.position.x + indent(self.depth);
+        let middle = self.position.y + self.size.height / 2.0;
+
+        if let Some(expanded) = self.expanded {
+            let center: Point = point(left + INDENT / 2.0, middle);
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color.with_opacity(0.7));
+
+            // Pointing down when expanded, right otherwise,
+            // y grows upwards
+            if expanded {
+                ctx.move_to(point(center.x - 4.0, center.y + 2.0));
+                ctx.line_to(point(center.x + 4.0, center.y + 2.0));
+                ctx.line_to(point(center.x, center.y - 3.0));
+            } else {
+                ctx.move_to(point(center.x - 2.0, center.y - 4.0));
+                ctx.line_to(point(center.x + 3.0, center.y));
+                ctx.line_to(point(center.x - 2.0, center.y + 4.0));
+            }
+
+            ctx.close();
+            ctx.fill();
+        }
+
+        if let Some(checked) = self.checkbox {
+            let corner = point(left + INDENT, middle - CHECKBOX_SIZE / 2.0);
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color.with_opacity(0.7));
+            ctx.stroke_width(1.0);
+            ctx.rect(corner, CHECKBOX_SIZE, CHECKBOX_SIZE);
+            ctx.stroke();
+
+            if checked {
+                ctx.begin_primitive();
+                ctx.color(self.options.text_color);
+                ctx.stroke_width(2.0);
+                ctx.move_to(point(corner.x + 2.5, middle));
+                ctx.line_to(point(corner.x + 5.0, middle - 3.0));
+                ctx.line_to(point(corner.x + 9.5, middle + 3.0));
+                ctx.stroke();
+            }
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let mut children_nodes = vec![];
+
+        for child in self.children.iter() {
+            children_nodes.push(child.generate_stretch_node(stretch, font_manager));
+        }
+
+        stretch.new_node(
+            Style {
+                align_items: AlignItems::Center,
+                flex_grow: self.options.flex,
+                padding: self.options.padding,
+                ..Default::default()
+            },
+            children_nodes
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+
+        for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
+            let child = self.children.get_mut(index).unwrap();
+
+            child.update_layout(stretch, child_node, self.position);
+        }
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<TreeRow>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if new.depth != self.depth || new.checkbox.is_some() != self.checkbox.is_some() {
+            change = Change::Layout;
+        }
+
+        if change != Change::None || new.expanded != self.expanded || new.checkbox != self.checkbox {
+            self.options = new.options;
+            self.depth = new.depth;
+            self.expanded = new.expanded;
+            self.checkbox = new.checkbox;
+            self.dirty = true;
+        }
+
+        change.max(widgets::reconcile(&mut self.children, new.children))
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        Some(rect(self.position.x, self.position.y, self.size.width, self.size.height))
+    }
+
+    fn type_name(&self) -> &'static str {
+        "TreeRow"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("TreeRow -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
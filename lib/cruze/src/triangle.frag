@@ -16,11 +16,17 @@ in VS_OUPUT {
   vec4 bbox;
   vec2 f_tex_pos;
   flat int is_textured;
+  flat vec4 clip;
 } IN;
 
 out vec4 Color;
 
 void main() {
+  // Clip is MIN_X, MIN_Y, MAX_X, MAX_Y in window coordinates
+  if (any(lessThan(gl_FragCoord.xy, IN.clip.xy)) || any(greaterThanEqual(gl_FragCoord.xy, IN.clip.zw))) {
+    discard;
+  }
+
   /* BBox
    * TOP = r
    * RIGHT = g
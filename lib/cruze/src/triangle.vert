@@ -1,7 +1,8 @@
 #version 330 core
 
-layout (location = 0) in vec3 Position;
+layout (location = 0) in vec2 Position;
 layout (location = 1) in vec2 TexCoords;
+layout (location = 2) in float PrimitiveIndex;
 
 out VS_OUPUT {
   flat uint gradient_type;
@@ -14,22 +15,37 @@ out VS_OUPUT {
   vec4 bbox;
   vec2 f_tex_pos;
   flat int is_textured;
+  flat vec4 clip;
 } OUT;
 
 
 uniform mat4 projection;
-uniform mat4 model;
 
-uniform vec4 first_color;
-uniform vec4 last_color;
-uniform vec2 start_pos;
-uniform vec2 end_pos;
-uniform vec4 bbox;
-uniform uint gradient_type;
-uniform float radius;
-uniform int is_textured;
+// Per primitive data, see canvas::PRIMITIVE_TEXELS
+uniform samplerBuffer primitives;
+
+const int PRIMITIVE_TEXELS = 10;
 
 void main() {
+  int base = int(PrimitiveIndex) * PRIMITIVE_TEXELS;
+
+  vec4 first_color = texelFetch(primitives, base);
+  vec4 last_color = texelFetch(primitives, base + 1);
+  vec4 gradient_pos = texelFetch(primitives, base + 2);
+  vec4 bbox = texelFetch(primitives, base + 3);
+  vec4 params = texelFetch(primitives, base + 4);
+
+  mat4 model = mat4(
+      texelFetch(primitives, base + 5),
+      texelFetch(primitives, base + 6),
+      texelFetch(primitives, base + 7),
+      texelFetch(primitives, base + 8)
+    );
+
+  vec4 clip = texelFetch(primitives, base + 9);
+
+  int is_textured = int(params.b);
+
   vec4 calculated_position;
 
   calculated_position = vec4(Position.xy, 1.0, 1.0);
@@ -50,13 +66,15 @@ void main() {
   OUT.first_color = first_color;
   OUT.last_color = last_color;
 
-  OUT.start_pos = start_pos;
-  OUT.end_pos = end_pos;
+  OUT.start_pos = gradient_pos.xy;
+  OUT.end_pos = gradient_pos.zw;
 
   OUT.bbox = bbox;
 
-  OUT.gradient_type = gradient_type;
-  OUT.radius = radius;
+  OUT.gradient_type = uint(params.r);
+  OUT.radius = params.g;
 
   OUT.is_textured = is_textured;
+
+  OUT.clip = clip;
 }
@@ -1,5 +1,6 @@
 use lyon::math::{
     Point,
+    Vector,
     point
 };
 
@@ -11,16 +12,176 @@ use super::canvas::{
 
 use super::font_manager::FontManager;
 
+use super::clipboard::Clipboard;
+use super::shortcut::Shortcuts;
+
+use super::animation::{
+    AnimatedStyle,
+    Transform,
+    Transition,
+};
+
+use glutin::event::{
+    ModifiersState,
+    VirtualKeyCode,
+};
+
 use stretch::{
     Stretch,
     style::*,
 };
 
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::convert::{
     Into
 };
+use std::time::{
+    Duration,
+    Instant,
+};
+
+// What a view function returns, a tree of widgets
+// that gets reconciled with the one on screen
+pub type Element = Box<dyn Widget>;
+
+// How much of the on screen tree an update invalidated,
+// ordered from the cheapest to the most expensive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    None,
+    // Only the widget geometry has to be generated again
+    Paint,
+    // Sizes may differ, the layout must be computed again
+    Layout,
+}
+
+// How far the wheel or the touchpad went, positive values
+// go right and away from the user, as in winit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollDelta {
+    // Mouse wheels, in notches
+    Lines(Vector),
+    // Touchpads, in logical pixels
+    Pixels(Vector),
+}
+
+#[derive(Debug, Clone)]
+pub enum WidgetEvent {
+    // Positions are in layout coordinates
+    MousePress(Point),
+    MouseRelease(Point),
+    MouseMove(Point),
+    // Goes to the innermost widgets under the cursor first,
+    // and up to their ancestors until one of them handles it
+    Scroll(Point, ScrollDelta),
+    // The fingers left the touchpad, kinetic scrolling may start
+    ScrollEnd(Point),
+    KeyPress(VirtualKeyCode),
+    Char(char),
+    // The secondary button was pressed, goes to the widgets under
+    // the cursor like `Scroll`. Widgets with a context menu open it.
+    ContextMenu(Point),
+    // Alt and a letter or digit, lowercase, that no widget handled,
+    // sent to every widget: menu bars open the menu it underlines
+    Mnemonic(char),
+    // Sent to every widget once one of them is due, see `next_tick`
+    Tick(Instant),
+}
+
+// Collects the messages emitted by widgets while they handle
+// an event, they are routed to `Application::update` afterwards
+pub struct EventCtx {
+    messages: Vec<Box<dyn Any>>,
+    handled: bool,
+    layout: bool,
+    modifiers: ModifiersState,
+    clipboard: Rc<RefCell<Clipboard>>,
+    shortcuts: Rc<RefCell<Shortcuts>>,
+}
+
+impl Default for EventCtx {
+    fn default() -> EventCtx {
+        EventCtx::new()
+    }
+}
+
+impl EventCtx {
+    pub fn new() -> EventCtx {
+        EventCtx {
+            messages: vec![],
+            handled: false,
+            layout: false,
+            modifiers: ModifiersState::default(),
+            clipboard: Rc::new(RefCell::new(Clipboard::in_memory())),
+            shortcuts: Rc::new(RefCell::new(Shortcuts::new())),
+        }
+    }
+
+    pub(crate) fn with_clipboard(mut self, clipboard: Rc<RefCell<Clipboard>>) -> EventCtx {
+        self.clipboard = clipboard;
+        self
+    }
+
+    pub(crate) fn with_shortcuts(mut self, shortcuts: Rc<RefCell<Shortcuts>>) -> EventCtx {
+        self.shortcuts = shortcuts;
+        self
+    }
+
+    // The registry of the app, see `AppHandle::bind_shortcut`
+    pub(crate) fn shortcuts(&self) -> Rc<RefCell<Shortcuts>> {
+        self.shortcuts.clone()
+    }
+
+    pub(crate) fn with_modifiers(mut self, modifiers: ModifiersState) -> EventCtx {
+        self.modifiers = modifiers;
+        self
+    }
+
+    // Held down when the event happened
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    // For widgets that changed their own children, the
+    // layout of the window is computed again after the event
+    pub fn request_layout(&mut self) {
+        self.layout = true;
+    }
+
+    pub fn is_layout_requested(&self) -> bool {
+        self.layout
+    }
+
+    pub fn copy(&mut self, text: String) {
+        self.clipboard.borrow_mut().set_text(text);
+    }
+
+    pub fn paste(&self) -> Option<String> {
+        self.clipboard.borrow_mut().text()
+    }
+
+    // Stops a keyboard event from bubbling up to the
+    // ancestors, and from moving the focus
+    pub fn set_handled(&mut self) {
+        self.handled = true;
+    }
+
+    pub fn is_handled(&self) -> bool {
+        self.handled
+    }
+
+    pub fn emit(&mut self, message: Box<dyn Any>) {
+        self.messages.push(message);
+    }
+
+    pub fn into_messages(self) -> Vec<Box<dyn Any>> {
+        self.messages
+    }
+}
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Orientation {
     Column,
     Row
@@ -35,7 +196,7 @@ impl Into<FlexDirection> for Orientation {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Alignment {
     Start,
     Center,
@@ -75,7 +236,23 @@ impl Into<JustifyContent> for Alignment {
     }
 }
 
-pub trait Widget {
+// Implemented by every widget, see `with_part`
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub trait Widget: AsAny {
     fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager);
     fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node;
     fn set_size(&mut self, size: Size<f32>);
@@ -83,12 +260,106 @@ pub trait Widget {
     fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
         ()
     }
+    fn type_name(&self) -> &'static str;
+    fn options(&self) -> &WidgetOptions;
+    // Marks the widget as dirty, since options
+    // are likely to change its geometry
+    fn options_mut(&mut self) -> &mut WidgetOptions;
+    fn position(&self) -> Point;
+    fn size(&self) -> Size<f32>;
+    // Hit testing, `point` is in layout coordinates
+    fn contains(&self, point: Point) -> bool {
+        let position = self.position();
+        let size = self.size();
+
+        point.x >= position.x && point.x < position.x + size.width &&
+            point.y >= position.y && point.y < position.y + size.height
+    }
+    // Called on every widget when the cursor moves
+    fn set_hovered(&mut self, _hovered: bool) {
+    }
+    // Takes the declarative properties (options, text, children)
+    // of a freshly built element of the same type and id, keeping
+    // the internal state of the widget on screen
+    fn update(&mut self, new: Element) -> Change;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    // Mouse events reach every widget, which has to check
+    // `contains`. Keyboard events go to the focused widget, then
+    // bubble up through its ancestors until one of them handles
+    // them; with nothing focused they go to the topmost layer.
+    // While a popup is open, see `is_popup`, keyboard events start
+    // from it instead, and so do presses and scrolls over it.
+    fn on_event(&mut self, _event: &WidgetEvent, _ctx: &mut EventCtx) {
+    }
+    fn is_focusable(&self) -> bool {
+        false
+    }
+    fn is_focused(&self) -> bool {
+        false
+    }
+    fn set_focused(&mut self, _focused: bool) {
+    }
+    // Current values of `options.transform` and `options.opacity`,
+    // which differ while they are animated. They apply to the
+    // widget itself, not to its children.
+    fn transform(&self) -> Transform {
+        self.options().transform
+    }
+    fn opacity(&self) -> f32 {
+        self.options().opacity
+    }
+    // When the widget wants the next `Tick`. Ticks come at most once
+    // per animation frame, so an instant in the past asks for the
+    // next frame. Without a tick due, the event loop sleeps.
+    fn next_tick(&self) -> Option<Instant> {
+        None
+    }
+    // A modal widget on a window layer keeps
+    // input away from the layers below
+    fn is_modal(&self) -> bool {
+        false
+    }
+    // An open popup, like a menu, is drawn above the rest of its
+    // window layer, out of the clips of its ancestors. Widgets
+    // under it get no hover, presses or scrolls.
+    fn is_popup(&self) -> bool {
+        false
+    }
+    // Called on open popups once the window is laid out, so that
+    // they move where they fit in `window`
+    fn place_popup(&mut self, _window: lyon::math::Rect) {
+    }
+    // Area the children are clipped to, in layout coordinates.
+    // Children get no mouse press, hover or scroll outside of it.
+    fn clip(&self) -> Option<lyon::math::Rect> {
+        None
+    }
+    // Widgets that build their children from the size they got
+    // (ListView) ask for another layout pass after `update_layout`
+    fn needs_layout(&self) -> bool {
+        false
+    }
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &[]
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        None
+    }
+    // A dirty widget gets re-tessellated the next
+    // time the canvas is updated
+    fn is_dirty(&self) -> bool;
+    fn set_dirty(&mut self, dirty: bool);
     fn debug(&self);
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct WidgetOptions {
     pub id: String,
     pub color: Color,
+    // Used instead of `color` while the cursor is over the widget
+    pub hover_color: Option<Color>,
+    // Text drawn on top of a filled widget (Button, TextInput)
+    pub text_color: Color,
     pub padding: stretch::geometry::Rect<stretch::style::Dimension>,
     pub margin: stretch::geometry::Rect<stretch::style::Dimension>,
     pub vertical_align: Alignment,
@@ -99,6 +370,21 @@ pub struct WidgetOptions {
     pub orientation: Orientation,
     pub flex: f32,
     pub font_size: f32,
+    // Order of focusable widgets when tabbing: positive indexes
+    // first in ascending order, then 0 in tree order. Widgets with
+    // a negative index are skipped, they can be focused otherwise.
+    pub tab_index: i32,
+    // Arrow keys move the focus among the focusable widgets
+    // inside, like the buttons of a toolbar
+    pub focus_group: bool,
+    // How color, radius, opacity, transform and layout changes
+    // are animated by Rect, Button, Label and TextInput, None
+    // applies them at once. Spinner and the composite widgets
+    // (List, Table, TreeView, Tabs, SplitPane, menus, ScrollView)
+    // place their parts themselves every layout and ignore it.
+    pub transition: Option<Transition>,
+    pub opacity: f32,
+    pub transform: Transform,
     pub debug: bool,
 }
 
@@ -125,13 +411,114 @@ impl WidgetOptions {
             height: Dimension::Points(amount)
         }
     }
+
+    pub fn diff(&self, other: &WidgetOptions) -> Change {
+        if self == other {
+            return Change::None;
+        }
+
+        // Fields that only affect how a widget is painted
+        let paint_only = WidgetOptions {
+            color: other.color,
+            hover_color: other.hover_color,
+            text_color: other.text_color,
+            radius: other.radius,
+            tab_index: other.tab_index,
+            focus_group: other.focus_group,
+            transition: other.transition,
+            opacity: other.opacity,
+            transform: other.transform,
+            debug: other.debug,
+            ..self.clone()
+        };
+
+        if paint_only == *other {
+            Change::Paint
+        } else {
+            Change::Layout
+        }
+    }
+}
+
+// Widgets with this id are matched by position only
+pub const ANONYMOUS_ID: &str = "###";
+
+fn same_widget(old: &Element, new: &Element, old_index: usize, new_index: usize) -> bool {
+    if old.type_name() != new.type_name() || old.options().id != new.options().id {
+        return false;
+    }
+
+    old.options().id != ANONYMOUS_ID || old_index == new_index
+}
+
+// Brings `old` in line with `new`: widgets are matched by type
+// and id and updated in place, so that their internal state
+// survives, unmatched elements are inserted as they are
+pub fn reconcile(old: &mut Vec<Element>, new: Vec<Element>) -> Change {
+    let mut change = if old.len() != new.len() { Change::Layout } else { Change::None };
+
+    let mut previous: Vec<Option<Element>> = old.drain(..).map(Some).collect();
+
+    for (new_index, element) in new.into_iter().enumerate() {
+        let matched = previous
+            .iter()
+            .enumerate()
+            .position(|(old_index, widget)| match widget {
+                Some(widget) => same_widget(widget, &element, old_index, new_index),
+                None => false,
+            });
+
+        match matched {
+            Some(old_index) => {
+                let mut widget = previous[old_index].take().unwrap();
+
+                if old_index != new_index {
+                    change = Change::Layout;
+                }
+
+                change = change.max(widget.update(element));
+
+                old.push(widget);
+            },
+            None => {
+                change = Change::Layout;
+
+                old.push(element);
+            }
+        }
+    }
+
+    change
+}
+
+// Composite widgets keep the parts they build among their
+// children, as elements: these give them back the concrete
+// type. Nothing happens if `part` is not a `W`.
+pub(crate) fn with_part<W: Widget, R, F: FnOnce(&mut W) -> R>(part: &mut Element, f: F) -> Option<R> {
+    part.as_mut().as_any_mut().downcast_mut::<W>().map(f)
+}
+
+// For the builder methods of `W`, which take it by value
+pub(crate) fn map_part<W: Widget, F: FnOnce(Box<W>) -> Box<W>>(part: &mut Element, f: F) {
+    if !part.as_ref().as_any().is::<W>() {
+        return;
+    }
+
+    // Only held while `f` runs
+    let taken = std::mem::replace(part, Container::new(vec![]));
+
+    if let Ok(widget) = taken.into_any().downcast::<W>() {
+        *part = f(widget);
+    }
 }
 
 impl Default for WidgetOptions {
     fn default() -> WidgetOptions {
         WidgetOptions {
-            id: "###".to_string(),
+            id: ANONYMOUS_ID.to_string(),
             color: Color::from_rgb(1.0, 1.0, 1.0),
+            hover_color: None,
+            text_color: Color::from_rgb(0.0, 0.0, 0.0),
             padding: WidgetOptions::uniform_padding(0.0),
             margin: WidgetOptions::uniform_padding(0.0),
             radius: 0.0,
@@ -142,6 +529,11 @@ impl Default for WidgetOptions {
             horizontal_align: Alignment::Undefined,
             font_size: 14.0,
             flex: 1.0,
+            tab_index: 0,
+            focus_group: false,
+            transition: None,
+            opacity: 1.0,
+            transform: Transform::identity(),
             debug: false,
         }
     }
@@ -152,30 +544,43 @@ pub struct Container {
     pub size: Size<f32>,
     pub position: Point,
     pub options: WidgetOptions,
-    pub children: Vec<Box<dyn Widget>>
+    pub children: Vec<Box<dyn Widget>>,
+    pub dirty: bool,
+    pub hovered: bool,
+    style: AnimatedStyle,
 }
 
 impl Container {
     pub fn new(children: Vec<Box<dyn Widget>>) -> Box<Container> {
+        let options = WidgetOptions::default();
+
         Box::new(Container {
             size: Size::new(0.0, 0.0),
             position: point(0.0, 0.0),
-            options: WidgetOptions::default(),
-            children
+            style: AnimatedStyle::new(options.color, &options),
+            options,
+            children,
+            dirty: true,
+            hovered: false,
         })
     }
+
+    fn paint_color(&self) -> Color {
+        match self.options.hover_color {
+            Some(hover_color) if self.hovered => hover_color,
+            _ => self.options.color,
+        }
+    }
 }
 
 impl Widget for Container {
-    fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager) {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        let size = self.size();
+
         ctx.begin_primitive();
-        ctx.color(self.options.color);
-        ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+        ctx.color(*self.style.color.get());
+        ctx.round_rect(self.position(), size.width, size.height, *self.style.radius.get());
         ctx.fill();
-
-        for child in self.children.iter() {
-            child.draw(ctx, font_manager);
-        }
     }
 
     fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
@@ -224,15 +629,24 @@ impl Widget for Container {
     fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
         let layout = stretch.layout(*node).unwrap();
 
-        self.set_size(Size {
+        let size = Size {
             width: layout.size.width,
             height: layout.size.height
-        });
+        };
 
-        self.set_position(lyon::math::point(
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        // Children are laid out against where the
+        // container ends up, not where it is drawn now
+        self.size = size;
+        self.position = lyon::math::point(
             layout.location.x + position.x,
             layout.location.y + position.y
-        ));
+        );
+
+        self.style.place(self.position, self.size, &self.options);
 
         for (index, child_node) in stretch.children(*node).unwrap().iter().enumerate() {
             // Get child at position index, this is the array of children of Window
@@ -243,11 +657,110 @@ impl Widget for Container {
     }
 
     fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
         self.size = size;
+        self.style.size.set(size, None);
     }
 
     fn set_position(&mut self, position: Point) {
-        self.position = position;
+        let delta = position - self.position();
+
+        self.position += delta;
+        self.style.position.translate(delta);
+    }
+
+    fn set_hovered(&mut self, hovered: bool) {
+        if hovered != self.hovered && self.options.hover_color.is_some() {
+            self.dirty = true;
+        }
+
+        self.hovered = hovered;
+
+        self.style.animate_to(self.paint_color(), &self.options);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<Container>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            self.style.animate_to(self.paint_color(), &self.options);
+        }
+
+        change.max(reconcile(&mut self.children, new.children))
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, _ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Tick(now) if self.style.sample(*now) => {
+                self.dirty = true;
+            },
+            _ => ()
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        *self.style.transform.get()
+    }
+
+    fn opacity(&self) -> f32 {
+        *self.style.opacity.get()
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.style.next_tick()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Container"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        *self.style.position.get()
+    }
+
+    fn size(&self) -> Size<f32> {
+        *self.style.size.get()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn children(&self) -> &[Box<dyn Widget>] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Box<dyn Widget>>> {
+        Some(&mut self.children)
     }
 
     fn debug(&self) {
@@ -262,8 +775,11 @@ impl Rect {
         Box::new(Container {
             size: Size::new(0.0, 0.0),
             position: point(0.0, 0.0),
+            style: AnimatedStyle::new(options.color, &options),
             options: options,
-            children
+            children,
+            dirty: true,
+            hovered: false,
         })
     }
 }
@@ -273,6 +789,8 @@ pub struct Label {
     pub position: Point,
     pub text: String,
     pub options: WidgetOptions,
+    pub dirty: bool,
+    style: AnimatedStyle,
 }
 
 impl Label {
@@ -280,25 +798,37 @@ impl Label {
         Box::new(Label {
             size: Size::new(0.0, 0.0),
             position: point(0.0, 0.0),
+            style: AnimatedStyle::new(options.color, &options),
             options,
             text,
+            dirty: true,
         })
     }
+
+    pub fn set_text(&mut self, text: String) {
+        if text != self.text {
+            self.text = text;
+            self.dirty = true;
+        }
+    }
 }
 
 impl Widget for Label {
     fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager) {
+        let position = self.position();
+        let size = self.size();
+
         if self.options.debug {
             ctx.begin_primitive();
             ctx.color(Color::from_rgb(1.0, 0.0, 0.0));
-            ctx.round_rect(self.position, self.size.width, self.size.height, self.options.radius);
+            ctx.round_rect(position, size.width, size.height, self.options.radius);
             ctx.fill();
         }
 
         ctx.begin_primitive();
-        ctx.color(self.options.color);
+        ctx.color(*self.style.color.get());
         ctx.font_size(self.options.font_size);
-        ctx.text(self.position, self.text.clone(), font_manager);
+        ctx.text(position, self.text.clone(), font_manager);
     }
 
     fn generate_stretch_node<'a>(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
@@ -324,26 +854,1082 @@ impl Widget for Label {
     fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
         let layout = stretch.layout(*node).unwrap();
 
-        self.set_size(Size {
+        let size = Size {
             width: layout.size.width,
             height: layout.size.height
-        });
+        };
 
-        self.set_position(lyon::math::point(
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+        self.position = lyon::math::point(
             layout.location.x + position.x,
             layout.location.y + position.y
-        ));
+        );
+
+        self.style.place(self.position, self.size, &self.options);
     }
 
     fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
         self.size = size;
+        self.style.size.set(size, None);
     }
 
     fn set_position(&mut self, position: Point) {
-        self.position = position;
+        let delta = position - self.position();
+
+        self.position += delta;
+        self.style.position.translate(delta);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<Label>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            self.style.animate_to(self.options.color, &self.options);
+        }
+
+        if new.text != self.text {
+            self.set_text(new.text);
+
+            change = Change::Layout;
+        }
+
+        change
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, _ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Tick(now) if self.style.sample(*now) => {
+                self.dirty = true;
+            },
+            _ => ()
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        *self.style.transform.get()
+    }
+
+    fn opacity(&self) -> f32 {
+        *self.style.opacity.get()
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.style.next_tick()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Label"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        *self.style.position.get()
+    }
+
+    fn size(&self) -> Size<f32> {
+        *self.style.size.get()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
     }
 
     fn debug(&self) {
         println!("Label -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
     }
 }
+
+// Space between the border of a Button or a TextInput and its text
+const TEXT_PADDING: f32 = 8.0;
+
+pub struct Button {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub text: String,
+    pub options: WidgetOptions,
+    pub dirty: bool,
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+    on_press: Option<Box<dyn Fn() -> Box<dyn Any>>>,
+    style: AnimatedStyle,
+}
+
+impl Button {
+    pub fn new(options: WidgetOptions, text: String) -> Box<Button> {
+        Box::new(Button {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            style: AnimatedStyle::new(options.color, &options),
+            options,
+            text,
+            dirty: true,
+            hovered: false,
+            pressed: false,
+            focused: false,
+            on_press: None,
+        })
+    }
+
+    // Message emitted every time the button is clicked
+    pub fn on_press<M: Clone + 'static>(mut self: Box<Self>, message: M) -> Box<Button> {
+        self.on_press = Some(Box::new(move || Box::new(message.clone())));
+
+        self
+    }
+
+    fn paint_color(&self) -> Color {
+        match self.options.hover_color {
+            _ if self.pressed => self.options.color.darker(0.2),
+            Some(hover_color) if self.hovered => hover_color,
+            _ => self.options.color,
+        }
+    }
+
+    fn set_pressed(&mut self, pressed: bool) {
+        self.pressed = pressed;
+        self.dirty = true;
+
+        self.style.animate_to(self.paint_color(), &self.options);
+    }
+}
+
+impl Widget for Button {
+    fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager) {
+        let position = self.position();
+        let size = self.size();
+
+        ctx.begin_primitive();
+        ctx.color(*self.style.color.get());
+        ctx.round_rect(position, size.width, size.height, *self.style.radius.get());
+        ctx.fill();
+
+        // BBox is TOP, RIGHT, BOTTOM, LEFT
+        let (bbox, _) = font_manager.calculate_text_bbox(self.options.font_size, "dejavu".to_string(), &self.text);
+
+        let text_width = bbox.y - bbox.w;
+        let text_height = bbox.x - bbox.z;
+
+        ctx.begin_primitive();
+        ctx.color(self.options.text_color);
+        ctx.font_size(self.options.font_size);
+        ctx.text(
+            point(
+                position.x + (size.width - text_width) / 2.0 - bbox.w,
+                position.y + (size.height - text_height) / 2.0
+            ),
+            self.text.clone(),
+            font_manager
+        );
+
+        // Focus ring, inside so that it is not clipped
+        if self.focused {
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color);
+            ctx.stroke_width(2.0);
+            ctx.round_rect(
+                point(position.x + 2.0, position.y + 2.0),
+                size.width - 4.0,
+                size.height - 4.0,
+                (*self.style.radius.get() - 2.0).max(0.0)
+            );
+            ctx.stroke();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        let (bbox, _) = font_manager.calculate_text_bbox(self.options.font_size, "dejavu".to_string(), &self.text);
+
+        let width = bbox.y - bbox.w + TEXT_PADDING * 2.0;
+        let height = bbox.x - bbox.z + TEXT_PADDING * 2.0;
+
+        stretch.new_leaf(
+            Style {
+                size: stretch::geometry::Size {
+                    width: self.options.width,
+                    height: self.options.height
+                },
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            Box::new(move |_| {
+                Ok(stretch::geometry::Size {
+                    width,
+                    height,
+                })
+            })
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        let size = Size {
+            width: layout.size.width,
+            height: layout.size.height
+        };
+
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+        self.position = lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        );
+
+        self.style.place(self.position, self.size, &self.options);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+        self.style.size.set(size, None);
+    }
+
+    fn set_position(&mut self, position: Point) {
+        let delta = position - self.position();
+
+        self.position += delta;
+        self.style.position.translate(delta);
+    }
+
+    fn set_hovered(&mut self, hovered: bool) {
+        if hovered != self.hovered && self.options.hover_color.is_some() {
+            self.dirty = true;
+        }
+
+        self.hovered = hovered;
+
+        self.style.animate_to(self.paint_color(), &self.options);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<Button>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let mut change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            self.style.animate_to(self.paint_color(), &self.options);
+        }
+
+        if new.text != self.text {
+            self.text = new.text;
+            self.dirty = true;
+
+            change = Change::Layout;
+        }
+
+        self.on_press = new.on_press;
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::MousePress(position) if self.contains(*position) => {
+                self.set_pressed(true);
+            },
+            WidgetEvent::MouseRelease(position) if self.pressed => {
+                self.set_pressed(false);
+
+                // Dragging out of the button cancels the click
+                if self.contains(*position) {
+                    if let Some(on_press) = &self.on_press {
+                        ctx.emit(on_press());
+                    }
+                }
+            },
+            // Keyboard activation
+            WidgetEvent::KeyPress(VirtualKeyCode::Space) | WidgetEvent::KeyPress(VirtualKeyCode::Return) => {
+                if let Some(on_press) = &self.on_press {
+                    ctx.emit(on_press());
+                }
+
+                ctx.set_handled();
+            },
+            WidgetEvent::Tick(now) if self.style.sample(*now) => {
+                self.dirty = true;
+            },
+            _ => ()
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        *self.style.transform.get()
+    }
+
+    fn opacity(&self) -> f32 {
+        *self.style.opacity.get()
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.style.next_tick()
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused != self.focused {
+            self.focused = focused;
+            self.dirty = true;
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Button"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        *self.style.position.get()
+    }
+
+    fn size(&self) -> Size<f32> {
+        *self.style.size.get()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("Button -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// Builds the message emitted with the new value
+type OnChange = Box<dyn Fn(String) -> Box<dyn Any>>;
+
+pub struct TextInput {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub value: String,
+    pub options: WidgetOptions,
+    pub dirty: bool,
+    pub focused: bool,
+    // In chars, not in bytes
+    pub caret: usize,
+    on_change: Option<OnChange>,
+    caret_visible: bool,
+    // When the caret was last shown or hidden
+    blink_time: Instant,
+    style: AnimatedStyle,
+}
+
+// How long the caret stays visible, and then hidden
+const CARET_BLINK: Duration = Duration::from_millis(500);
+
+impl TextInput {
+    pub fn new(options: WidgetOptions, value: String) -> Box<TextInput> {
+        Box::new(TextInput {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            caret: value.chars().count(),
+            style: AnimatedStyle::new(options.color, &options),
+            options,
+            value,
+            dirty: true,
+            focused: false,
+            on_change: None,
+            caret_visible: true,
+            blink_time: Instant::now(),
+        })
+    }
+
+    // Builds the message emitted with the new value every time
+    // the user edits the text. The value shown only changes once
+    // the view passes it back.
+    pub fn on_change<M: 'static, F: Fn(String) -> M + 'static>(mut self: Box<Self>, on_change: F) -> Box<TextInput> {
+        self.on_change = Some(Box::new(move |value| Box::new(on_change(value))));
+
+        self
+    }
+
+    fn byte_offset(&self, caret: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(caret)
+            .map(|(offset, _)| offset)
+            .unwrap_or(self.value.len())
+    }
+
+    fn edit(&mut self, value: String, caret: usize, ctx: &mut EventCtx) {
+        self.caret = caret;
+        self.dirty = true;
+        self.show_caret();
+
+        match &self.on_change {
+            Some(on_change) => ctx.emit(on_change(value)),
+            // Nobody owns the value, so it lives here
+            None => self.value = value,
+        }
+    }
+
+    fn move_caret(&mut self, caret: usize) {
+        if caret != self.caret {
+            self.caret = caret;
+            self.dirty = true;
+            self.show_caret();
+        }
+    }
+
+    // The caret does not blink while the user is typing
+    fn show_caret(&mut self) {
+        if !self.caret_visible {
+            self.caret_visible = true;
+            self.dirty = true;
+        }
+
+        self.blink_time = Instant::now();
+    }
+}
+
+impl Widget for TextInput {
+    fn draw(&self, ctx: &mut Ctx, font_manager: &mut FontManager) {
+        let position = self.position();
+        let size = self.size();
+
+        let font = "dejavu".to_string();
+        let line_height = font_manager.line_height(self.options.font_size, font.clone());
+        let line_top = position.y + (size.height - line_height) / 2.0;
+        let text_left = position.x + TEXT_PADDING;
+
+        let radius = *self.style.radius.get();
+
+        ctx.begin_primitive();
+        ctx.color(*self.style.color.get());
+        ctx.round_rect(position, size.width, size.height, radius);
+        ctx.fill();
+
+        ctx.begin_primitive();
+        ctx.color(self.options.text_color);
+        ctx.stroke_width(if self.focused { 2.0 } else { 1.0 });
+        ctx.round_rect(position, size.width, size.height, radius);
+        ctx.stroke();
+
+        if !self.value.is_empty() {
+            // BBox is TOP, RIGHT, BOTTOM, LEFT, the baseline has
+            // to stay put whatever glyphs are in the value
+            let (bbox, _) = font_manager.calculate_text_bbox(self.options.font_size, font.clone(), &self.value);
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color);
+            ctx.font_size(self.options.font_size);
+            ctx.text(
+                point(text_left, line_top + line_height - bbox.x),
+                self.value.clone(),
+                font_manager
+            );
+        }
+
+        if self.focused && self.caret_visible {
+            let prefix = &self.value[..self.byte_offset(self.caret)];
+            let caret_x = text_left + font_manager.advance_width(self.options.font_size, font, prefix);
+
+            ctx.begin_primitive();
+            ctx.color(self.options.text_color);
+            ctx.rect(point(caret_x, line_top), 1.0, line_height);
+            ctx.fill();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, font_manager: &mut FontManager) -> stretch::node::Node {
+        // The size does not depend on the value,
+        // so typing does not trigger a relayout
+        let height = font_manager.line_height(self.options.font_size, "dejavu".to_string()) + TEXT_PADDING * 2.0;
+
+        stretch.new_leaf(
+            Style {
+                size: stretch::geometry::Size {
+                    width: self.options.width,
+                    height: self.options.height
+                },
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            Box::new(move |_| {
+                Ok(stretch::geometry::Size {
+                    width: 160.0,
+                    height,
+                })
+            })
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        let size = Size {
+            width: layout.size.width,
+            height: layout.size.height
+        };
+
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+        self.position = lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        );
+
+        self.style.place(self.position, self.size, &self.options);
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+        self.style.size.set(size, None);
+    }
+
+    fn set_position(&mut self, position: Point) {
+        let delta = position - self.position();
+
+        self.position += delta;
+        self.style.position.translate(delta);
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<TextInput>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+
+            self.style.animate_to(self.options.color, &self.options);
+        }
+
+        self.on_change = new.on_change;
+
+        let value_changed = new.value != self.value;
+
+        if value_changed {
+            self.value = new.value;
+            self.dirty = true;
+        }
+
+        // The caret moves with an edit before the view takes
+        // the new value, it may have rejected it
+        let caret = self.caret.min(self.value.chars().count());
+        let caret_moved = caret != self.caret;
+
+        if caret_moved {
+            self.caret = caret;
+            self.dirty = true;
+        }
+
+        if value_changed || caret_moved {
+            return change.max(Change::Paint);
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, ctx: &mut EventCtx) {
+        if let WidgetEvent::Tick(now) = event {
+            if self.style.sample(*now) {
+                self.dirty = true;
+            }
+        }
+
+        if !self.focused {
+            return;
+        }
+
+        let length = self.value.chars().count();
+
+        match event {
+            WidgetEvent::Tick(now) if *now >= self.blink_time + CARET_BLINK => {
+                self.caret_visible = !self.caret_visible;
+                self.blink_time = *now;
+                self.dirty = true;
+            },
+            WidgetEvent::Char(c) if !c.is_control() => {
+                let mut value = self.value.clone();
+
+                value.insert(self.byte_offset(self.caret), *c);
+
+                self.edit(value, self.caret + 1, ctx);
+            },
+            WidgetEvent::KeyPress(VirtualKeyCode::Back) if self.caret > 0 && self.caret <= length => {
+                let mut value = self.value.clone();
+
+                value.remove(self.byte_offset(self.caret - 1));
+
+                self.edit(value, self.caret - 1, ctx);
+            },
+            WidgetEvent::KeyPress(VirtualKeyCode::Delete) if self.caret < length => {
+                let mut value = self.value.clone();
+
+                value.remove(self.byte_offset(self.caret));
+
+                self.edit(value, self.caret, ctx);
+            },
+            WidgetEvent::KeyPress(VirtualKeyCode::Left) if self.caret > 0 => self.move_caret(self.caret - 1),
+            WidgetEvent::KeyPress(VirtualKeyCode::Right) => self.move_caret((self.caret + 1).min(length)),
+            WidgetEvent::KeyPress(VirtualKeyCode::Home) => self.move_caret(0),
+            WidgetEvent::KeyPress(VirtualKeyCode::End) => self.move_caret(length),
+            _ => ()
+        }
+
+        // Keys editing the text stop here, even
+        // when there is nothing to do with them
+        let handled = match event {
+            WidgetEvent::Char(c) => !c.is_control(),
+            WidgetEvent::KeyPress(
+                VirtualKeyCode::Back | VirtualKeyCode::Delete |
+                VirtualKeyCode::Left | VirtualKeyCode::Right |
+                VirtualKeyCode::Home | VirtualKeyCode::End
+            ) => true,
+            _ => false,
+        };
+
+        if handled {
+            ctx.set_handled();
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused != self.focused {
+            self.focused = focused;
+            self.dirty = true;
+            self.show_caret();
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        *self.style.transform.get()
+    }
+
+    fn opacity(&self) -> f32 {
+        *self.style.opacity.get()
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        let blink = if self.focused {
+            Some(self.blink_time + CARET_BLINK)
+        } else {
+            None
+        };
+
+        blink.into_iter().chain(self.style.next_tick()).min()
+    }
+
+    fn type_name(&self) -> &'static str {
+        "TextInput"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        *self.style.position.get()
+    }
+
+    fn size(&self) -> Size<f32> {
+        *self.style.size.get()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("TextInput -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+// Dots going round while something is in progress,
+// `options.color` is the color of the leading dot
+pub struct Spinner {
+    pub size: Size<f32>,
+    pub position: Point,
+    pub options: WidgetOptions,
+    pub dirty: bool,
+    // Index of the leading dot
+    pub step: usize,
+    step_time: Instant,
+}
+
+const SPINNER_DOTS: usize = 8;
+const SPINNER_STEP: Duration = Duration::from_millis(100);
+
+impl Spinner {
+    pub fn new(options: WidgetOptions) -> Box<Spinner> {
+        Box::new(Spinner {
+            size: Size::new(0.0, 0.0),
+            position: point(0.0, 0.0),
+            options,
+            dirty: true,
+            step: 0,
+            step_time: Instant::now(),
+        })
+    }
+}
+
+impl Widget for Spinner {
+    fn draw(&self, ctx: &mut Ctx, _font_manager: &mut FontManager) {
+        let radius = self.size.width.min(self.size.height) / 2.0;
+        let dot_radius = radius / 5.0;
+        let center: Point = point(self.position.x + self.size.width / 2.0, self.position.y + self.size.height / 2.0);
+
+        for dot in 0..SPINNER_DOTS {
+            // Dots behind the leading one fade out
+            let age = (self.step + SPINNER_DOTS - dot) % SPINNER_DOTS;
+            let angle = dot as f32 / SPINNER_DOTS as f32 * std::f32::consts::PI * 2.0;
+            let color = self.options.color.to_vec();
+
+            ctx.begin_primitive();
+            ctx.color(Color::from_rgba(color.x, color.y, color.z, color.w * (1.0 - age as f32 / SPINNER_DOTS as f32)));
+            ctx.circle(
+                point(
+                    center.x + angle.cos() * (radius - dot_radius),
+                    center.y + angle.sin() * (radius - dot_radius)
+                ),
+                dot_radius
+            );
+            ctx.fill();
+        }
+    }
+
+    fn generate_stretch_node(&self, stretch: &mut Stretch, _font_manager: &mut FontManager) -> stretch::node::Node {
+        let size = self.options.font_size * 2.0;
+
+        stretch.new_leaf(
+            Style {
+                size: stretch::geometry::Size {
+                    width: self.options.width,
+                    height: self.options.height
+                },
+                margin: self.options.margin,
+                ..Default::default()
+            },
+            Box::new(move |_| {
+                Ok(stretch::geometry::Size {
+                    width: size,
+                    height: size,
+                })
+            })
+        ).unwrap()
+    }
+
+    fn update_layout(&mut self, stretch: &Stretch, node: &stretch::node::Node, position: Point) {
+        let layout = stretch.layout(*node).unwrap();
+
+        self.set_size(Size {
+            width: layout.size.width,
+            height: layout.size.height
+        });
+
+        self.set_position(lyon::math::point(
+            layout.location.x + position.x,
+            layout.location.y + position.y
+        ));
+    }
+
+    fn set_size(&mut self, size: Size<f32>) {
+        if size != self.size {
+            self.dirty = true;
+        }
+
+        self.size = size;
+    }
+
+    fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    fn update(&mut self, new: Element) -> Change {
+        let new = match new.into_any().downcast::<Spinner>() {
+            Ok(new) => *new,
+            Err(_) => return Change::None,
+        };
+
+        let change = self.options.diff(&new.options);
+
+        if change != Change::None {
+            self.options = new.options;
+            self.dirty = true;
+        }
+
+        change
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn on_event(&mut self, event: &WidgetEvent, _ctx: &mut EventCtx) {
+        match event {
+            WidgetEvent::Tick(now) if *now >= self.step_time + SPINNER_STEP => {
+                self.step = (self.step + 1) % SPINNER_DOTS;
+                self.step_time = *now;
+                self.dirty = true;
+            },
+            _ => ()
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        Some(self.step_time + SPINNER_STEP)
+    }
+
+    fn type_name(&self) -> &'static str {
+        "Spinner"
+    }
+
+    fn options(&self) -> &WidgetOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut WidgetOptions {
+        self.dirty = true;
+
+        &mut self.options
+    }
+
+    fn position(&self) -> Point {
+        self.position
+    }
+
+    fn size(&self) -> Size<f32> {
+        self.size
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    fn debug(&self) {
+        println!("Spinner -> ({} x {}) [{}, {}]", self.size.width, self.size.height, self.position.x, self.position.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(value: &str) -> Box<TextInput> {
+        TextInput::new(Default::default(), value.to_string()).on_change(|value| value)
+    }
+
+    fn send(input: &mut TextInput, event: WidgetEvent) -> Vec<String> {
+        let mut ctx = EventCtx::new();
+
+        input.on_event(&event, &mut ctx);
+
+        ctx.into_messages()
+            .into_iter()
+            .map(|message| *message.downcast::<String>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn rejected_edit_leaves_the_caret_in_the_value() {
+        let mut current = input("ab");
+
+        current.set_focused(true);
+
+        assert_eq!(send(&mut current, WidgetEvent::Char('c')), vec!["abc".to_string()]);
+
+        // The view keeps the old value
+        current.update(input("ab"));
+
+        assert_eq!(current.caret, 2);
+        assert_eq!(send(&mut current, WidgetEvent::KeyPress(VirtualKeyCode::Back)), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn accepted_edit_keeps_the_caret_after_the_new_char() {
+        let mut current = input("ab");
+
+        current.set_focused(true);
+        send(&mut current, WidgetEvent::KeyPress(VirtualKeyCode::Home));
+        send(&mut current, WidgetEvent::Char('x'));
+
+        current.update(input("xab"));
+
+        assert_eq!(current.caret, 1);
+        assert_eq!(current.value, "xab");
+    }
+
+    #[test]
+    fn parts_of_another_type_are_left_alone() {
+        let mut part: Element = Label::new(Default::default(), "label".to_string());
+
+        assert!(with_part(&mut part, |_: &mut Button| ()).is_none());
+
+        map_part(&mut part, |button: Box<Button>| button);
+
+        assert_eq!(part.type_name(), "Label");
+        assert_eq!(with_part(&mut part, |label: &mut Label| label.text.clone()), Some("label".to_string()));
+    }
+
+    fn lay_out(widget: &mut dyn Widget, stretch: &mut Stretch, width: f32) {
+        let node = stretch.new_node(
+            Style {
+                size: stretch::geometry::Size {
+                    width: stretch::style::Dimension::Points(width),
+                    height: stretch::style::Dimension::Points(20.0),
+                },
+                ..Default::default()
+            },
+            vec![]
+        ).unwrap();
+
+        stretch.compute_layout(node, stretch::geometry::Size::undefined()).unwrap();
+        widget.update_layout(stretch, &node, point(10.0, 10.0));
+    }
+
+    #[test]
+    fn layout_changes_follow_the_transition() {
+        let mut stretch = Stretch::new();
+        let mut rect = Rect::new(
+            WidgetOptions {
+                transition: Some(Transition::new(100)),
+                ..Default::default()
+            },
+            vec![]
+        );
+
+        // Nothing to animate from on the first layout
+        lay_out(rect.as_mut(), &mut stretch, 40.0);
+
+        assert_eq!(rect.size(), Size::new(40.0, 20.0));
+        assert!(rect.next_tick().is_none());
+
+        lay_out(rect.as_mut(), &mut stretch, 80.0);
+
+        assert_eq!(rect.size(), Size::new(40.0, 20.0));
+        assert!(rect.next_tick().is_some());
+
+        let mut ctx = EventCtx::new();
+
+        rect.on_event(&WidgetEvent::Tick(Instant::now() + Duration::from_millis(200)), &mut ctx);
+
+        assert_eq!(rect.size(), Size::new(80.0, 20.0));
+        assert!(rect.next_tick().is_none());
+    }
+
+    #[test]
+    fn scrolling_moves_an_animated_widget_with_its_parent() {
+        let mut stretch = Stretch::new();
+        let mut label = Label::new(
+            WidgetOptions {
+                transition: Some(Transition::new(100)),
+                ..Default::default()
+            },
+            "label".to_string()
+        );
+
+        lay_out(label.as_mut(), &mut stretch, 40.0);
+
+        label.set_position(label.position() + lyon::math::vector(0.0, 5.0));
+
+        assert_eq!(label.position(), point(10.0, 15.0));
+        assert!(label.next_tick().is_none());
+    }
+}
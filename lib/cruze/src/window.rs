@@ -1,7 +1,17 @@
-use super::app;
+use super::application::{
+    Application,
+    AnyApplication,
+};
+use super::renderer;
 use super::render_gl;
 use super::layout_manager;
 use super::font_manager;
+use super::clipboard::Clipboard;
+use super::shortcut::{
+    self,
+    Chord,
+    Shortcuts,
+};
 
 use super::canvas::{
     Size,
@@ -9,19 +19,39 @@ use super::canvas::{
 };
 
 use super::widgets::{
-    Orientation,
-    Alignment,
-    Rect,
-    Label,
+    self,
+    Change,
+    Element,
+    EventCtx,
+    ScrollDelta,
     Widget,
-    WidgetOptions
+    WidgetEvent,
 };
 
-use glutin::window::{WindowBuilder, WindowId};
+use lyon::math::{
+    point,
+    vector,
+    Point,
+};
+
+use glutin::window::{Icon, WindowBuilder, WindowId};
 use glutin::event::{
     VirtualKeyCode,
     ElementState,
-    MouseButton
+    ModifiersState,
+    MouseButton,
+    MouseScrollDelta,
+    TouchPhase,
+};
+
+use glutin::event_loop::EventLoop;
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{
+    Duration,
+    Instant,
 };
 
 use glutin::{
@@ -30,35 +60,205 @@ use glutin::{
     PossiblyCurrent
 };
 
+// Ticks are not delivered more often than this
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+// How a window looks and behaves when it is created,
+// sizes and positions are logical pixels
+#[derive(Clone)]
+pub struct WindowOptions {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+    pub position: Option<(i32, i32)>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub always_on_top: bool,
+    // The clear color alpha is what shows through
+    pub transparent: bool,
+    // On the monitor the window is created on
+    pub fullscreen: bool,
+    pub maximized: bool,
+    // Set through `with_icon`, which checks the image
+    pub icon: Option<Icon>,
+    // Antialiasing of the offscreen framebuffer, 0 disables it
+    pub samples: u16,
+    pub vsync: bool,
+    pub clear_color: Color,
+}
+
+impl WindowOptions {
+    pub fn new(title: &str) -> WindowOptions {
+        WindowOptions {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> WindowOptions {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_min_size(mut self, min_size: Option<(u32, u32)>) -> WindowOptions {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: Option<(u32, u32)>) -> WindowOptions {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_position(mut self, x: i32, y: i32) -> WindowOptions {
+        self.position = Some((x, y));
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> WindowOptions {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_decorations(mut self, decorations: bool) -> WindowOptions {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn with_always_on_top(mut self, always_on_top: bool) -> WindowOptions {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    pub fn with_transparent(mut self, transparent: bool) -> WindowOptions {
+        self.transparent = transparent;
+        self
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> WindowOptions {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn with_maximized(mut self, maximized: bool) -> WindowOptions {
+        self.maximized = maximized;
+        self
+    }
+
+    pub fn with_icon(mut self, icon: image::RgbaImage) -> Result<WindowOptions, String> {
+        let (width, height) = icon.dimensions();
+        let icon = Icon::from_rgba(icon.into_raw(), width, height)
+            .map_err(|e| format!("Invalid window icon: {}", e))?;
+
+        self.icon = Some(icon);
+
+        Ok(self)
+    }
+
+    // Any format the `image` crate can decode
+    pub fn with_icon_file(self, path: &str) -> Result<WindowOptions, String> {
+        let icon = image::open(path).map_err(|e| e.to_string())?;
+
+        self.with_icon(icon.to_rgba())
+    }
+
+    pub fn with_samples(mut self, samples: u16) -> WindowOptions {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_vsync(mut self, vsync: bool) -> WindowOptions {
+        self.vsync = vsync;
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: Color) -> WindowOptions {
+        self.clear_color = clear_color;
+        self
+    }
+
+    fn window_builder(&self) -> WindowBuilder {
+        let logical_size = |(width, height): (u32, u32)| glutin::dpi::LogicalSize {
+            width: width as f64,
+            height: height as f64
+        };
+
+        let mut wb = WindowBuilder::new()
+            .with_title(self.title.clone())
+            .with_inner_size(logical_size((self.width, self.height)))
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_always_on_top(self.always_on_top)
+            .with_transparent(self.transparent)
+            .with_maximized(self.maximized)
+            .with_window_icon(self.icon.clone());
+
+        if let Some(min_size) = self.min_size {
+            wb = wb.with_min_inner_size(logical_size(min_size));
+        }
+
+        if let Some(max_size) = self.max_size {
+            wb = wb.with_max_inner_size(logical_size(max_size));
+        }
+
+        wb
+    }
+}
+
+impl Default for WindowOptions {
+    fn default() -> WindowOptions {
+        WindowOptions {
+            title: "cruze".to_string(),
+            width: 800,
+            height: 600,
+            min_size: Some((300, 300)),
+            max_size: None,
+            position: None,
+            resizable: true,
+            decorations: true,
+            always_on_top: false,
+            transparent: false,
+            fullscreen: false,
+            maximized: false,
+            icon: None,
+            samples: 8,
+            vsync: true,
+            clear_color: Color::from_rgba(0.3, 0.3, 0.5, 0.1),
+        }
+    }
+}
+
+// Fields are dropped in order: the GL resources owned by the
+// renderer must go before the context they belong to
 pub struct Window {
     pub id: WindowId,
-    pub context: ContextWrapper<PossiblyCurrent, glutin::window::Window>,
+    renderer: renderer::Renderer<render_gl::GlBackend>,
     pub children: Vec<Box<dyn Widget>>,
+    title: String,
     mouse_x: f64,
     mouse_y: f64,
     size: glutin::dpi::LogicalSize,
-    renderer: render_gl::Renderer,
     layout: layout_manager::LayoutBuilder,
     font_manager: font_manager::FontManager,
+    application: Option<Box<dyn AnyApplication>>,
+    last_tick: Instant,
+    // As of the last key press
+    modifiers: ModifiersState,
+    // Shared with the other windows of the app
+    clipboard: Rc<RefCell<Clipboard>>,
+    shortcuts: Rc<RefCell<Shortcuts>>,
+    // Only None while it is being made current
+    context: Option<ContextWrapper<PossiblyCurrent, glutin::window::Window>>,
 }
 
 impl Window {
-    pub fn new(app: &mut app::App, width: u32, height: u32, title: &'static str) -> Window {
-        let wb = WindowBuilder::new()
-            .with_title(title)
-            .with_inner_size(glutin::dpi::LogicalSize {
-                width: width as f64,
-                height: height as f64
-            })
-            .with_min_inner_size(glutin::dpi::LogicalSize {
-                width: 300 as f64,
-                height: 300 as f64
-            });
-
+    pub fn new<T>(el: &EventLoop<T>, options: WindowOptions, root: Element) -> Window {
         let context = ContextBuilder::new()
-            .with_multisampling(8)
-            .with_vsync(true)
-            .build_windowed(wb, &app.el)
+            .with_vsync(options.vsync)
+            .build_windowed(options.window_builder(), el)
             .unwrap();
 
         let context = unsafe {
@@ -67,162 +267,423 @@ impl Window {
                 .unwrap()
         };
 
+        if let Some((x, y)) = options.position {
+            context.window().set_outer_position(glutin::dpi::LogicalPosition {
+                x: x as f64,
+                y: y as f64
+            });
+        }
+
+        if options.fullscreen {
+            let monitor = context.window().current_monitor();
+
+            context.window().set_fullscreen(Some(monitor));
+        }
+
         let window_id = context.window().id();
 
         let gl = gl::Gl::load(&context.context());
 
         let window_size = glutin::dpi::LogicalSize {
-            width: width as f64,
-            height: height as f64
+            width: options.width as f64,
+            height: options.height as f64
         };
 
-        let renderer = render_gl::Renderer::new(&gl);
+        let mut renderer = renderer::Renderer::new(render_gl::GlBackend::new(&gl, options.samples));
         let layout = layout_manager::LayoutBuilder::new();
         let font_manager = font_manager::FontManager::new();
 
+        renderer.set_clear_color(options.clear_color);
+
         let mut window = Window {
             children: vec![],
+            title: options.title,
             size: window_size,
             renderer: renderer,
             font_manager: font_manager,
             layout: layout,
-            context: context,
+            context: Some(context),
             id: window_id,
             mouse_x: 0.0,
             mouse_y: 0.0,
+            application: None,
+            last_tick: Instant::now(),
+            modifiers: ModifiersState::default(),
+            clipboard: Rc::new(RefCell::new(Clipboard::in_memory())),
+            shortcuts: Rc::new(RefCell::new(Shortcuts::new())),
         };
 
-        window.generate_content();
+        window.set_root(root);
 
         window
     }
 
-    pub fn generate_content(&mut self) {
-        self.children.push(
-            Rect::new(
-                WidgetOptions {
-                    id: "main_cont".to_string(),
-                    orientation: Orientation::Column,
-                    ..Default::default()
-                },
-                vec![
-                    Rect::new(
-                        WidgetOptions {
-                            id: "top_bar".to_string(),
-                            height: stretch::style::Dimension::Points(60.0),
-                            horizontal_align: Alignment::Center,
-                            vertical_align: Alignment::Center,
-                            color: Color::from_rgb(0.1, 0.1, 0.1),
-                            ..Default::default()
-                        },
-                        vec![
-                            Label::new(
-                                WidgetOptions {
-                                    font_size: 16.0,
-                                    ..Default::default()
-                                },
-                                "Top Bar".to_string()
-                            )
-                        ]
-                    ),
-                    Rect::new(
-                        WidgetOptions {
-                            id: "main_content".to_string(),
-                            flex: 1.0,
-                            color: Color::from_rgb(0.9, 0.9, 0.9),
-                            ..Default::default()
-                        },
-                        vec![
-                            Rect::new(
-                                WidgetOptions {
-                                    padding: WidgetOptions::uniform_padding(5.0),
-                                    id: "left_side".to_string(),
-                                    color: Color::from_rgb(0.7, 0.7, 0.7),
-                                    width: stretch::style::Dimension::Points(250.0),
-                                    vertical_align: Alignment::Center,
-                                    horizontal_align: Alignment::Center,
-                                    ..Default::default()
-                                },
-                                vec![
-                                    Label::new(
-                                        WidgetOptions {
-                                            font_size: 21.0,
-                                            ..Default::default()
-                                        },
-                                        "Left Side".to_string(),
-                                    ),
-                                ]
-                            ),
-                            Rect::new(
-                                WidgetOptions {
-                                    id: "canvas".to_string(),
-                                    flex: 1.0,
-                                    vertical_align: Alignment::Center,
-                                    horizontal_align: Alignment::Center,
-                                    ..Default::default()
-                                },
-                                vec![
-                                    Label::new(
-                                        WidgetOptions {
-                                            font_size: 21.0,
-                                            color: Color::from_rgb(0.0, 0.0, 0.0),
-                                            ..Default::default()
-                                        },
-                                        "Canvas".to_string(),
-                                    )
-                                ]
-                            ),
-                            Rect::new(
-                                WidgetOptions {
-                                    padding: WidgetOptions::uniform_padding(5.0),
-                                    id: "right_side".to_string(),
-                                    color: Color::from_rgb(0.7, 0.7, 0.7),
-                                    width: stretch::style::Dimension::Points(250.0),
-                                    vertical_align: Alignment::Center,
-                                    horizontal_align: Alignment::Center,
-                                    ..Default::default()
-                                },
-                                vec![
-                                    Label::new(
-                                        WidgetOptions {
-                                            font_size: 21.0,
-                                            vertical_align: Alignment::Center,
-                                            horizontal_align: Alignment::Center,
-                                            ..Default::default()
-                                        },
-                                        "Right Side".to_string(),
-                                    )
-                                ]
-                            ),
-                        ]
-                    ),
-                    Rect::new(
-                        WidgetOptions {
-                            id: "bottom_bar".to_string(),
-                            height: stretch::style::Dimension::Points(60.0),
-                            horizontal_align: Alignment::Center,
-                            vertical_align: Alignment::Center,
-                            color: Color::from_rgb(0.1, 0.1, 0.1),
-                            ..Default::default()
-                        },
-                        vec![
-                            Label::new(
-                                WidgetOptions {
-                                    font_size: 16.0,
-                                    ..Default::default()
-                                },
-                                "Bottom Bar".to_string()
-                            )
-                        ]
-                    )
-                ]
-            )
-        );
-
-        self.renderer.resize(self.size, &self.children, &mut self.font_manager);
-    }
-
-    pub fn draw(&mut self) {
+    pub fn context(&self) -> &ContextWrapper<PossiblyCurrent, glutin::window::Window> {
+        self.context.as_ref().unwrap()
+    }
+
+    // Every window has its own context, it must be made
+    // current before touching any of its GL resources
+    fn make_current(&mut self) -> Result<(), String> {
+        let context = self.context.take().unwrap();
+
+        if context.is_current() {
+            self.context = Some(context);
+
+            return Ok(());
+        }
+
+        match unsafe { context.make_current() } {
+            Ok(context) => {
+                self.context = Some(context);
+
+                Ok(())
+            },
+            Err((context, e)) => {
+                self.context = Some(context);
+
+                Err(format!("Cannot make the GL context of {} current: {}", self.title, e))
+            }
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+
+        self.context().window().set_title(title);
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: Color) {
+        self.renderer.set_clear_color(clear_color);
+
+        self.context().window().request_redraw();
+    }
+
+    // Throws the current widgets away, state included,
+    // use `update_view` to keep what did not change
+    pub fn set_root(&mut self, root: Element) {
+        self.children = vec![root];
+
+        self.layout.build(self.size, &mut self.children, &mut self.font_manager);
+
+        self.refresh();
+    }
+
+    // From now on the content of the window is
+    // the view of `application`
+    pub fn set_application<A: Application>(&mut self, application: A) {
+        self.set_any_application(Box::new(application));
+    }
+
+    // Reconciles the widgets on screen with the tree returned by
+    // a view function, layout is computed again only if needed
+    pub fn update_view(&mut self, view: Element) {
+        self.update_layers(vec![view]);
+    }
+
+    // Same as `update_view`, with overlays (dialogs) stacked on
+    // top of the view. Below the topmost modal layer widgets
+    // get no input.
+    pub fn update_layers(&mut self, layers: Vec<Element>) {
+        let modal = topmost_modal(&self.children);
+        let change = widgets::reconcile(&mut self.children, layers);
+
+        if change == Change::Layout {
+            self.layout.build(self.size, &mut self.children, &mut self.font_manager);
+        }
+
+        // Whatever is under a dialog that just opened
+        // must not stay hovered or focused
+        self.update_input_layers();
+
+        // The focus moves into the dialog, as if Tab was pressed
+        let opened = match (modal, topmost_modal(&self.children)) {
+            (Some(before), Some(after)) => after > before,
+            (None, after) => after.is_some(),
+            _ => false,
+        };
+
+        if opened {
+            self.focus_first();
+        }
+
+        self.refresh();
+    }
+
+    fn focus_first(&mut self) {
+        let (_, active) = input_layers(&mut self.children);
+
+        if let Some(target) = tab_order(active).first().cloned() {
+            for (index, child) in active.iter_mut().enumerate() {
+                set_focus(child, &mut vec![index], &target);
+            }
+        }
+    }
+
+    // Hover follows the cursor on the layers that get input,
+    // the ones blocked by a modal layer are reset
+    fn update_input_layers(&mut self) {
+        let position = self.cursor_position();
+        let (blocked, active) = input_layers(&mut self.children);
+
+        for child in blocked.iter_mut() {
+            clear_input_state(child);
+        }
+
+        // Nothing is hovered under a popup
+        let popup = popup_at(active, position);
+
+        for child in active.iter_mut() {
+            update_hover(child, if popup.is_some() { None } else { Some(position) });
+        }
+
+        if let Some(popup) = popup {
+            update_hover(widget_at(active, &popup), Some(position));
+        }
+    }
+
+    // Re-tessellates dirty widgets, asking
+    // for a redraw if anything changed
+    fn refresh(&mut self) {
+        // Left to `draw`, which reports the error
+        if self.make_current().is_err() {
+            self.context().window().request_redraw();
+
+            return;
+        }
+
+        self.renderer.update(&mut self.children, &mut self.font_manager);
+
+        if self.renderer.needs_redraw() {
+            self.context().window().request_redraw();
+        }
+    }
+
+    // Whether a widget, or focus navigation, used the event
+    fn dispatch(&mut self, event: WidgetEvent) -> bool {
+        let mut ctx = EventCtx::new()
+            .with_modifiers(self.modifiers)
+            .with_clipboard(self.clipboard.clone())
+            .with_shortcuts(self.shortcuts.clone());
+
+        match event {
+            WidgetEvent::KeyPress(_) | WidgetEvent::Char(_) => {
+                let (_, active) = input_layers(&mut self.children);
+
+                bubble_key_event(active, &event, &mut ctx);
+            },
+            WidgetEvent::Scroll(position, _) => {
+                let (_, active) = input_layers(&mut self.children);
+
+                match popup_at(active, position) {
+                    Some(popup) => bubble_up(active, &popup, &event, &mut ctx),
+                    None => bubble_pointer_events(active, position, &event, &mut ctx),
+                }
+            },
+            WidgetEvent::ContextMenu(position) => {
+                let (_, active) = input_layers(&mut self.children);
+
+                // An open popup gets it wherever it is, to close
+                match open_popups(active).pop() {
+                    Some(popup) => bubble_up(active, &popup, &event, &mut ctx),
+                    None => bubble_pointer_events(active, position, &event, &mut ctx),
+                }
+            },
+            WidgetEvent::MousePress(position) => {
+                let (_, active) = input_layers(&mut self.children);
+
+                match popup_at(active, position) {
+                    Some(popup) => {
+                        dispatch_event(widget_at(active, &popup), &event, &mut ctx);
+
+                        if !ctx.is_handled() {
+                            bubble_up(active, &popup[..popup.len() - 1], &event, &mut ctx);
+                        }
+                    },
+                    None => match open_popups(active).pop() {
+                        // A click outside an open popup only
+                        // closes it, nothing under it gets it
+                        Some(popup) => bubble_up(active, &popup, &event, &mut ctx),
+                        None => {
+                            for child in active.iter_mut() {
+                                dispatch_event(child, &event, &mut ctx);
+                            }
+                        }
+                    }
+                }
+            },
+            // Ticks are not input, they go under modal layers too
+            WidgetEvent::Tick(_) => {
+                for child in self.children.iter_mut() {
+                    dispatch_event(child, &event, &mut ctx);
+                }
+            },
+            _ => {
+                let (_, active) = input_layers(&mut self.children);
+
+                for child in active.iter_mut() {
+                    dispatch_event(child, &event, &mut ctx);
+                }
+            }
+        }
+
+        let mut handled = ctx.is_handled();
+
+        if !handled && self.modifiers.alt {
+            if let WidgetEvent::KeyPress(key) = event {
+                if let Some(mnemonic) = shortcut::key_char(key) {
+                    let (_, active) = input_layers(&mut self.children);
+
+                    for child in active.iter_mut() {
+                        dispatch_event(child, &WidgetEvent::Mnemonic(mnemonic), &mut ctx);
+                    }
+
+                    handled = ctx.is_handled();
+                }
+            }
+        }
+
+        // Before navigating, so that a shortcut
+        // on an arrow works in a focus group too
+        if !handled {
+            if let WidgetEvent::KeyPress(key) = event {
+                let chord = Chord {
+                    key,
+                    modifiers: self.modifiers,
+                };
+                let focus = self.focus_ids();
+
+                // Not borrowed while the application
+                // handles it, it may bind shortcuts
+                let message = self.shortcuts.borrow().lookup(chord, self.id, &focus);
+
+                if let Some(message) = message {
+                    ctx.emit(message);
+
+                    handled = true;
+                }
+            }
+        }
+
+        if !handled {
+            if let WidgetEvent::KeyPress(key) = event {
+                handled = self.navigate(key);
+            }
+        }
+
+        if ctx.is_layout_requested() {
+            self.layout.build(self.size, &mut self.children, &mut self.font_manager);
+
+            self.update_input_layers();
+        }
+
+        self.handle_messages(ctx.into_messages());
+
+        handled
+    }
+
+    // What keys nobody handled do: Tab and Shift+Tab go through
+    // the focusable widgets, arrows through the ones of a group
+    fn navigate(&mut self, key: VirtualKeyCode) -> bool {
+        let shift = self.modifiers.shift;
+        let (_, active) = input_layers(&mut self.children);
+        let focused = focused_path(active);
+
+        let (candidates, reverse) = match key {
+            VirtualKeyCode::Tab => (tab_order(active), shift),
+            VirtualKeyCode::Left | VirtualKeyCode::Up | VirtualKeyCode::Right | VirtualKeyCode::Down => {
+                let group = match &focused {
+                    Some(focused) => focus_group(active, focused),
+                    None => None,
+                };
+
+                let group = match group {
+                    Some(group) => group,
+                    None => return false,
+                };
+
+                let mut candidates = vec![];
+
+                collect_focusable(widget_ref_at(active, &group).as_ref(), &mut group.clone(), &mut candidates, true);
+
+                let reverse = key == VirtualKeyCode::Left || key == VirtualKeyCode::Up;
+
+                (candidates, reverse)
+            },
+            _ => return false,
+        };
+
+        if candidates.is_empty() {
+            return false;
+        }
+
+        let current = focused
+            .as_ref()
+            .and_then(|focused| candidates.iter().position(|candidate| candidate == focused));
+
+        let next = match (current, reverse) {
+            (Some(current), false) => (current + 1) % candidates.len(),
+            (Some(current), true) => (current + candidates.len() - 1) % candidates.len(),
+            (None, false) => 0,
+            (None, true) => candidates.len() - 1,
+        };
+
+        let target = candidates[next].clone();
+
+        for (index, child) in active.iter_mut().enumerate() {
+            set_focus(child, &mut vec![index], &target);
+        }
+
+        true
+    }
+
+    // Hands messages, from widgets or timers, to the application,
+    // the view is then reconciled with the widgets on screen
+    fn handle_messages(&mut self, messages: Vec<Box<dyn Any>>) {
+        match &mut self.application {
+            Some(application) if !messages.is_empty() => {
+                for message in messages {
+                    application.update(message);
+                }
+
+                let layers = application_layers(application.as_ref());
+
+                self.update_layers(layers);
+            },
+            _ => self.refresh(),
+        }
+    }
+
+    pub(crate) fn send_message(&mut self, message: Box<dyn Any>) {
+        self.handle_messages(vec![message]);
+    }
+
+    // When the widgets want the next `Tick`, if they do
+    pub fn next_tick(&self) -> Option<Instant> {
+        let mut next_tick = None;
+
+        for child in self.children.iter() {
+            widget_next_tick(child.as_ref(), &mut next_tick);
+        }
+
+        next_tick.map(|tick: Instant| tick.max(self.last_tick + FRAME_INTERVAL))
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        self.last_tick = now;
+
+        self.dispatch(WidgetEvent::Tick(now));
+    }
+
+    // Fails when the context cannot be used anymore,
+    // the window cannot be drawn then
+    pub fn draw(&mut self) -> Result<(), String> {
+        self.make_current()?;
+
         let _start_time = std::time::Instant::now();
 
         self.renderer.draw();
@@ -231,48 +692,440 @@ impl Window {
 
         let _start_time = std::time::Instant::now();
 
-        self.context
+        self.context()
             .swap_buffers()
-            .unwrap();
+            .map_err(|e| format!("Cannot swap the buffers of {}: {}", self.title, e))?;
 
         //println!("Frame render (buffer swap): {}", _start_time.elapsed().as_micros());
+
+        Ok(())
     }
 
-    pub fn resize(&mut self, size: glutin::dpi::LogicalSize) {
+    pub fn resize(&mut self, size: glutin::dpi::LogicalSize) -> Result<(), String> {
         self.size = size;
 
         self.layout.build(self.size, &mut self.children, &mut self.font_manager);
 
-        self.renderer.resize(size, &self.children, &mut self.font_manager);
-    }
-
-    pub fn send_key(&mut self, key: VirtualKeyCode) {
-        match key {
-            VirtualKeyCode::Left |
-                VirtualKeyCode::Right |
-                VirtualKeyCode::Up |
-                VirtualKeyCode::Down |
-                VirtualKeyCode::R |
-                VirtualKeyCode::D |
-                VirtualKeyCode::S |
-                VirtualKeyCode::X
-                => (),//self.renderer.send_key(key),
-            _ => println!("Do nothing")
+        self.make_current()?;
+        self.renderer.resize(size, &mut self.children, &mut self.font_manager);
+
+        Ok(())
+    }
+
+    // Returns whether the key was used, by a widget,
+    // a shortcut or to move the focus
+    pub fn send_key(&mut self, key: VirtualKeyCode, modifiers: ModifiersState) -> bool {
+        self.modifiers = modifiers;
+
+        self.dispatch(WidgetEvent::KeyPress(key))
+    }
+
+    pub fn send_char(&mut self, c: char) {
+        self.dispatch(WidgetEvent::Char(c));
+    }
+
+    // Modifiers also come with mouse events and key releases
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub(crate) fn set_clipboard(&mut self, clipboard: Rc<RefCell<Clipboard>>) {
+        self.clipboard = clipboard;
+    }
+
+    pub(crate) fn set_shortcuts(&mut self, shortcuts: Rc<RefCell<Shortcuts>>) {
+        self.shortcuts = shortcuts;
+    }
+
+    // Ids of the focused widget and of its ancestors, innermost
+    // first, anonymous ones left out
+    pub fn focus_ids(&mut self) -> Vec<String> {
+        let (_, active) = input_layers(&mut self.children);
+
+        let path = match focused_path(active) {
+            Some(path) => path,
+            None => return vec![],
         };
 
-        self.draw();
+        (1..=path.len())
+            .rev()
+            .map(|depth| widget_ref_at(active, &path[..depth]).options().id.clone())
+            .filter(|id| id != widgets::ANONYMOUS_ID)
+            .collect()
     }
 
     pub fn send_mouse_input(&mut self, state: ElementState, button: MouseButton) {
-        println!("Button: {:?} has been {:?} at [{}, {}]", button, state, self.mouse_x, self.mouse_y);
+        let position = self.cursor_position();
+
+        match (button, state) {
+            (MouseButton::Left, ElementState::Pressed) => {
+                // Clicking moves the focus, or removes it,
+                // unless a popup is open: the click is
+                // on it, or only closes it
+                let (_, active) = input_layers(&mut self.children);
+
+                if open_popups(active).is_empty() {
+                    for child in active.iter_mut() {
+                        update_focus(child.as_mut(), Some(position));
+                    }
+                }
+
+                self.dispatch(WidgetEvent::MousePress(position));
+            },
+            (MouseButton::Left, ElementState::Released) => {
+                self.dispatch(WidgetEvent::MouseRelease(position));
+            },
+            (MouseButton::Right, ElementState::Pressed) => {
+                self.dispatch(WidgetEvent::ContextMenu(position));
+            },
+            _ => ()
+        }
     }
 
     pub fn set_cursor_position(&mut self,x: f64, y: f64) {
         self.mouse_x = x;
         self.mouse_y = y;
+
+        self.update_input_layers();
+
+        self.dispatch(WidgetEvent::MouseMove(self.cursor_position()));
+    }
+
+    pub fn send_scroll(&mut self, delta: MouseScrollDelta, phase: TouchPhase) {
+        let position = self.cursor_position();
+
+        let delta = match delta {
+            MouseScrollDelta::LineDelta(x, y) => ScrollDelta::Lines(vector(x, y)),
+            MouseScrollDelta::PixelDelta(delta) => ScrollDelta::Pixels(vector(delta.x as f32, delta.y as f32)),
+        };
+
+        match phase {
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.dispatch(WidgetEvent::ScrollEnd(position));
+            },
+            _ => {
+                self.dispatch(WidgetEvent::Scroll(position, delta));
+            }
+        }
+    }
+
+    // Layout is y-down from the bottom of the window
+    fn cursor_position(&self) -> Point {
+        point(self.mouse_x as f32, (self.size.height - self.mouse_y) as f32)
     }
 
     pub fn get_size(&self) -> glutin::dpi::LogicalSize {
         self.size
     }
+
+    // Used by `AppHandle`, where the type of the application is gone
+    pub(crate) fn set_any_application(&mut self, application: Box<dyn AnyApplication>) {
+        let layers = application_layers(application.as_ref());
+
+        self.application = Some(application);
+        self.update_layers(layers);
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        // The GL resources go anyway, there is
+        // nothing to do if they cannot be freed
+        let _ = self.make_current();
+    }
+}
+
+fn application_layers(application: &dyn AnyApplication) -> Vec<Element> {
+    let mut layers = vec![application.view()];
+
+    layers.extend(application.overlays());
+
+    layers
+}
+
+fn topmost_modal(children: &[Element]) -> Option<usize> {
+    children.iter().rposition(|child| child.is_modal())
+}
+
+// Splits the layers of a window in the ones blocked by the
+// topmost modal layer and the ones that get input
+fn input_layers(children: &mut Vec<Element>) -> (&mut [Element], &mut [Element]) {
+    let first_active = topmost_modal(children).unwrap_or(0);
+
+    children.split_at_mut(first_active)
+}
+
+fn widget_next_tick(widget: &dyn Widget, next_tick: &mut Option<Instant>) {
+    if let Some(tick) = widget.next_tick() {
+        *next_tick = Some(next_tick.map_or(tick, |next_tick| next_tick.min(tick)));
+    }
+
+    for child in widget.children().iter() {
+        widget_next_tick(child.as_ref(), next_tick);
+    }
+}
+
+fn clear_input_state(widget: &mut Box<dyn Widget>) {
+    widget.set_hovered(false);
+    widget.set_focused(false);
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            clear_input_state(child);
+        }
+    }
+}
+
+// `position` is None where the widget is clipped away
+fn update_focus(widget: &mut dyn Widget, position: Option<Point>) {
+    widget.set_focused(widget.is_focusable() && position.is_some_and(|position| widget.contains(position)));
+
+    let position = position.filter(|position| in_clip(widget, *position));
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            update_focus(child.as_mut(), position);
+        }
+    }
+}
+
+// Whether the children of `widget` can be hit at `position`
+fn in_clip(widget: &dyn Widget, position: Point) -> bool {
+    widget.clip().is_none_or(|clip| clip.contains(position))
+}
+
+fn dispatch_event(widget: &mut Box<dyn Widget>, event: &WidgetEvent, ctx: &mut EventCtx) {
+    let handled = ctx.is_handled();
+
+    widget.on_event(event, ctx);
+
+    // A press clipped away, or taken by the widget (on its
+    // scrollbar for instance), does not reach the children
+    if let WidgetEvent::MousePress(position) = event {
+        if (!handled && ctx.is_handled()) || !in_clip(widget.as_ref(), *position) {
+            return;
+        }
+    }
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            dispatch_event(child, event, ctx);
+        }
+    }
+}
+
+// Widgets are found through their path: the index of their layer,
+// then the index of each ancestor among its siblings
+fn widget_at<'a>(layers: &'a mut [Element], path: &[usize]) -> &'a mut Element {
+    let mut widget = &mut layers[path[0]];
+
+    for index in path[1..].iter() {
+        widget = &mut widget.children_mut().unwrap()[*index];
+    }
+
+    widget
+}
+
+fn widget_ref_at<'a>(layers: &'a [Element], path: &[usize]) -> &'a Element {
+    let mut widget = &layers[path[0]];
+
+    for index in path[1..].iter() {
+        widget = &widget.children()[*index];
+    }
+
+    widget
+}
+
+fn find_focused(widget: &dyn Widget, path: &mut Vec<usize>) -> bool {
+    if widget.is_focused() {
+        return true;
+    }
+
+    for (index, child) in widget.children().iter().enumerate() {
+        path.push(index);
+
+        if find_focused(child.as_ref(), path) {
+            return true;
+        }
+
+        path.pop();
+    }
+
+    false
+}
+
+fn focused_path(layers: &[Element]) -> Option<Vec<usize>> {
+    // The topmost layer first, should more than one have focus
+    for (index, layer) in layers.iter().enumerate().rev() {
+        let mut path = vec![index];
+
+        if find_focused(layer.as_ref(), &mut path) {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+// Focused widget first, then its ancestors. An open
+// popup comes before the focused widget.
+fn bubble_key_event(layers: &mut [Element], event: &WidgetEvent, ctx: &mut EventCtx) {
+    if layers.is_empty() {
+        return;
+    }
+
+    let path = open_popups(layers)
+        .pop()
+        .or_else(|| focused_path(layers))
+        .unwrap_or(vec![layers.len() - 1]);
+
+    bubble_up(layers, &path, event, ctx);
+}
+
+// The widget at `path`, then its ancestors, until one
+// of them handles the event
+fn bubble_up(layers: &mut [Element], path: &[usize], event: &WidgetEvent, ctx: &mut EventCtx) {
+    for depth in (1..=path.len()).rev() {
+        widget_at(layers, &path[..depth]).on_event(event, ctx);
+
+        if ctx.is_handled() {
+            break;
+        }
+    }
+}
+
+fn find_popups(widget: &dyn Widget, path: &mut Vec<usize>, popups: &mut Vec<Vec<usize>>) {
+    if widget.is_popup() {
+        popups.push(path.clone());
+    }
+
+    for (index, child) in widget.children().iter().enumerate() {
+        path.push(index);
+
+        find_popups(child.as_ref(), path, popups);
+
+        path.pop();
+    }
+}
+
+// Paths of the open popups, in the order they are drawn
+fn open_popups(layers: &[Element]) -> Vec<Vec<usize>> {
+    let mut popups = vec![];
+
+    for (index, layer) in layers.iter().enumerate() {
+        find_popups(layer.as_ref(), &mut vec![index], &mut popups);
+    }
+
+    popups
+}
+
+// The topmost open popup at `position`
+fn popup_at(layers: &[Element], position: Point) -> Option<Vec<usize>> {
+    open_popups(layers)
+        .into_iter()
+        .rev()
+        .find(|popup| widget_ref_at(layers, popup).contains(position))
+}
+
+// `all` includes the widgets with a negative `tab_index`
+fn collect_focusable(widget: &dyn Widget, path: &mut Vec<usize>, focusable: &mut Vec<Vec<usize>>, all: bool) {
+    if widget.is_focusable() && (all || widget.options().tab_index >= 0) {
+        focusable.push(path.clone());
+    }
+
+    for (index, child) in widget.children().iter().enumerate() {
+        path.push(index);
+
+        collect_focusable(child.as_ref(), path, focusable, all);
+
+        path.pop();
+    }
+}
+
+fn tab_order(layers: &[Element]) -> Vec<Vec<usize>> {
+    let mut focusable = vec![];
+
+    for (index, layer) in layers.iter().enumerate() {
+        collect_focusable(layer.as_ref(), &mut vec![index], &mut focusable, false);
+    }
+
+    // Stable, so that equal indexes stay in tree order
+    focusable.sort_by_key(|path| {
+        let tab_index = widget_ref_at(layers, path).options().tab_index;
+
+        (tab_index == 0, tab_index)
+    });
+
+    focusable
+}
+
+// The innermost focus group around the widget at `path`
+fn focus_group(layers: &[Element], path: &[usize]) -> Option<Vec<usize>> {
+    (1..path.len())
+        .rev()
+        .map(|depth| &path[..depth])
+        .find(|ancestor| widget_ref_at(layers, ancestor).options().focus_group)
+        .map(|ancestor| ancestor.to_vec())
+}
+
+fn set_focus(widget: &mut Box<dyn Widget>, path: &mut Vec<usize>, target: &[usize]) {
+    widget.set_focused(path[..] == target[..]);
+
+    if let Some(children) = widget.children_mut() {
+        for (index, child) in children.iter_mut().enumerate() {
+            path.push(index);
+
+            set_focus(child, path, target);
+
+            path.pop();
+        }
+    }
+}
+
+// The topmost widgets under the cursor first, then their
+// ancestors, until one of them handles the event
+fn bubble_pointer_event(widget: &mut Box<dyn Widget>, position: Point, event: &WidgetEvent, ctx: &mut EventCtx) {
+    if in_clip(widget.as_ref(), position) {
+        if let Some(children) = widget.children_mut() {
+            for child in children.iter_mut().rev() {
+                bubble_pointer_event(child, position, event, ctx);
+
+                if ctx.is_handled() {
+                    return;
+                }
+            }
+        }
+    }
+
+    if widget.contains(position) {
+        widget.on_event(event, ctx);
+    }
+}
+
+// The topmost layer first
+fn bubble_pointer_events(layers: &mut [Element], position: Point, event: &WidgetEvent, ctx: &mut EventCtx) {
+    for child in layers.iter_mut().rev() {
+        bubble_pointer_event(child, position, event, ctx);
+
+        if ctx.is_handled() {
+            break;
+        }
+    }
+}
+
+// `position` is None where the widget is clipped away
+fn update_hover(widget: &mut Box<dyn Widget>, position: Option<Point>) {
+    widget.set_hovered(position.is_some_and(|position| widget.contains(position)));
+
+    let position = position.filter(|position| in_clip(widget.as_ref(), *position));
+
+    if let Some(children) = widget.children_mut() {
+        for child in children.iter_mut() {
+            update_hover(child, position);
+        }
+    }
 }
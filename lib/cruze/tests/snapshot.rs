@@ -0,0 +1,97 @@
+extern crate cruze;
+
+use cruze::canvas::Color;
+use cruze::testing::Snapshot;
+use cruze::widgets::*;
+
+// A small version of the demo window: a top bar
+// over two side panels around the canvas
+fn side_panels() -> Vec<Box<dyn Widget>> {
+    vec![
+        Rect::new(
+            WidgetOptions {
+                id: "main_cont".to_string(),
+                orientation: Orientation::Column,
+                ..Default::default()
+            },
+            vec![
+                Rect::new(
+                    WidgetOptions {
+                        id: "top_bar".to_string(),
+                        height: stretch::style::Dimension::Points(30.0),
+                        horizontal_align: Alignment::Center,
+                        vertical_align: Alignment::Center,
+                        color: Color::from_rgb(0.1, 0.1, 0.1),
+                        ..Default::default()
+                    },
+                    vec![
+                        Label::new(
+                            WidgetOptions {
+                                font_size: 16.0,
+                                ..Default::default()
+                            },
+                            "Top Bar".to_string()
+                        )
+                    ]
+                ),
+                Rect::new(
+                    WidgetOptions {
+                        id: "main_content".to_string(),
+                        flex: 1.0,
+                        color: Color::from_rgb(0.9, 0.9, 0.9),
+                        ..Default::default()
+                    },
+                    vec![
+                        Rect::new(
+                            WidgetOptions {
+                                id: "left_side".to_string(),
+                                color: Color::from_rgb(0.7, 0.7, 0.7),
+                                width: stretch::style::Dimension::Points(60.0),
+                                ..Default::default()
+                            },
+                            vec![]
+                        ),
+                        Rect::new(
+                            WidgetOptions {
+                                id: "canvas".to_string(),
+                                flex: 1.0,
+                                padding: WidgetOptions::uniform_padding(20.0),
+                                ..Default::default()
+                            },
+                            vec![
+                                Rect::new(
+                                    WidgetOptions {
+                                        color: Color::from_rgb(0.2, 0.4, 0.8),
+                                        radius: 10.0,
+                                        flex: 1.0,
+                                        ..Default::default()
+                                    },
+                                    vec![]
+                                )
+                            ]
+                        ),
+                        Rect::new(
+                            WidgetOptions {
+                                id: "right_side".to_string(),
+                                color: Color::from_rgb(0.7, 0.7, 0.7),
+                                width: stretch::style::Dimension::Points(60.0),
+                                ..Default::default()
+                            },
+                            vec![]
+                        ),
+                    ]
+                ),
+            ]
+        )
+    ]
+}
+
+#[test]
+fn side_panels_layout() {
+    Snapshot::new(240, 160).assert_layout("side_panels", side_panels());
+}
+
+#[test]
+fn side_panels_render() {
+    Snapshot::new(240, 160).assert_matches("side_panels", side_panels());
+}
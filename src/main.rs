@@ -1,21 +1,185 @@
 extern crate cruze;
 
 use cruze::{window, app};
+use cruze::animation::Transition;
+use cruze::application::Application;
+use cruze::dialog;
+use cruze::shortcut::{
+    Chord,
+    ShortcutScope,
+};
+use cruze::canvas::Color;
+use cruze::widgets::{
+    Alignment,
+    Button,
+    Element,
+    Label,
+    Orientation,
+    Rect,
+    TextInput,
+    WidgetOptions,
+};
 
-fn main() {
-    let width: u32 = 800;
-    let height: u32 = 600;
+#[derive(Clone)]
+enum Message {
+    Increment,
+    Decrement,
+    NameChanged(String),
+    AskReset,
+    Reset,
+    CancelReset,
+}
+
+struct Counter {
+    count: i32,
+    name: String,
+    asking_reset: bool,
+}
+
+impl Application for Counter {
+    type Message = Message;
+
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::Increment => self.count += 1,
+            Message::Decrement => self.count -= 1,
+            Message::NameChanged(name) => self.name = name,
+            Message::AskReset => self.asking_reset = true,
+            Message::Reset => {
+                self.count = 0;
+                self.asking_reset = false;
+            },
+            Message::CancelReset => self.asking_reset = false,
+        }
+    }
+
+    fn view(&self) -> Element {
+        Rect::new(
+            WidgetOptions {
+                id: "main_cont".to_string(),
+                orientation: Orientation::Column,
+                horizontal_align: Alignment::Center,
+                vertical_align: Alignment::Center,
+                color: Color::from_rgb(0.9, 0.9, 0.9),
+                ..Default::default()
+            },
+            vec![
+                Label::new(
+                    WidgetOptions {
+                        id: "greeting".to_string(),
+                        font_size: 21.0,
+                        color: Color::from_rgb(0.0, 0.0, 0.0),
+                        ..Default::default()
+                    },
+                    format!("{} clicked {} times", self.name, self.count)
+                ),
+                TextInput::new(
+                    WidgetOptions {
+                        id: "name".to_string(),
+                        font_size: 16.0,
+                        radius: 4.0,
+                        margin: WidgetOptions::uniform_padding(10.0),
+                        ..Default::default()
+                    },
+                    self.name.clone()
+                ).on_change(Message::NameChanged),
+                Rect::new(
+                    WidgetOptions {
+                        id: "buttons".to_string(),
+                        flex: 0.0,
+                        color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+                        ..Default::default()
+                    },
+                    vec![
+                        Button::new(
+                            WidgetOptions {
+                                id: "decrement".to_string(),
+                                font_size: 16.0,
+                                radius: 4.0,
+                                margin: WidgetOptions::uniform_padding(5.0),
+                                color: Color::from_rgb(0.3, 0.4, 0.7),
+                                hover_color: Some(Color::from_rgb(0.4, 0.5, 0.8)),
+                                transition: Some(Transition::new(150)),
+                                text_color: Color::from_rgb(1.0, 1.0, 1.0),
+                                ..Default::default()
+                            },
+                            "-1".to_string()
+                        ).on_press(Message::Decrement),
+                        Button::new(
+                            WidgetOptions {
+                                id: "increment".to_string(),
+                                font_size: 16.0,
+                                radius: 4.0,
+                                margin: WidgetOptions::uniform_padding(5.0),
+                                color: Color::from_rgb(0.3, 0.4, 0.7),
+                                hover_color: Some(Color::from_rgb(0.4, 0.5, 0.8)),
+                                transition: Some(Transition::new(150)),
+                                text_color: Color::from_rgb(1.0, 1.0, 1.0),
+                                ..Default::default()
+                            },
+                            "+1".to_string()
+                        ).on_press(Message::Increment),
+                        Button::new(
+                            WidgetOptions {
+                                id: "reset".to_string(),
+                                font_size: 16.0,
+                                radius: 4.0,
+                                margin: WidgetOptions::uniform_padding(5.0),
+                                color: Color::from_rgb(0.7, 0.3, 0.3),
+                                hover_color: Some(Color::from_rgb(0.8, 0.4, 0.4)),
+                                transition: Some(Transition::new(150)),
+                                text_color: Color::from_rgb(1.0, 1.0, 1.0),
+                                ..Default::default()
+                            },
+                            "Reset".to_string()
+                        ).on_press(Message::AskReset),
+                    ]
+                ),
+            ]
+        )
+    }
+
+    fn overlays(&self) -> Vec<Element> {
+        if !self.asking_reset {
+            return vec![];
+        }
 
+        vec![
+            dialog::confirm(
+                "Reset",
+                &format!("Set the counter of {} back to 0?", self.name),
+                Message::Reset,
+                Message::CancelReset
+            )
+        ]
+    }
+}
+
+fn main() {
     let mut app = app::App::new();
 
-    let window = window::Window::new(
-        &mut app,
-        width,
-        height,
-        "Come una cerva anela ai corsi delle acque"
-    );
+    let counter = Counter {
+        count: 0,
+        name: "Nobody".to_string(),
+        asking_reset: false,
+    };
+
+    let options = window::WindowOptions::new("Come una cerva anela ai corsi delle acque")
+        .with_size(800, 600);
+
+    let mut window = window::Window::new(&app.el, options, counter.view());
+
+    window.set_application(counter);
 
     app.add_window(window);
 
-    app.run();
+    let handle = app.handle();
+
+    handle.bind_shortcut("counter.increment", Chord::parse("Ctrl+Up").unwrap(), ShortcutScope::Global, Message::Increment).unwrap();
+    handle.bind_shortcut("counter.decrement", Chord::parse("Ctrl+Down").unwrap(), ShortcutScope::Global, Message::Decrement).unwrap();
+    handle.bind_shortcut("counter.reset", Chord::parse("Ctrl+R").unwrap(), ShortcutScope::Global, Message::AskReset).unwrap();
+
+    if let Err(e) = app.run() {
+        eprintln!("{}", e);
+    }
 }